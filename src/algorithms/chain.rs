@@ -0,0 +1,215 @@
+use std::collections::HashMap;
+
+use crate::algorithms::fast::{Bicubic, Lanczos};
+use crate::algorithms::image::Image;
+use crate::algorithms::medium::ScaleByRules;
+use crate::algorithms::sampling::SamplingConfig;
+use crate::algorithms::upscaler::{Upscaler, UpscaleTier};
+
+/// Stages of a progressive chain, by registry name
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChainSpec {
+    /// Explicit `(algorithm, factor)` stages, applied in order. The last stage
+    /// absorbs whatever is left of the target factor; a `None` factor asks for
+    /// exactly that remainder.
+    Stages(Vec<(String, Option<f32>)>),
+    /// `stage` at 2x while at least 2x remains, then `finisher` for the rest
+    Auto { stage: String, finisher: String },
+}
+
+impl ChainSpec {
+    /// Resolve the `(algorithm, factor)` stages for a final `scale_factor`
+    pub fn plan(&self, scale_factor: f32) -> Vec<(String, f32)> {
+        const EPSILON: f32 = 1e-3;
+        match self {
+            ChainSpec::Auto { stage, finisher } => {
+                let mut stages = Vec::new();
+                let mut remaining = scale_factor;
+                while remaining >= 2.0 - EPSILON {
+                    stages.push((stage.clone(), 2.0));
+                    remaining /= 2.0;
+                }
+                if (remaining - 1.0).abs() > EPSILON || stages.is_empty() {
+                    stages.push((finisher.clone(), remaining));
+                }
+                stages
+            }
+            ChainSpec::Stages(given) => {
+                let Some(((last, _), rest)) = given.split_last() else {
+                    return Vec::new();
+                };
+                let mut stages: Vec<(String, f32)> = rest.iter()
+                    .map(|(name, factor)| (name.clone(), factor.unwrap_or(1.0)))
+                    .collect();
+                let done: f32 = stages.iter().map(|(_, factor)| factor).product();
+                stages.push((last.clone(), scale_factor / done));
+                stages
+            }
+        }
+    }
+
+    /// Every algorithm the spec names
+    fn algorithms(&self) -> Vec<&str> {
+        match self {
+            ChainSpec::Auto { stage, finisher } => vec![stage, finisher],
+            ChainSpec::Stages(given) => given.iter().map(|(name, _)| name.as_str()).collect(),
+        }
+    }
+
+    /// Parse `auto`, `auto:STAGE` or `auto:STAGE,FINISHER` (doubling), or
+    /// `NAME:FACTOR,...,NAME[:FACTOR]` such as `xbr:2,xbr:2,lanczos3`
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.to_lowercase();
+        if s == "auto" {
+            return Some(ChainSpec::Auto { stage: "xbr".to_string(), finisher: "lanczos3".to_string() });
+        }
+        if let Some(names) = s.strip_prefix("auto:") {
+            let (stage, finisher) = names.split_once(',').unwrap_or((names, "lanczos3"));
+            return (!stage.is_empty() && !finisher.is_empty())
+                .then(|| ChainSpec::Auto { stage: stage.to_string(), finisher: finisher.to_string() });
+        }
+
+        let count = s.split(',').count();
+        let stages = s.split(',')
+            .enumerate()
+            .map(|(i, stage)| {
+                let (name, factor) = match stage.split_once(':') {
+                    Some((name, factor)) => (name.trim(), Some(factor.trim().parse::<f32>().ok().filter(|f| *f > 0.0)?)),
+                    // Only the last stage may leave its factor to the remainder
+                    None if i + 1 == count => (stage.trim(), None),
+                    None => return None,
+                };
+                (!name.is_empty()).then(|| (name.to_string(), factor))
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some(ChainSpec::Stages(stages))
+    }
+}
+
+/// Progressive multi-stage upscaling, a different algorithm per stage
+///
+/// Large factors go badly in one pass of any algorithm: kernels blur, pattern
+/// rules only know 2x. A chain applies each stage to the previous stage's
+/// output, e.g. xBR 2x, xBR 2x, then Lanczos for the remaining 3.75x of a
+/// 15x enlargement. Rounding drift between stages is corrected with a final
+/// bicubic resize to the exact target size.
+///
+/// Time complexity: O(sum of stages), dominated by the last
+/// Space complexity: O(n) for the largest intermediate
+pub struct ProgressiveChain {
+    spec: ChainSpec,
+    upscalers: HashMap<String, Box<dyn Upscaler>>,
+}
+
+impl ProgressiveChain {
+    /// Doubling with xBR, finishing with Lanczos3
+    pub fn new() -> Self {
+        let mut upscalers: HashMap<String, Box<dyn Upscaler>> = HashMap::new();
+        upscalers.insert("xbr".to_string(), Box::new(ScaleByRules));
+        upscalers.insert("lanczos3".to_string(), Box::new(Lanczos::new()));
+        Self {
+            spec: ChainSpec::Auto { stage: "xbr".to_string(), finisher: "lanczos3".to_string() },
+            upscalers,
+        }
+    }
+
+    /// Build a chain, looking up every stage's algorithm in the registry
+    pub fn from_spec(spec: ChainSpec) -> Result<Self, String> {
+        if let ChainSpec::Stages(ref given) = spec
+            && given.is_empty()
+        {
+            return Err("A chain needs at least one stage".to_string());
+        }
+
+        let mut upscalers = HashMap::new();
+        for name in spec.algorithms() {
+            if !upscalers.contains_key(name) {
+                let upscaler = crate::get_upscaler(name).ok_or_else(|| format!("Unknown algorithm: {}", name))?;
+                upscalers.insert(name.to_string(), upscaler);
+            }
+        }
+        Ok(Self { spec, upscalers })
+    }
+
+    /// The stages this chain runs for `scale_factor`
+    pub fn plan(&self, scale_factor: f32) -> Vec<(String, f32)> {
+        self.spec.plan(scale_factor)
+    }
+}
+
+impl Upscaler for ProgressiveChain {
+    fn upscale(&self, image: &Image, scale_factor: f32) -> Image {
+        self.upscale_with_sampling(image, scale_factor, &SamplingConfig::default())
+    }
+
+    fn upscale_with_sampling(&self, image: &Image, scale_factor: f32, sampling: &SamplingConfig) -> Image {
+        let target_width = (image.width as f32 * scale_factor).round() as usize;
+        let target_height = (image.height as f32 * scale_factor).round() as usize;
+
+        let mut current = image.clone();
+        for (name, factor) in self.plan(scale_factor) {
+            if let Some(upscaler) = self.upscalers.get(&name) {
+                current = upscaler.upscale_with_sampling(&current, factor, sampling);
+            }
+        }
+
+        if current.width != target_width || current.height != target_height {
+            Bicubic::resize(&current, target_width, target_height)
+        } else {
+            current
+        }
+    }
+
+    fn name(&self) -> &str {
+        "Progressive Chain"
+    }
+
+    fn supports_sampling(&self) -> bool {
+        self.upscalers.values().all(|upscaler| upscaler.supports_sampling())
+    }
+
+    fn tier(&self) -> UpscaleTier {
+        self.upscalers.values().map(|u| u.tier()).max().unwrap_or(UpscaleTier::Fast)
+    }
+}
+
+impl Default for ProgressiveChain {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::image::Pixel;
+
+    #[test]
+    fn test_chain_plans() {
+        let auto = ChainSpec::parse("auto").unwrap();
+        let plan = auto.plan(15.0);
+        assert_eq!(plan.len(), 4);
+        assert!(plan[..3].iter().all(|(name, factor)| name == "xbr" && *factor == 2.0));
+        assert_eq!(plan[3], ("lanczos3".to_string(), 1.875));
+        assert_eq!(auto.plan(4.0).len(), 2);
+        assert_eq!(auto.plan(1.5), vec![("lanczos3".to_string(), 1.5)]);
+
+        let given = ChainSpec::parse("xbr:2,xbr:2,lanczos3").unwrap();
+        assert_eq!(given.plan(15.0)[2], ("lanczos3".to_string(), 3.75));
+        assert_eq!(ChainSpec::parse("auto:edi,bicubic"), Some(ChainSpec::Auto { stage: "edi".to_string(), finisher: "bicubic".to_string() }));
+        assert_eq!(ChainSpec::parse("xbr,lanczos3"), None);
+        assert_eq!(ChainSpec::parse("xbr:0,lanczos3"), None);
+    }
+
+    #[test]
+    fn test_chain_reaches_exact_size() {
+        let img = Image::from_pixels(7, 5, vec![Pixel::new(10, 200, 90); 35]).unwrap();
+        let chain = ProgressiveChain::from_spec(ChainSpec::parse("nearest:2,xbr:2,bicubic").unwrap()).unwrap();
+        let result = chain.upscale(&img, 6.3);
+        assert_eq!((result.width, result.height), (44, 32));
+        assert!(result.pixels.iter().all(|p| p.g.abs_diff(200) <= 1));
+        assert_eq!(chain.tier(), UpscaleTier::Medium);
+
+        assert!(ProgressiveChain::from_spec(ChainSpec::parse("xbr:2,nonsense").unwrap()).is_err());
+    }
+}
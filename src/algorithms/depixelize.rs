@@ -0,0 +1,416 @@
+use crate::algorithms::image::{Image, Pixel};
+use crate::algorithms::upscaler::{Upscaler, UpscaleTier};
+use crate::algorithms::vector::{
+    assemble_shapes, colour_key, outline, trace_boundaries, Outline, PathSegment, Point, VectorImage, Vectorizer, OUTSIDE,
+};
+
+/// Neighbour offsets clockwise from east; direction `d + 4` reverses `d`
+const DIRECTIONS: [(i32, i32); 8] = [(1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0), (-1, -1), (0, -1), (1, -1)];
+
+/// Samples per pixel side when sampling the Voronoi cells
+const CELL_SAMPLES: usize = 8;
+
+/// Pixels joined when their colours are similar, one bit per direction
+struct SimilarityGraph {
+    width: usize,
+    height: usize,
+    links: Vec<u8>,
+}
+
+impl SimilarityGraph {
+    /// Links neighbours whose YUV difference is within the hqx thresholds
+    fn build(image: &Image) -> Self {
+        let (w, h) = (image.width, image.height);
+        let yuv: Vec<[f32; 3]> = image.pixels.iter().map(|&p| to_yuv(p)).collect();
+        let mut links = vec![0u8; w * h];
+        for y in 0..h {
+            for x in 0..w {
+                for (d, &(dx, dy)) in DIRECTIONS.iter().enumerate() {
+                    let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+                    if nx >= 0 && ny >= 0 && (nx as usize) < w && (ny as usize) < h {
+                        let (a, b) = (yuv[y * w + x], yuv[ny as usize * w + nx as usize]);
+                        if (a[0] - b[0]).abs() <= 48.0 && (a[1] - b[1]).abs() <= 7.0 && (a[2] - b[2]).abs() <= 6.0 {
+                            links[y * w + x] |= 1 << d;
+                        }
+                    }
+                }
+            }
+        }
+        Self { width: w, height: h, links }
+    }
+
+    fn linked(&self, p: usize, d: usize) -> bool {
+        self.links[p] >> d & 1 == 1
+    }
+
+    fn neighbour(&self, p: usize, d: usize) -> usize {
+        let (dx, dy) = DIRECTIONS[d];
+        ((p / self.width) as i32 + dy) as usize * self.width + ((p % self.width) as i32 + dx) as usize
+    }
+
+    fn unlink(&mut self, p: usize, d: usize) {
+        let q = self.neighbour(p, d);
+        self.links[p] &= !(1 << d);
+        self.links[q] &= !(1 << ((d + 4) % 8));
+    }
+
+    /// Whether diagonal `d` from `p` is bridged by one of the block's other
+    /// two pixels, which is linked to both ends
+    fn bridged(&self, p: usize, d: usize) -> bool {
+        let (before, after) = ((d + 7) % 8, (d + 1) % 8);
+        (self.linked(p, before) && self.linked(self.neighbour(p, before), after))
+            || (self.linked(p, after) && self.linked(self.neighbour(p, after), before))
+    }
+
+    fn valence(&self, p: usize) -> u32 {
+        self.links[p].count_ones()
+    }
+
+    /// Leaves at most one diagonal in every 2x2 block
+    ///
+    /// Fully similar blocks lose both diagonals; otherwise the curves, sparse
+    /// pixels and islands heuristics vote, and ties remove both.
+    fn resolve_crossings(&mut self) {
+        let mut removals = Vec::new();
+        for y in 0..self.height.saturating_sub(1) {
+            for x in 0..self.width.saturating_sub(1) {
+                let (a, b) = (y * self.width + x, y * self.width + x + 1);
+                let (c, d) = (a + self.width, b + self.width);
+                if !(self.linked(a, 1) && self.linked(b, 3)) {
+                    continue;
+                }
+                if self.linked(a, 0) && self.linked(a, 2) && self.linked(b, 2) && self.linked(c, 0) {
+                    removals.extend([(a, 1), (b, 3)]);
+                    continue;
+                }
+
+                let (mut main, mut anti) = (0i64, 0i64);
+
+                // Curves: keep the diagonal on the longer valence-2 curve
+                let (l1, l2) = (self.curve_length(a, d), self.curve_length(b, c));
+                if l1 > l2 {
+                    main += l1 - l2;
+                } else {
+                    anti += l2 - l1;
+                }
+
+                // Sparse pixels: the smaller component in an 8x8 window is foreground
+                let (s1, s2) = (self.component_size(a, x, y), self.component_size(b, x, y));
+                if s1 < s2 {
+                    main += s2 - s1;
+                } else {
+                    anti += s1 - s2;
+                }
+
+                // Islands: don't cut off a pixel whose only link is the diagonal
+                if self.valence(a) == 1 || self.valence(d) == 1 {
+                    main += 5;
+                }
+                if self.valence(b) == 1 || self.valence(c) == 1 {
+                    anti += 5;
+                }
+
+                if main <= anti {
+                    removals.push((a, 1));
+                }
+                if anti <= main {
+                    removals.push((b, 3));
+                }
+            }
+        }
+        for (p, d) in removals {
+            self.unlink(p, d);
+        }
+    }
+
+    /// Links in the chain of valence-2 pixels through the link `p`-`q`
+    fn curve_length(&self, p: usize, q: usize) -> i64 {
+        let mut length = 1;
+        for (mut prev, mut cur) in [(p, q), (q, p)] {
+            while self.valence(cur) == 2 && length < self.links.len() as i64 {
+                let next = (0..8)
+                    .filter(|&d| self.linked(cur, d))
+                    .map(|d| self.neighbour(cur, d))
+                    .find(|&n| n != prev)
+                    .unwrap_or(prev);
+                length += 1;
+                if next == p || next == q {
+                    return length;
+                }
+                (prev, cur) = (cur, next);
+            }
+        }
+        length
+    }
+
+    /// Size of `start`'s component within the 8x8 window around block (x, y)
+    fn component_size(&self, start: usize, x: usize, y: usize) -> i64 {
+        let (x0, y0) = (x.saturating_sub(3), y.saturating_sub(3));
+        let (x1, y1) = ((x + 4).min(self.width - 1), (y + 4).min(self.height - 1));
+        let inside = |p: usize| (x0..=x1).contains(&(p % self.width)) && (y0..=y1).contains(&(p / self.width));
+
+        let mut seen = vec![start];
+        let mut stack = vec![start];
+        while let Some(p) = stack.pop() {
+            for d in (0..8).filter(|&d| self.linked(p, d)) {
+                let n = self.neighbour(p, d);
+                if inside(n) && !seen.contains(&n) {
+                    seen.push(n);
+                    stack.push(n);
+                }
+            }
+        }
+        seen.len() as i64
+    }
+}
+
+/// YUV as used by hqx, on the 0-255 scale
+fn to_yuv(p: Pixel) -> [f32; 3] {
+    let (r, g, b) = (p.r as f32, p.g as f32, p.b as f32);
+    [
+        0.299 * r + 0.587 * g + 0.114 * b,
+        -0.169 * r - 0.331 * g + 0.5 * b,
+        0.5 * r - 0.419 * g - 0.081 * b,
+    ]
+}
+
+/// Pixel-art depixelization with the similarity graph of Kopf & Lischinski (2011)
+///
+/// Builds a similarity graph over the pixels and resolves crossing diagonals
+/// with the curves, sparse-pixels and islands heuristics. Cells are reshaped
+/// into the graph's generalised Voronoi diagram, sampled eight times per
+/// pixel side, so diagonally linked pixels meet. The boundaries between
+/// colours are then traced on that lattice and fitted with quadratic
+/// B-splines, keeping junctions, the image frame and
+/// corners between runs of at least `corner_run` pixels sharp. The result is
+/// a `VectorImage` for SVG export, rasterised at any scale as an `Upscaler`.
+///
+/// Time complexity: O(n) for the graph, cells and tracing, O(output pixels) to rasterise
+/// Space complexity: O(n) for the vector image
+pub struct Depixelize {
+    corner_run: f32,
+}
+
+impl Depixelize {
+    /// Corners stay sharp between straight runs of two pixels or more
+    pub fn new() -> Self {
+        Self { corner_run: 2.0 }
+    }
+
+    /// Shortest straight run, in pixels, on both sides of a corner that keeps it sharp
+    pub fn with_corner_run(mut self, pixels: f32) -> Self {
+        self.corner_run = pixels.max(0.5);
+        self
+    }
+
+    /// Vectorise the image into smooth colour regions in source pixel units
+    pub fn vectorize(&self, image: &Image) -> VectorImage {
+        let (w, h) = (image.width, image.height);
+        let mut vector = VectorImage { width: w as f32, height: h as f32, shapes: Vec::new() };
+        if w == 0 || h == 0 {
+            return vector;
+        }
+
+        let mut graph = SimilarityGraph::build(image);
+        graph.resolve_crossings();
+        let cells = reshape(image, &graph);
+
+        // Trace on the sampling lattice; the image frame stays straight
+        let boundaries = trace_boundaries(&cells, CELL_SAMPLES * w, CELL_SAMPLES * h);
+        let unit = CELL_SAMPLES as f32;
+        let paths: Vec<(Point, Vec<PathSegment>)> = boundaries
+            .iter()
+            .map(|boundary| {
+                let points: Vec<Point> = boundary.points.iter().map(|&(x, y)| (x as f32 / unit, y as f32 / unit)).collect();
+                let corner_run = if boundary.sides.contains(&OUTSIDE) { 0.0 } else { self.corner_run };
+                smooth(outline(&points, boundary.closed, corner_run))
+            })
+            .collect();
+        vector.shapes = assemble_shapes(&boundaries, &paths);
+        vector
+    }
+}
+
+/// Quadratic B-spline with the outline's points as control points, clamped
+/// at corners and ends
+fn smooth(outline: Outline) -> (Point, Vec<PathSegment>) {
+    let mid = |a: Point, b: Point| ((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0);
+    match outline {
+        Outline::Loop(controls) => {
+            let n = controls.len();
+            let segments = (0..n).map(|i| PathSegment::Quad(controls[i], mid(controls[i], controls[(i + 1) % n]))).collect();
+            (mid(controls[n - 1], controls[0]), segments)
+        }
+        Outline::Pieces(pieces) => {
+            let mut segments = Vec::new();
+            for piece in &pieces {
+                let k = piece.len() - 1;
+                if k == 1 {
+                    segments.push(PathSegment::Line(piece[1]));
+                    continue;
+                }
+                for i in 1..k - 1 {
+                    segments.push(PathSegment::Quad(piece[i], mid(piece[i], piece[i + 1])));
+                }
+                segments.push(PathSegment::Quad(piece[k - 1], piece[k]));
+            }
+            (pieces[0][0], segments)
+        }
+    }
+}
+
+/// Colours of the similarity graph's generalised Voronoi cells, sampled
+/// `CELL_SAMPLES` times per pixel side
+///
+/// Each pixel's site is its centre plus half of each diagonal link it keeps,
+/// and a sample belongs to the pixel whose site is nearest. Orthogonally
+/// linked pixels already share a side, and bridged diagonals join pixels
+/// that are already joined, so their half-links would only notch the
+/// corners of the pixels around them.
+fn reshape(image: &Image, graph: &SimilarityGraph) -> Vec<u32> {
+    let (w, h) = (image.width, image.height);
+    let keys: Vec<u32> = image.pixels.iter().map(|&p| colour_key(p)).collect();
+
+    // Diagonal half-link ends relative to each pixel's centre
+    let sites: Vec<Vec<Point>> = (0..w * h)
+        .map(|p| {
+            (0..8)
+                .filter(|&d| d % 2 == 1 && graph.linked(p, d) && !graph.bridged(p, d))
+                .map(|d| (DIRECTIONS[d].0 as f32 / 2.0, DIRECTIONS[d].1 as f32 / 2.0))
+                .collect()
+        })
+        .collect();
+    let distance = |p: usize, (x, y): Point| {
+        let (qx, qy) = (x - (p % w) as f32 - 0.5, y - (p / w) as f32 - 0.5);
+        sites[p].iter().fold(qx.hypot(qy), |nearest, &(ex, ey)| {
+            let t = ((qx * ex + qy * ey) / (ex * ex + ey * ey)).clamp(0.0, 1.0);
+            nearest.min((qx - t * ex).hypot(qy - t * ey))
+        })
+    };
+
+    let (sw, sh) = (CELL_SAMPLES * w, CELL_SAMPLES * h);
+    let mut cells = vec![0u32; sw * sh];
+    for sy in 0..sh {
+        for sx in 0..sw {
+            let point = ((sx as f32 + 0.5) / CELL_SAMPLES as f32, (sy as f32 + 0.5) / CELL_SAMPLES as f32);
+            let (x, y) = (sx / CELL_SAMPLES, sy / CELL_SAMPLES);
+
+            // Half-links end at a pixel corner, so sites two pixels
+            // away can still be nearest; ties stay with the covering pixel
+            let own = y * w + x;
+            let mut nearest = (distance(own, point), own);
+            for ny in y.saturating_sub(2)..(y + 3).min(h) {
+                for nx in x.saturating_sub(2)..(x + 3).min(w) {
+                    let p = ny * w + nx;
+                    let d = distance(p, point);
+                    if d < nearest.0 {
+                        nearest = (d, p);
+                    }
+                }
+            }
+            cells[sy * sw + sx] = keys[nearest.1];
+        }
+    }
+    cells
+}
+
+impl Upscaler for Depixelize {
+    fn upscale(&self, image: &Image, scale_factor: f32) -> Image {
+        let width = (image.width as f32 * scale_factor).round() as usize;
+        let height = (image.height as f32 * scale_factor).round() as usize;
+        if image.width == 0 || image.height == 0 {
+            return Image::new(width, height);
+        }
+        Depixelize::vectorize(self, image).rasterize(width, height)
+    }
+
+    fn name(&self) -> &str {
+        "Depixelize (Kopf-Lischinski)"
+    }
+
+    fn tier(&self) -> UpscaleTier {
+        UpscaleTier::Slow
+    }
+}
+
+impl Vectorizer for Depixelize {
+    fn vectorize(&self, image: &Image) -> VectorImage {
+        Depixelize::vectorize(self, image)
+    }
+}
+
+impl Default for Depixelize {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dark(p: Pixel) -> bool {
+        p.r < 128
+    }
+
+    #[test]
+    fn test_diagonal_line_stays_connected() {
+        // A one-pixel black diagonal on white: both diagonals cross in every
+        // block, and the curves heuristic must keep the line's
+        let mut img = Image::new(8, 8);
+        for y in 0..8 {
+            for x in 0..8 {
+                img.set_pixel(x, y, if x == y { Pixel::black() } else { Pixel::white() });
+            }
+        }
+
+        let vector = Depixelize::new().vectorize(&img);
+        let line = vector.shapes.iter().find(|s| s.fill == Pixel::black()).unwrap();
+        assert_eq!(line.contours.len(), 1);
+        let paper = vector.shapes.iter().find(|s| s.fill == Pixel::white()).unwrap();
+        assert_eq!(paper.contours.len(), 2);
+
+        // The corner shared by two line pixels is filled
+        let big = Depixelize::new().upscale(&img, 8.0);
+        assert_eq!((big.width, big.height), (64, 64));
+        assert!(dark(big.get_pixel(24, 24).unwrap()));
+        assert!(!dark(big.get_pixel(40, 8).unwrap()));
+    }
+
+    #[test]
+    fn test_diagonal_cells_follow_the_voronoi_bisector() {
+        // Black on one diagonal, red and blue on the other: only the black
+        // pixels are linked, across the shared corner at (1, 1)
+        let (red, blue) = (Pixel::new(220, 30, 30), Pixel::new(20, 40, 200));
+        let img = Image::from_pixels(2, 2, vec![Pixel::black(), red, blue, Pixel::black()]).unwrap();
+        let cells = reshape(&img, &SimilarityGraph::build(&img));
+        let at = |x: f32, y: f32| cells[(y * CELL_SAMPLES as f32) as usize * 2 * CELL_SAMPLES + (x * CELL_SAMPLES as f32) as usize];
+
+        assert_eq!(at(1.06, 0.94), colour_key(Pixel::black()));
+        assert_eq!(at(0.94, 1.06), colour_key(Pixel::black()));
+        // Closer to red's centre than to the diagonal, though in its corner quarter
+        assert_eq!(at(1.44, 0.56), colour_key(red));
+        assert_eq!(at(1.94, 0.94), colour_key(red));
+        assert_eq!(at(0.06, 1.94), colour_key(blue));
+    }
+
+    #[test]
+    fn test_blocks_keep_colours_and_corners() {
+        // A 3x3 red square on blue: long sides keep sharp corners
+        let mut img = Image::new(7, 7);
+        for y in 0..7 {
+            for x in 0..7 {
+                let inside = (2..5).contains(&x) && (2..5).contains(&y);
+                img.set_pixel(x, y, if inside { Pixel::new(220, 30, 30) } else { Pixel::new(20, 40, 200) });
+            }
+        }
+
+        let big = Depixelize::new().upscale(&img, 4.0);
+        assert_eq!(big.get_pixel(8, 8), Some(Pixel::new(220, 30, 30)));
+        assert_eq!(big.get_pixel(19, 19), Some(Pixel::new(220, 30, 30)));
+        assert_eq!(big.get_pixel(7, 7), Some(Pixel::new(20, 40, 200)));
+
+        let svg = Depixelize::new().vectorize(&img).to_svg(4.0);
+        assert!(svg.contains("fill=\"#dc1e1e\"") && svg.contains("fill=\"#1428c8\""));
+    }
+}
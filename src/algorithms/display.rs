@@ -0,0 +1,238 @@
+use std::f32::consts::PI;
+
+use crate::algorithms::degradation::Psf;
+use crate::algorithms::image::{FloatImage, Image};
+
+/// One retro display effect, applied to an upscaled image
+///
+/// Strengths run from 0 (no effect) to 1 (darkest gaps). Effects tied to the
+/// source pixels (scanlines, LCD grid) need the pixel pitch, the number of
+/// output pixels per source pixel; the phosphor masks repeat every three
+/// output pixels like a real screen's triads.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DisplayFilter {
+    /// Dark gaps between source rows with a smooth beam profile
+    Scanlines { strength: f32 },
+    /// Vertical red, green and blue phosphor stripes (Trinitron)
+    ApertureGrille { strength: f32 },
+    /// Red, green and blue phosphor dots, offset on alternate rows
+    ShadowMask { strength: f32 },
+    /// Glow of bright phosphors, a Gaussian of `radius` source pixels over
+    /// the luma above a soft-knee threshold, so dark and mid tones stay put
+    Bloom { radius: f32, strength: f32 },
+    /// Dark gaps around every source pixel, as on a handheld's LCD
+    LcdGrid { strength: f32 },
+}
+
+impl DisplayFilter {
+    /// Parse `scanlines`, `aperture`, `shadowmask` and `lcd` with an optional
+    /// `:STRENGTH`, or `bloom[:RADIUS[:STRENGTH]]`
+    pub fn parse(s: &str) -> Option<Self> {
+        let mut parts = s.split(':');
+        let name = parts.next()?.trim().to_lowercase();
+        let params = parts.map(|p| p.trim().parse::<f32>().ok().filter(|v| *v >= 0.0)).collect::<Option<Vec<f32>>>()?;
+        let strength = |default: f32, i: usize| params.get(i).map_or(default, |s| s.min(1.0));
+        let filter = match name.as_str() {
+            "scanlines" | "scanline" => DisplayFilter::Scanlines { strength: strength(0.5, 0) },
+            "aperture" | "aperture_grille" | "grille" => DisplayFilter::ApertureGrille { strength: strength(0.3, 0) },
+            "shadowmask" | "shadow_mask" | "mask" => DisplayFilter::ShadowMask { strength: strength(0.3, 0) },
+            "lcd" | "lcd_grid" | "grid" => DisplayFilter::LcdGrid { strength: strength(0.4, 0) },
+            "bloom" | "glow" => DisplayFilter::Bloom {
+                radius: params.first().copied().unwrap_or(1.0).max(0.1),
+                strength: strength(0.3, 1),
+            },
+            _ => return None,
+        };
+        let max_params = if matches!(filter, DisplayFilter::Bloom { .. }) { 2 } else { 1 };
+        (params.len() <= max_params).then_some(filter)
+    }
+
+    fn apply(&self, image: &FloatImage, pitch: f32) -> FloatImage {
+        let mut result = image.clone();
+        let (w, h) = (image.width, image.height);
+
+        match *self {
+            DisplayFilter::Scanlines { strength } => {
+                for y in 0..h {
+                    // Brightest mid-row, darkest on the boundary between source rows
+                    let v = ((y as f32 + 0.5) / pitch).fract();
+                    let gain = 1.0 - strength * (PI * v).cos().powi(2);
+                    for value in &mut result.data[y * w..(y + 1) * w] {
+                        value.iter_mut().for_each(|c| *c *= gain);
+                    }
+                }
+            }
+            DisplayFilter::ApertureGrille { strength } | DisplayFilter::ShadowMask { strength } => {
+                let shadow = matches!(self, DisplayFilter::ShadowMask { .. });
+                for y in 0..h {
+                    let offset = if shadow && y % 2 == 1 { 1 } else { 0 };
+                    for x in 0..w {
+                        let phosphor = (x + offset) % 3;
+                        let value = &mut result.data[y * w + x];
+                        for (c, channel) in value.iter_mut().enumerate() {
+                            if c != phosphor {
+                                *channel *= 1.0 - strength;
+                            }
+                        }
+                    }
+                }
+            }
+            DisplayFilter::Bloom { radius, strength } => {
+                // Only the part of each pixel above the soft-knee threshold glows
+                let mut bright = image.clone();
+                for value in &mut bright.data {
+                    let luma = 0.299 * value[0] + 0.587 * value[1] + 0.114 * value[2];
+                    let gain = bloom_excess(luma) / luma.max(1e-3);
+                    value.iter_mut().for_each(|c| *c *= gain);
+                }
+                let glow = Psf::Gaussian(radius * pitch).resample(&bright, w, h);
+                for (value, glow) in result.data.iter_mut().zip(&glow.data) {
+                    for c in 0..3 {
+                        value[c] += strength * glow[c];
+                    }
+                }
+            }
+            DisplayFilter::LcdGrid { strength } => {
+                // The last output pixel of every source pixel is gap
+                let gap = |i: usize| ((i + 1) as f32 / pitch).floor() != (i as f32 / pitch).floor();
+                for y in 0..h {
+                    for x in 0..w {
+                        if gap(x) || gap(y) {
+                            result.data[y * w + x].iter_mut().for_each(|c| *c *= 1.0 - strength);
+                        }
+                    }
+                }
+            }
+        }
+        result
+    }
+}
+
+/// Luma, on a 0-255 scale, where bloom starts to pick up light
+const BLOOM_THRESHOLD: f32 = 190.0;
+/// Half-width of the quadratic ramp that eases bloom in around the threshold
+const BLOOM_KNEE: f32 = 25.0;
+
+/// Luma above the bloom threshold, with a quadratic soft knee
+fn bloom_excess(luma: f32) -> f32 {
+    let soft = (luma - BLOOM_THRESHOLD + BLOOM_KNEE).clamp(0.0, 2.0 * BLOOM_KNEE);
+    (soft * soft / (4.0 * BLOOM_KNEE)).max(luma - BLOOM_THRESHOLD)
+}
+
+/// A stack of display filters applied in order
+///
+/// Meant for integer nearest-neighbour output, where every source pixel is a
+/// `pitch` x `pitch` block, so scanlines and grid gaps line up with the art.
+///
+/// Time complexity: O(n) per filter, O(n * radius * pitch) for bloom
+/// Space complexity: O(n) for the f32 working image
+#[derive(Debug, Clone, PartialEq)]
+pub struct DisplayEmulation {
+    filters: Vec<DisplayFilter>,
+}
+
+impl DisplayEmulation {
+    pub fn new() -> Self {
+        Self { filters: Vec::new() }
+    }
+
+    /// Scanlines, an aperture grille and a little bloom
+    pub fn crt() -> Self {
+        Self::new()
+            .with_filter(DisplayFilter::Scanlines { strength: 0.5 })
+            .with_filter(DisplayFilter::ApertureGrille { strength: 0.3 })
+            .with_filter(DisplayFilter::Bloom { radius: 1.0, strength: 0.3 })
+    }
+
+    pub fn with_filter(mut self, filter: DisplayFilter) -> Self {
+        self.filters.push(filter);
+        self
+    }
+
+    pub fn filters(&self) -> &[DisplayFilter] {
+        &self.filters
+    }
+
+    /// Parse `crt` or a comma-separated list of filters such as `scanlines:0.6,bloom:1.5:0.2`
+    pub fn parse(s: &str) -> Option<Self> {
+        if s.eq_ignore_ascii_case("crt") {
+            return Some(Self::crt());
+        }
+        let filters = s.split(',').map(DisplayFilter::parse).collect::<Option<Vec<_>>>()?;
+        Some(Self { filters })
+    }
+
+    /// Apply every filter to an image enlarged `pitch` times
+    pub fn apply(&self, image: &Image, pitch: f32) -> Image {
+        let pitch = pitch.max(1.0);
+        self.filters
+            .iter()
+            .fold(FloatImage::from_image(image), |current, filter| filter.apply(&current, pitch))
+            .to_image()
+    }
+}
+
+impl Default for DisplayEmulation {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::image::Pixel;
+
+    fn grey(size: usize) -> Image {
+        Image::from_pixels(size, size, vec![Pixel::new(200, 200, 200); size * size]).unwrap()
+    }
+
+    #[test]
+    fn test_scanlines_and_lcd_follow_the_pixel_grid() {
+        let image = grey(8);
+
+        let scanlines = DisplayEmulation::parse("scanlines:1").unwrap().apply(&image, 4.0);
+        let row = |y: usize| scanlines.get_pixel(0, y).unwrap().r;
+        assert!(row(0) < 80 && row(3) < 80);
+        assert!(row(1) > 150 && row(2) > 150);
+        assert_eq!(row(1), row(5));
+
+        let lcd = DisplayEmulation::parse("lcd:0.5").unwrap().apply(&image, 4.0);
+        assert_eq!(lcd.get_pixel(1, 1), Some(Pixel::new(200, 200, 200)));
+        assert_eq!(lcd.get_pixel(3, 1), Some(Pixel::new(100, 100, 100)));
+        assert_eq!(lcd.get_pixel(4, 4), Some(Pixel::new(200, 200, 200)));
+    }
+
+    #[test]
+    fn test_masks_and_parsing() {
+        let image = grey(6);
+        let grille = DisplayEmulation::new().with_filter(DisplayFilter::ApertureGrille { strength: 1.0 }).apply(&image, 3.0);
+        assert_eq!(grille.get_pixel(0, 0), Some(Pixel::new(200, 0, 0)));
+        assert_eq!(grille.get_pixel(1, 5), Some(Pixel::new(0, 200, 0)));
+
+        let shadow = DisplayEmulation::parse("shadowmask:1").unwrap().apply(&image, 3.0);
+        assert_eq!(shadow.get_pixel(0, 1), Some(Pixel::new(0, 200, 0)));
+
+        assert_eq!(DisplayEmulation::parse("crt"), Some(DisplayEmulation::crt()));
+        assert_eq!(DisplayEmulation::parse("scanlines:0.5:2"), None);
+        assert_eq!(DisplayEmulation::parse("vhs"), None);
+    }
+
+    #[test]
+    fn test_bloom_only_lifts_bright_areas() {
+        // Dark, mid and bright bands, each 8 output pixels wide
+        let mut image = Image::new(24, 8);
+        for y in 0..8 {
+            for x in 0..24 {
+                let v = [30, 128, 230][x / 8];
+                image.set_pixel(x, y, Pixel::new(v, v, v));
+            }
+        }
+        let bloom = DisplayEmulation::parse("bloom:1:0.5").unwrap().apply(&image, 2.0);
+        assert_eq!(bloom.get_pixel(1, 4), Some(Pixel::new(30, 30, 30)));
+        assert_eq!(bloom.get_pixel(9, 4), Some(Pixel::new(128, 128, 128)));
+        assert!(bloom.get_pixel(20, 4).unwrap().r > 240);
+        // The glow spills onto the mid band next to the bright one
+        assert!(bloom.get_pixel(15, 4).unwrap().r > 128);
+    }
+}
@@ -0,0 +1,311 @@
+use crate::algorithms::degradation::Psf;
+use crate::algorithms::fast::{Bicubic, Lanczos};
+use crate::algorithms::image::{FloatImage, Image, Pixel};
+use crate::algorithms::medium::EdgeDirected;
+use crate::algorithms::patch::Plane;
+use crate::algorithms::sampling::SamplingConfig;
+use crate::algorithms::upscaler::{Upscaler, UpscaleTier};
+
+/// How an ensemble combines its members' outputs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Fusion {
+    /// Per-pixel, per-channel median; robust to one member's artefacts
+    Median,
+    /// Per-pixel mean weighted by each member's weight
+    WeightedMean,
+    /// Per-pixel choice of the member whose output, degraded by the PSF, best
+    /// reproduces the input over a window of this many source pixels
+    BestBackProjection(usize),
+}
+
+impl Fusion {
+    /// Parse `median`, `mean`, `backprojection` or `backprojection:WINDOW`
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "median" => Some(Fusion::Median),
+            "mean" | "weighted_mean" => Some(Fusion::WeightedMean),
+            "backprojection" | "back_projection" | "bp" => Some(Fusion::BestBackProjection(7)),
+            other => {
+                let window: usize = other.strip_prefix("backprojection:")?.parse().ok()?;
+                (window > 0).then_some(Fusion::BestBackProjection(window))
+            }
+        }
+    }
+}
+
+/// Ensemble of upscalers fused into one output
+///
+/// Every member upscales the whole image and the results are combined by the
+/// `Fusion` rule. Back-projection fusion downsamples each candidate with the
+/// `psf`, measures its squared residual against the input, box-filters the
+/// residual over the window and, for every output pixel, takes the candidate
+/// with the smallest local residual at the source pixel it maps to.
+///
+/// Time complexity: O(sum of members) plus O(n * members) to fuse
+/// Space complexity: O(n * members) for the candidate outputs
+pub struct Ensemble {
+    members: Vec<(Box<dyn Upscaler>, f32)>,
+    fusion: Fusion,
+    psf: Psf,
+}
+
+impl Ensemble {
+    /// Median of bicubic, Lanczos3 and edge-directed interpolation
+    pub fn new() -> Self {
+        Self {
+            members: vec![
+                (Box::new(Bicubic), 1.0),
+                (Box::new(Lanczos::new()), 1.0),
+                (Box::new(EdgeDirected), 1.0),
+            ],
+            fusion: Fusion::Median,
+            psf: Psf::Bicubic,
+        }
+    }
+
+    /// Look members up by registry name, each with its mean weight
+    pub fn from_members(members: &[(String, f32)]) -> Result<Self, String> {
+        let mut ensemble = Self::new();
+        ensemble.members.clear();
+        for (name, weight) in members {
+            let upscaler = crate::get_upscaler(name).ok_or_else(|| format!("Unknown algorithm: {}", name))?;
+            ensemble = ensemble.with_member(upscaler, *weight);
+        }
+        if ensemble.members.len() < 2 {
+            return Err("An ensemble needs at least two algorithms".to_string());
+        }
+        Ok(ensemble)
+    }
+
+    /// Parse `NAME[:WEIGHT],...`, e.g. `lanczos3:2,edi,xbr`; weights default to 1
+    pub fn parse_members(spec: &str) -> Option<Vec<(String, f32)>> {
+        spec.split(',')
+            .map(|member| match member.split_once(':') {
+                Some((name, weight)) => weight.parse::<f32>().ok().filter(|w| *w > 0.0).map(|w| (name.to_string(), w)),
+                None => Some((member.to_string(), 1.0)),
+            })
+            .map(|m| m.filter(|(name, _)| !name.is_empty()))
+            .collect()
+    }
+
+    /// Add a member; `weight` only matters for `Fusion::WeightedMean`
+    pub fn with_member(mut self, upscaler: Box<dyn Upscaler>, weight: f32) -> Self {
+        self.members.push((upscaler, weight.max(0.0)));
+        self
+    }
+
+    pub fn with_fusion(mut self, fusion: Fusion) -> Self {
+        self.fusion = fusion;
+        self
+    }
+
+    /// Degradation model for back-projection fusion
+    pub fn with_psf(mut self, psf: Psf) -> Self {
+        self.psf = psf;
+        self
+    }
+
+    fn fuse(&self, image: &Image, scale_factor: f32, candidates: Vec<Image>, sampling: &SamplingConfig) -> Image {
+        let (width, height) = (candidates[0].width, candidates[0].height);
+        // Members normally agree on the size; read any that don't at the nearest pixel
+        let at = |c: &Image, x: usize, y: usize| c.get_pixel(x * c.width / width, y * c.height / height).unwrap_or_else(Pixel::black);
+        let mut result = Image::new(width, height);
+
+        match self.fusion {
+            Fusion::Median => {
+                let mut values = vec![[0u8; 3]; candidates.len()];
+                for y in 0..height {
+                    for x in 0..width {
+                        for (value, c) in values.iter_mut().zip(&candidates) {
+                            let p = at(c, x, y);
+                            *value = [p.r, p.g, p.b];
+                        }
+                        let channel = |i: usize| {
+                            let mut v: Vec<u8> = values.iter().map(|value| value[i]).collect();
+                            v.sort_unstable();
+                            let mid = v.len() / 2;
+                            if v.len() % 2 == 1 {
+                                v[mid]
+                            } else {
+                                (v[mid - 1] as u16 + v[mid] as u16).div_ceil(2) as u8
+                            }
+                        };
+                        result.set_pixel(x, y, Pixel::new(channel(0), channel(1), channel(2)));
+                    }
+                }
+            }
+            Fusion::WeightedMean => {
+                let total: f32 = self.members.iter().map(|(_, w)| w).sum::<f32>().max(f32::EPSILON);
+                for y in 0..height {
+                    for x in 0..width {
+                        let mut sum = [0.0f32; 3];
+                        for (c, (_, w)) in candidates.iter().zip(&self.members) {
+                            let p = at(c, x, y);
+                            sum[0] += p.r as f32 * w;
+                            sum[1] += p.g as f32 * w;
+                            sum[2] += p.b as f32 * w;
+                        }
+                        let channel = |s: f32| (s / total).round().clamp(0.0, 255.0) as u8;
+                        result.set_pixel(x, y, Pixel::new(channel(sum[0]), channel(sum[1]), channel(sum[2])));
+                    }
+                }
+            }
+            Fusion::BestBackProjection(window) => {
+                let errors: Vec<Plane> = candidates.iter().map(|c| self.local_residual(image, c, window)).collect();
+                for y in 0..height {
+                    let sy = sampling.alignment.map_nearest(y, scale_factor).clamp(0, image.height as i32 - 1) as usize;
+                    for x in 0..width {
+                        let sx = sampling.alignment.map_nearest(x, scale_factor).clamp(0, image.width as i32 - 1) as usize;
+                        let best = (0..candidates.len())
+                            .min_by(|&a, &b| errors[a].get(sx, sy).total_cmp(&errors[b].get(sx, sy)))
+                            .unwrap_or(0);
+                        result.set_pixel(x, y, at(&candidates[best], x, y));
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    /// Squared back-projection residual of `candidate`, box-summed over `window` source pixels
+    fn local_residual(&self, image: &Image, candidate: &Image, window: usize) -> Plane {
+        let (w, h) = (image.width, image.height);
+        let degraded = self.psf.resample(&FloatImage::from_image(candidate), w, h);
+        let source = FloatImage::from_image(image);
+
+        let mut residual = Plane::new(w, h);
+        for y in 0..h {
+            for x in 0..w {
+                let (a, b) = (degraded.get(x, y), source.get(x, y));
+                residual.data[y * w + x] = (0..3).map(|c| (a[c] - b[c]).powi(2)).sum();
+            }
+        }
+
+        // Summed-area table for the window sums
+        let mut table = vec![0.0f64; (w + 1) * (h + 1)];
+        for y in 0..h {
+            for x in 0..w {
+                table[(y + 1) * (w + 1) + x + 1] = residual.data[y * w + x] as f64 + table[y * (w + 1) + x + 1]
+                    + table[(y + 1) * (w + 1) + x]
+                    - table[y * (w + 1) + x];
+            }
+        }
+
+        let radius = window / 2;
+        let mut summed = Plane::new(w, h);
+        for y in 0..h {
+            let (y0, y1) = (y.saturating_sub(radius), (y + window - radius).min(h));
+            for x in 0..w {
+                let (x0, x1) = (x.saturating_sub(radius), (x + window - radius).min(w));
+                let sum = table[y1 * (w + 1) + x1] - table[y0 * (w + 1) + x1] - table[y1 * (w + 1) + x0] + table[y0 * (w + 1) + x0];
+                summed.data[y * w + x] = sum as f32;
+            }
+        }
+        summed
+    }
+}
+
+impl Upscaler for Ensemble {
+    fn upscale(&self, image: &Image, scale_factor: f32) -> Image {
+        self.upscale_with_sampling(image, scale_factor, &SamplingConfig::default())
+    }
+
+    fn upscale_with_sampling(&self, image: &Image, scale_factor: f32, sampling: &SamplingConfig) -> Image {
+        let candidates: Vec<Image> = self.members.iter()
+            .map(|(upscaler, _)| upscaler.upscale_with_sampling(image, scale_factor, sampling))
+            .collect();
+        if candidates.is_empty() {
+            return Bicubic.upscale_with_sampling(image, scale_factor, sampling);
+        }
+        self.fuse(image, scale_factor, candidates, sampling)
+    }
+
+    fn name(&self) -> &str {
+        "Ensemble"
+    }
+
+    fn supports_sampling(&self) -> bool {
+        self.members.iter().all(|(upscaler, _)| upscaler.supports_sampling())
+    }
+
+    fn tier(&self) -> UpscaleTier {
+        self.members.iter().map(|(u, _)| u.tier()).max().unwrap_or(UpscaleTier::Fast)
+    }
+}
+
+impl Default for Ensemble {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::instant::NearestNeighbor;
+
+    /// Always returns a flat grey image of the right size
+    struct Grey(u8);
+
+    impl Upscaler for Grey {
+        fn upscale(&self, image: &Image, scale_factor: f32) -> Image {
+            let (w, h) = ((image.width as f32 * scale_factor).round() as usize, (image.height as f32 * scale_factor).round() as usize);
+            Image::from_pixels(w, h, vec![Pixel::new(self.0, self.0, self.0); w * h]).unwrap()
+        }
+
+        fn name(&self) -> &str {
+            "Grey"
+        }
+
+        fn tier(&self) -> UpscaleTier {
+            UpscaleTier::Instant
+        }
+    }
+
+    fn checker() -> Image {
+        let mut img = Image::new(8, 8);
+        for y in 0..8 {
+            for x in 0..8 {
+                let v = if (x / 2 + y / 2) % 2 == 0 { 30 } else { 220 };
+                img.set_pixel(x, y, Pixel::new(v, v, v));
+            }
+        }
+        img
+    }
+
+    #[test]
+    fn test_median_and_weighted_mean() {
+        let img = checker();
+        let mut ensemble = Ensemble::new();
+        ensemble.members.clear();
+        let ensemble = ensemble
+            .with_member(Box::new(NearestNeighbor), 3.0)
+            .with_member(Box::new(NearestNeighbor), 1.0)
+            .with_member(Box::new(Grey(0)), 1.0);
+
+        // Two agreeing members outvote the outlier
+        let median = ensemble.upscale(&img, 2.0);
+        assert_eq!(median.pixels, NearestNeighbor.upscale(&img, 2.0).pixels);
+
+        let mean = ensemble.with_fusion(Fusion::WeightedMean).upscale(&img, 2.0);
+        assert_eq!(mean.get_pixel(0, 0), Some(Pixel::new(24, 24, 24)));
+    }
+
+    #[test]
+    fn test_back_projection_picks_consistent_candidate() {
+        let img = checker();
+        let ensemble = Ensemble::from_members(&[("nearest".to_string(), 1.0), ("bilinear".to_string(), 1.0)])
+            .unwrap()
+            .with_member(Box::new(Grey(128)), 1.0)
+            .with_fusion(Fusion::BestBackProjection(3))
+            .with_psf(Psf::Box);
+
+        // Box-averaging nearest-neighbour output reproduces the input exactly
+        let result = ensemble.upscale(&img, 2.0);
+        assert_eq!(result.pixels, NearestNeighbor.upscale(&img, 2.0).pixels);
+
+        assert_eq!(Ensemble::parse_members("lanczos3:2,edi"), Some(vec![("lanczos3".to_string(), 2.0), ("edi".to_string(), 1.0)]));
+        assert_eq!(Ensemble::parse_members("lanczos3:x"), None);
+        assert_eq!(Fusion::parse("backprojection:5"), Some(Fusion::BestBackProjection(5)));
+    }
+}
@@ -0,0 +1,433 @@
+use crate::algorithms::degradation::Psf;
+use crate::algorithms::image::{FloatImage, Image};
+use crate::algorithms::patch::{patch_origins, KdTree, Plane};
+use crate::algorithms::upscaler::{Upscaler, UpscaleTier};
+
+/// Low-pass band of `image`, blurred as if it had been enlarged by `ratio`
+fn low_band(image: &FloatImage, ratio: f32) -> FloatImage {
+    let w = ((image.width as f32 / ratio).round() as usize).max(1);
+    let h = ((image.height as f32 / ratio).round() as usize).max(1);
+    let small = Psf::Bicubic.resample(image, w, h);
+    Psf::Bicubic.resample(&small, image.width, image.height)
+}
+
+/// Self-Similarity Super-Resolution (Glasner / Freedman-Fattal style)
+///
+/// Natural images repeat small structures across scales. The image is grown in
+/// small steps; at each step a bicubic base is built, and every base patch is
+/// matched against the low-pass bands of a pyramid of the current image. The
+/// high-frequency band that the best match lost to blurring is pasted back into
+/// the base. Matching is approximate: the search only covers a window around
+/// the corresponding position in each pyramid level (local self-similarity).
+///
+/// Time complexity: O(n * levels * radius² * patch²) per step
+/// Space complexity: O(n) for the pyramid and accumulation buffers
+pub struct SelfSimilarity {
+    patch_size: usize,
+    search_radius: usize,
+    step_factor: f32,
+    levels: usize,
+}
+
+impl SelfSimilarity {
+    /// 5x5 patches, ±4 pixel search, 1.25x steps over a 3-level pyramid
+    pub fn new() -> Self {
+        Self {
+            patch_size: 5,
+            search_radius: 4,
+            step_factor: 1.25,
+            levels: 3,
+        }
+    }
+
+    pub fn with_patch_size(mut self, patch_size: usize) -> Self {
+        self.patch_size = patch_size.max(2);
+        self
+    }
+
+    /// Search window half-width, in pixels of each pyramid level
+    pub fn with_search_radius(mut self, search_radius: usize) -> Self {
+        self.search_radius = search_radius;
+        self
+    }
+
+    /// Largest magnification per step; small steps keep matches reliable
+    pub fn with_step_factor(mut self, step_factor: f32) -> Self {
+        self.step_factor = step_factor.clamp(1.05, 2.0);
+        self
+    }
+
+    /// Number of pyramid levels searched (1 = the current image only)
+    pub fn with_levels(mut self, levels: usize) -> Self {
+        self.levels = levels.max(1);
+        self
+    }
+
+    /// Enlarge `current` to `width` x `height` (at most `step_factor`)
+    fn upscale_step(&self, current: &FloatImage, width: usize, height: usize) -> FloatImage {
+        let ratio = width as f32 / current.width as f32;
+        let size = self.patch_size;
+        let base = Psf::Bicubic.resample(current, width, height);
+        let base_luma = Plane::luma(&base);
+
+        // Pyramid of (scale relative to `current`, low-band luma, high band)
+        let mut pyramid = Vec::new();
+        for level in 0..self.levels {
+            let scale = ratio.powi(-(level as i32));
+            let w = (current.width as f32 * scale).round() as usize;
+            let h = (current.height as f32 * scale).round() as usize;
+            if w < size || h < size {
+                break;
+            }
+            let image = if level == 0 { current.clone() } else { Psf::Bicubic.resample(current, w, h) };
+            let low = low_band(&image, ratio);
+            let high = image.difference(&low);
+            pyramid.push((scale, Plane::luma(&low), high));
+        }
+
+        let mut detail = FloatImage::new(width, height);
+        let mut weights = vec![0.0f32; width * height];
+        let radius = self.search_radius as i32;
+
+        for &py in &patch_origins(height, size, size / 2) {
+            for &px in &patch_origins(width, size, size / 2) {
+                let query = base_luma.patch(px as i32, py as i32, size);
+
+                let mut best = (f32::INFINITY, 0, 0, 0);
+                for (index, (scale, low_luma, _)) in pyramid.iter().enumerate() {
+                    // Where this base patch sits in the pyramid level
+                    let cx = ((px as f32 / ratio) * scale).round() as i32;
+                    let cy = ((py as f32 / ratio) * scale).round() as i32;
+                    let max_x = low_luma.width as i32 - size as i32;
+                    let max_y = low_luma.height as i32 - size as i32;
+
+                    for sy in (cy - radius).max(0)..=(cy + radius).min(max_y) {
+                        for sx in (cx - radius).max(0)..=(cx + radius).min(max_x) {
+                            let d = low_luma.ssd(&query, sx, sy, size, best.0);
+                            if d < best.0 {
+                                best = (d, index, sx as usize, sy as usize);
+                            }
+                        }
+                    }
+                }
+
+                if !best.0.is_finite() {
+                    continue;
+                }
+                let high = &pyramid[best.1].2;
+                for dy in 0..size.min(height - py) {
+                    for dx in 0..size.min(width - px) {
+                        let hx = (best.2 + dx).min(high.width - 1);
+                        let hy = (best.3 + dy).min(high.height - 1);
+                        let h = high.get(hx, hy);
+                        let i = (py + dy) * width + px + dx;
+                        for (d, v) in detail.data[i].iter_mut().zip(h) {
+                            *d += v;
+                        }
+                        weights[i] += 1.0;
+                    }
+                }
+            }
+        }
+
+        let mut result = base;
+        for ((out, d), &w) in result.data.iter_mut().zip(&detail.data).zip(&weights) {
+            if w > 0.0 {
+                for c in 0..3 {
+                    out[c] += d[c] / w;
+                }
+            }
+        }
+        result.clamp_to_range();
+        result
+    }
+}
+
+impl Upscaler for SelfSimilarity {
+    fn upscale(&self, image: &Image, scale_factor: f32) -> Image {
+        let target_w = (image.width as f32 * scale_factor).round() as usize;
+        let target_h = (image.height as f32 * scale_factor).round() as usize;
+        let mut current = FloatImage::from_image(image);
+
+        // Grow in small steps while the input is large enough to hold patches
+        while current.width < target_w && current.width >= self.patch_size && current.height >= self.patch_size {
+            let ratio = (target_w as f32 / current.width as f32).min(self.step_factor);
+            let w = ((current.width as f32 * ratio).round() as usize).clamp(current.width + 1, target_w);
+            let h = ((current.height as f32 * w as f32 / current.width as f32).round() as usize).min(target_h.max(1));
+            current = self.upscale_step(&current, w, h.max(1));
+        }
+
+        if current.width != target_w || current.height != target_h {
+            current = Psf::Bicubic.resample(&current, target_w, target_h);
+        }
+        current.to_image()
+    }
+
+    fn name(&self) -> &str {
+        "Self-Similarity SR"
+    }
+
+    fn tier(&self) -> UpscaleTier {
+        UpscaleTier::Slow
+    }
+}
+
+impl Default for SelfSimilarity {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Reference-Guided Example-Based Super-Resolution (Freeman style)
+///
+/// Builds a database from one or more high-resolution reference images: each
+/// reference is blurred as if it had been upscaled, and every low-pass patch is
+/// paired with the high-frequency patch the blur removed. While upscaling the
+/// target, each bicubic base patch is contrast-normalised, looked up in a k-d
+/// tree, and the matching high-frequency patch is pasted back in.
+///
+/// Time complexity: O(n * checks * patch²) per step, plus database build
+/// Space complexity: O(r * patch²) for a reference set of r pixels
+pub struct ReferenceGuided {
+    references: Vec<FloatImage>,
+    patch_size: usize,
+    max_checks: usize,
+    step_factor: f32,
+}
+
+/// Patches per database are capped so huge references stay tractable
+const MAX_DATABASE_PATCHES: usize = 200_000;
+
+/// Contrast floor (in 8-bit levels) below which patches carry no useful detail
+const CONTRAST_EPSILON: f32 = 2.0;
+
+/// Low-pass keys in a k-d tree with their high-frequency RGB patches
+struct PatchDatabase {
+    tree: KdTree,
+    high: Vec<[f32; 3]>,
+}
+
+impl ReferenceGuided {
+    /// 5x5 patches, 64 candidate checks per lookup, 2x steps
+    pub fn new(references: Vec<Image>) -> Self {
+        Self {
+            references: references.iter().map(FloatImage::from_image).collect(),
+            patch_size: 5,
+            max_checks: 64,
+            step_factor: 2.0,
+        }
+    }
+
+    /// Load the reference set from image files
+    pub fn from_files<P: AsRef<std::path::Path>>(paths: &[P]) -> Result<Self, String> {
+        if paths.is_empty() {
+            return Err("Reference-guided upscaling needs at least one reference image".to_string());
+        }
+        let references = paths.iter()
+            .map(|path| Image::load(path).map_err(|e| format!("Reference {}: {}", path.as_ref().display(), e)))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self::new(references))
+    }
+
+    pub fn with_patch_size(mut self, patch_size: usize) -> Self {
+        self.patch_size = patch_size.max(2);
+        self
+    }
+
+    /// Candidate patches examined per lookup; higher is slower but closer to exact
+    pub fn with_search_checks(mut self, max_checks: usize) -> Self {
+        self.max_checks = max_checks.max(1);
+        self
+    }
+
+    /// Largest magnification per step
+    pub fn with_step_factor(mut self, step_factor: f32) -> Self {
+        self.step_factor = step_factor.clamp(1.1, 4.0);
+        self
+    }
+
+    /// Subtract the mean and divide by the RMS contrast; returns (mean, contrast)
+    fn normalise(patch: &mut [f32]) -> (f32, f32) {
+        let mean = patch.iter().sum::<f32>() / patch.len() as f32;
+        let contrast = (patch.iter().map(|v| (v - mean).powi(2)).sum::<f32>() / patch.len() as f32).sqrt();
+        let scale = 1.0 / (contrast + CONTRAST_EPSILON);
+        patch.iter_mut().for_each(|v| *v = (*v - mean) * scale);
+        (mean, contrast)
+    }
+
+    fn build_database(&self, ratio: f32) -> PatchDatabase {
+        let size = self.patch_size;
+        let total: usize = self.references.iter().map(|r| r.width * r.height).sum();
+        let stride = (size / 2).max(((total / MAX_DATABASE_PATCHES) as f32).sqrt().ceil() as usize).max(1);
+
+        let mut keys = Vec::new();
+        let mut high_patches = Vec::new();
+        for reference in self.references.iter().filter(|r| r.width >= size && r.height >= size) {
+            let low = low_band(reference, ratio);
+            let high = reference.difference(&low);
+            let low_luma = Plane::luma(&low);
+
+            for &y in &patch_origins(reference.height, size, stride) {
+                for &x in &patch_origins(reference.width, size, stride) {
+                    let mut key = low_luma.patch(x as i32, y as i32, size);
+                    let (_, contrast) = Self::normalise(&mut key);
+                    if contrast < CONTRAST_EPSILON {
+                        continue;
+                    }
+
+                    let scale = 1.0 / (contrast + CONTRAST_EPSILON);
+                    keys.extend(key);
+                    for dy in 0..size {
+                        for dx in 0..size {
+                            let h = high.get(x + dx, y + dy);
+                            high_patches.push([h[0] * scale, h[1] * scale, h[2] * scale]);
+                        }
+                    }
+                }
+            }
+        }
+
+        PatchDatabase {
+            tree: KdTree::new(keys, size * size),
+            high: high_patches,
+        }
+    }
+
+    fn upscale_step(&self, current: &FloatImage, width: usize, height: usize, database: &PatchDatabase) -> FloatImage {
+        let size = self.patch_size;
+        let mut result = Psf::Bicubic.resample(current, width, height);
+        if database.tree.is_empty() {
+            return result;
+        }
+        let base_luma = Plane::luma(&result);
+
+        let mut detail = FloatImage::new(width, height);
+        let mut weights = vec![0.0f32; width * height];
+        for &py in &patch_origins(height, size, size / 2) {
+            for &px in &patch_origins(width, size, size / 2) {
+                let mut query = base_luma.patch(px as i32, py as i32, size);
+                let (_, contrast) = Self::normalise(&mut query);
+                if contrast < CONTRAST_EPSILON {
+                    continue;
+                }
+
+                let Some((index, _)) = database.tree.nearest(&query, self.max_checks) else {
+                    continue;
+                };
+                let scale = contrast + CONTRAST_EPSILON;
+                let high = &database.high[index * size * size..(index + 1) * size * size];
+                for dy in 0..size.min(height - py) {
+                    for dx in 0..size.min(width - px) {
+                        let i = (py + dy) * width + px + dx;
+                        for (d, v) in detail.data[i].iter_mut().zip(high[dy * size + dx]) {
+                            *d += v * scale;
+                        }
+                        weights[i] += 1.0;
+                    }
+                }
+            }
+        }
+
+        for ((out, d), &w) in result.data.iter_mut().zip(&detail.data).zip(&weights) {
+            if w > 0.0 {
+                for c in 0..3 {
+                    out[c] += d[c] / w;
+                }
+            }
+        }
+        result.clamp_to_range();
+        result
+    }
+}
+
+impl Upscaler for ReferenceGuided {
+    fn upscale(&self, image: &Image, scale_factor: f32) -> Image {
+        let target_w = (image.width as f32 * scale_factor).round() as usize;
+        let target_h = (image.height as f32 * scale_factor).round() as usize;
+        let mut current = FloatImage::from_image(image);
+        let mut database: Option<(f32, PatchDatabase)> = None;
+
+        while current.width < target_w && current.width >= self.patch_size && current.height >= self.patch_size {
+            let ratio = (target_w as f32 / current.width as f32).min(self.step_factor);
+            let w = ((current.width as f32 * ratio).round() as usize).clamp(current.width + 1, target_w);
+            let h = ((current.height as f32 * ratio).round() as usize).clamp(1, target_h.max(1));
+
+            // Databases depend on the step ratio; rebuild only when it changes
+            if database.as_ref().is_none_or(|(r, _)| (r - ratio).abs() > 1e-3) {
+                database = Some((ratio, self.build_database(ratio)));
+            }
+            let (_, db) = database.as_ref().unwrap();
+            current = self.upscale_step(&current, w, h, db);
+        }
+
+        if current.width != target_w || current.height != target_h {
+            current = Psf::Bicubic.resample(&current, target_w, target_h);
+        }
+        current.to_image()
+    }
+
+    fn name(&self) -> &str {
+        "Reference-Guided Example-Based SR"
+    }
+
+    fn tier(&self) -> UpscaleTier {
+        UpscaleTier::Slow
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::image::Pixel;
+    use crate::algorithms::test_support::rms_error;
+
+    #[test]
+    fn test_self_similarity() {
+        let mut img = Image::new(12, 10);
+        for y in 0..10 {
+            for x in 0..12 {
+                let val = if (x / 3 + y / 3) % 2 == 0 { 230 } else { 20 };
+                img.set_pixel(x, y, Pixel::new(val, val, val));
+            }
+        }
+
+        let result = SelfSimilarity::new().upscale(&img, 2.0);
+        assert_eq!(result.width, 24);
+        assert_eq!(result.height, 20);
+
+        // Pasted detail keeps the checker edges crisper than plain bicubic
+        let contrast = |im: &Image| -> f32 {
+            (0..im.width - 1).map(|x| {
+                let a = im.get_pixel(x, 8).unwrap().r as f32;
+                let b = im.get_pixel(x + 1, 8).unwrap().r as f32;
+                (a - b).abs()
+            }).fold(0.0, f32::max)
+        };
+        let bicubic = crate::fast::Bicubic.upscale(&img, 2.0);
+        assert!(contrast(&result) >= contrast(&bicubic));
+    }
+
+    #[test]
+    fn test_reference_guided_recovers_detail() {
+        let stripes = |w: usize, h: usize, period: usize| {
+            let mut img = Image::new(w, h);
+            for y in 0..h {
+                for x in 0..w {
+                    let val = if (x / period).is_multiple_of(2) { 220 } else { 40 };
+                    img.set_pixel(x, y, Pixel::new(val, val, val));
+                }
+            }
+            img
+        };
+
+        let truth = stripes(24, 16, 4);
+        let target = Psf::Bicubic.resample(&FloatImage::from_image(&truth), 12, 8).to_image();
+        let upscaler = ReferenceGuided::new(vec![stripes(32, 32, 4)]);
+
+        let error = |im: &Image| rms_error(im, &truth);
+        let result = upscaler.upscale(&target, 2.0);
+        assert_eq!((result.width, result.height), (24, 16));
+        assert!(error(&result) < error(&crate::fast::Bicubic.upscale(&target, 2.0)));
+        assert!(ReferenceGuided::from_files::<&str>(&[]).is_err());
+    }
+}
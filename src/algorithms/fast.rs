@@ -1,4 +1,5 @@
 use crate::algorithms::image::{Image, Pixel};
+use crate::algorithms::sampling::{EdgeMode, SamplingConfig};
 use crate::algorithms::upscaler::{Upscaler, UpscaleTier};
 use std::f32::consts::PI;
 
@@ -12,7 +13,7 @@ pub struct Bicubic;
 
 impl Bicubic {
     /// Cubic interpolation kernel (Catmull-Rom spline)
-    fn cubic_kernel(t: f32) -> f32 {
+    pub(crate) fn cubic_kernel(t: f32) -> f32 {
         let t = t.abs();
         if t < 1.0 {
             1.5 * t * t * t - 2.5 * t * t + 1.0
@@ -24,7 +25,7 @@ impl Bicubic {
     }
 
     /// Sample using bicubic interpolation
-    fn sample_bicubic(image: &Image, x: f32, y: f32) -> Pixel {
+    pub(crate) fn sample_bicubic(image: &Image, x: f32, y: f32, edge_mode: EdgeMode) -> Pixel {
         let x0 = x.floor() as i32;
         let y0 = y.floor() as i32;
 
@@ -36,7 +37,7 @@ impl Bicubic {
         // Sample 4x4 neighborhood
         for dy in -1..=2 {
             for dx in -1..=2 {
-                let px = image.get_pixel_clamped(x0 + dx, y0 + dy);
+                let px = image.get_pixel_edge(x0 + dx, y0 + dy, edge_mode);
                 let weight_x = Self::cubic_kernel(dx as f32 - fx);
                 let weight_y = Self::cubic_kernel(dy as f32 - fy);
                 let weight = weight_x * weight_y;
@@ -47,10 +48,34 @@ impl Bicubic {
 
         Pixel::weighted_average(&pixels)
     }
+
+    /// Resample to exact output dimensions (independent scale per axis)
+    pub fn resize(image: &Image, width: usize, height: usize) -> Image {
+        let scale_x = width as f32 / image.width as f32;
+        let scale_y = height as f32 / image.height as f32;
+
+        let mut result = Image::new(width, height);
+
+        for y in 0..height {
+            for x in 0..width {
+                let src_x = (x as f32 + 0.5) / scale_x - 0.5;
+                let src_y = (y as f32 + 0.5) / scale_y - 0.5;
+
+                let pixel = Self::sample_bicubic(image, src_x, src_y, EdgeMode::Clamp);
+                result.set_pixel(x, y, pixel);
+            }
+        }
+
+        result
+    }
 }
 
 impl Upscaler for Bicubic {
     fn upscale(&self, image: &Image, scale_factor: f32) -> Image {
+        self.upscale_with_sampling(image, scale_factor, &SamplingConfig::default())
+    }
+
+    fn upscale_with_sampling(&self, image: &Image, scale_factor: f32, sampling: &SamplingConfig) -> Image {
         let new_width = (image.width as f32 * scale_factor).round() as usize;
         let new_height = (image.height as f32 * scale_factor).round() as usize;
 
@@ -58,10 +83,10 @@ impl Upscaler for Bicubic {
 
         for y in 0..new_height {
             for x in 0..new_width {
-                let src_x = (x as f32 + 0.5) / scale_factor - 0.5;
-                let src_y = (y as f32 + 0.5) / scale_factor - 0.5;
+                let src_x = sampling.alignment.map(x, scale_factor);
+                let src_y = sampling.alignment.map(y, scale_factor);
 
-                let pixel = Self::sample_bicubic(image, src_x, src_y);
+                let pixel = Self::sample_bicubic(image, src_x, src_y, sampling.edge_mode);
                 result.set_pixel(x, y, pixel);
             }
         }
@@ -73,6 +98,10 @@ impl Upscaler for Bicubic {
         "Bicubic"
     }
 
+    fn supports_sampling(&self) -> bool {
+        true
+    }
+
     fn tier(&self) -> UpscaleTier {
         UpscaleTier::Fast
     }
@@ -105,6 +134,11 @@ impl Lanczos {
         Self { lobes: 4 }
     }
 
+    /// Create Lanczos upscaler with a custom lobe count
+    pub fn with_lobes(lobes: i32) -> Self {
+        Self { lobes: lobes.max(1) }
+    }
+
     /// Lanczos kernel function
     fn lanczos_kernel(&self, t: f32) -> f32 {
         let t = t.abs();
@@ -123,7 +157,7 @@ impl Lanczos {
     }
 
     /// Sample using Lanczos interpolation
-    fn sample_lanczos(&self, image: &Image, x: f32, y: f32) -> Pixel {
+    pub(crate) fn sample_lanczos(&self, image: &Image, x: f32, y: f32, edge_mode: EdgeMode) -> Pixel {
         let x0 = x.floor() as i32;
         let y0 = y.floor() as i32;
 
@@ -136,7 +170,7 @@ impl Lanczos {
         let range = self.lobes;
         for dy in (-range + 1)..=range {
             for dx in (-range + 1)..=range {
-                let px = image.get_pixel_clamped(x0 + dx, y0 + dy);
+                let px = image.get_pixel_edge(x0 + dx, y0 + dy, edge_mode);
                 let weight_x = self.lanczos_kernel(dx as f32 - fx);
                 let weight_y = self.lanczos_kernel(dy as f32 - fy);
                 let weight = weight_x * weight_y;
@@ -151,6 +185,10 @@ impl Lanczos {
 
 impl Upscaler for Lanczos {
     fn upscale(&self, image: &Image, scale_factor: f32) -> Image {
+        self.upscale_with_sampling(image, scale_factor, &SamplingConfig::default())
+    }
+
+    fn upscale_with_sampling(&self, image: &Image, scale_factor: f32, sampling: &SamplingConfig) -> Image {
         let new_width = (image.width as f32 * scale_factor).round() as usize;
         let new_height = (image.height as f32 * scale_factor).round() as usize;
 
@@ -158,10 +196,10 @@ impl Upscaler for Lanczos {
 
         for y in 0..new_height {
             for x in 0..new_width {
-                let src_x = (x as f32 + 0.5) / scale_factor - 0.5;
-                let src_y = (y as f32 + 0.5) / scale_factor - 0.5;
+                let src_x = sampling.alignment.map(x, scale_factor);
+                let src_y = sampling.alignment.map(y, scale_factor);
 
-                let pixel = self.sample_lanczos(image, src_x, src_y);
+                let pixel = self.sample_lanczos(image, src_x, src_y, sampling.edge_mode);
                 result.set_pixel(x, y, pixel);
             }
         }
@@ -178,6 +216,10 @@ impl Upscaler for Lanczos {
         }
     }
 
+    fn supports_sampling(&self) -> bool {
+        true
+    }
+
     fn tier(&self) -> UpscaleTier {
         UpscaleTier::Fast
     }
@@ -189,6 +231,218 @@ impl Default for Lanczos {
     }
 }
 
+/// Elliptical Weighted Average (EWA) resampling with a Jinc-windowed-Jinc kernel
+///
+/// Radially symmetric counterpart of Lanczos (mpv's `ewa_lanczos`): every source
+/// pixel within `radius` of the sample point is weighted by its Euclidean
+/// distance, so diagonal edges avoid the axis-aligned artifacts of separable kernels.
+/// Time complexity: O(n * radius²) where n is output pixels (~33 samples per pixel)
+/// Space complexity: O(1) working memory plus a small kernel lookup table
+pub struct EwaLanczos {
+    /// Kernel support in source pixels
+    radius: f32,
+    /// Kernel stretch factor (< 1.0 sharpens, > 1.0 blurs)
+    blur: f32,
+    /// Kernel weights indexed by squared distance
+    lut: Vec<f32>,
+}
+
+impl EwaLanczos {
+    /// Third zero of the Jinc function (mpv's default EWA radius)
+    const DEFAULT_RADIUS: f32 = 3.238_315_5;
+
+    /// First zero of the Jinc function, used to stretch the window to the radius
+    const JINC_FIRST_ZERO: f64 = 1.219_669_891_266_504_5;
+
+    /// Blur that minimises the kernel's square error (mpv's `ewa_lanczossharp`)
+    const SHARP_BLUR: f32 = 0.981_250_6;
+
+    /// Entries in the squared-distance lookup table
+    const LUT_SIZE: usize = 1024;
+
+    /// Create with mpv's `ewa_lanczos` defaults (radius 3.2383, no blur)
+    pub fn new() -> Self {
+        Self::with_params(Self::DEFAULT_RADIUS, 1.0)
+    }
+
+    /// Create with mpv's `ewa_lanczossharp` blur
+    pub fn sharp() -> Self {
+        Self::with_params(Self::DEFAULT_RADIUS, Self::SHARP_BLUR)
+    }
+
+    /// Create with a custom radius and blur
+    pub fn with_params(radius: f32, blur: f32) -> Self {
+        let radius = radius.max(0.5);
+        let blur = blur.max(0.1);
+
+        let r2_max = (radius * radius) as f64;
+        let lut = (0..Self::LUT_SIZE)
+            .map(|i| {
+                let r = (r2_max * i as f64 / (Self::LUT_SIZE - 1) as f64).sqrt();
+                Self::kernel(r, radius as f64, blur as f64) as f32
+            })
+            .collect();
+
+        Self { radius, blur, lut }
+    }
+
+    /// Bessel function of the first kind, order one (Numerical Recipes approximation)
+    fn bessel_j1(x: f64) -> f64 {
+        let ax = x.abs();
+        if ax < 8.0 {
+            let y = x * x;
+            let num = x
+                * (72362614232.0
+                    + y * (-7895059235.0
+                        + y * (242396853.1
+                            + y * (-2972611.439 + y * (15704.48260 + y * -30.16036606)))));
+            let den = 144725228442.0
+                + y * (2300535178.0 + y * (18583304.74 + y * (99447.43394 + y * (376.9991397 + y))));
+            num / den
+        } else {
+            let z = 8.0 / ax;
+            let y = z * z;
+            let xx = ax - 2.356194491;
+            let p = 1.0
+                + y * (0.183105e-2 + y * (-0.3516396496e-4 + y * (0.2457520174e-5 + y * -0.240337019e-6)));
+            let q = 0.04687499995
+                + y * (-0.2002690873e-3 + y * (0.8449199096e-5 + y * (-0.88228987e-6 + y * 0.105787412e-6)));
+            let ans = (std::f64::consts::FRAC_2_PI / ax).sqrt() * (xx.cos() * p - z * xx.sin() * q);
+            if x < 0.0 { -ans } else { ans }
+        }
+    }
+
+    /// Normalised Jinc: 2 * J1(pi * x) / (pi * x), equal to 1 at the origin
+    fn jinc(x: f64) -> f64 {
+        if x.abs() < 1e-8 {
+            return 1.0;
+        }
+        let pi_x = std::f64::consts::PI * x;
+        2.0 * Self::bessel_j1(pi_x) / pi_x
+    }
+
+    /// Jinc-windowed Jinc at distance `r` (in source pixels)
+    fn kernel(r: f64, radius: f64, blur: f64) -> f64 {
+        if r >= radius {
+            return 0.0;
+        }
+        let window = Self::jinc(r * Self::JINC_FIRST_ZERO / radius);
+        Self::jinc(r / blur) * window
+    }
+
+    /// Look up the kernel weight for a squared distance in kernel units
+    fn weight(&self, r2: f32) -> f32 {
+        let r2_max = self.radius * self.radius;
+        if r2 >= r2_max {
+            return 0.0;
+        }
+        let index = (r2 / r2_max * (Self::LUT_SIZE - 1) as f32).round() as usize;
+        self.lut[index.min(Self::LUT_SIZE - 1)]
+    }
+
+    /// Sample using EWA; `support` widens the kernel when downscaling
+    pub(crate) fn sample_ewa(&self, image: &Image, x: f32, y: f32, support: f32, edge_mode: EdgeMode) -> Pixel {
+        self.sample_ewa_footprint(image, x, y, [support, 0.0, 0.0, support], edge_mode)
+    }
+
+    /// Sample using EWA over the ellipse an output pixel covers in the source
+    ///
+    /// `jacobian` is `[dx/du, dx/dv, dy/du, dy/dv]`: the source displacement of
+    /// one output pixel step. The footprint `J * J^T` has its axes clamped to
+    /// at least one source pixel, so enlarged directions keep the plain kernel
+    /// and shrunk or sheared ones are filtered over their full extent.
+    pub(crate) fn sample_ewa_footprint(&self, image: &Image, x: f32, y: f32, jacobian: [f32; 4], edge_mode: EdgeMode) -> Pixel {
+        let [a, b, c, d] = jacobian;
+        let (sxx, sxy, syy) = (a * a + b * b, a * c + b * d, c * c + d * d);
+
+        // Eigen-decomposition of the symmetric footprint
+        let mean = (sxx + syy) / 2.0;
+        let spread = (((sxx - syy) / 2.0).powi(2) + sxy * sxy).sqrt();
+        let (l1, l2) = ((mean + spread).max(1.0), (mean - spread).max(1.0));
+        let angle = 0.5 * (2.0 * sxy).atan2(sxx - syy);
+        let (sin, cos) = angle.sin_cos();
+
+        // Clamped footprint and its inverse, the quadratic form of the ellipse
+        let fxx = l1 * cos * cos + l2 * sin * sin;
+        let fyy = l1 * sin * sin + l2 * cos * cos;
+        let fxy = (l1 - l2) * sin * cos;
+        let det = fxx * fyy - fxy * fxy;
+        let (qxx, qxy, qyy) = (fyy / det, -fxy / det, fxx / det);
+
+        let reach_x = self.radius * fxx.sqrt();
+        let reach_y = self.radius * fyy.sqrt();
+        let x_start = (x - reach_x).ceil() as i32;
+        let x_end = (x + reach_x).floor() as i32;
+        let y_start = (y - reach_y).ceil() as i32;
+        let y_end = (y + reach_y).floor() as i32;
+
+        let mut pixels = Vec::new();
+
+        for sy in y_start..=y_end {
+            for sx in x_start..=x_end {
+                let dx = sx as f32 - x;
+                let dy = sy as f32 - y;
+                let weight = self.weight(qxx * dx * dx + 2.0 * qxy * dx * dy + qyy * dy * dy);
+                if weight != 0.0 {
+                    pixels.push((image.get_pixel_edge(sx, sy, edge_mode), weight));
+                }
+            }
+        }
+
+        Pixel::weighted_average(&pixels)
+    }
+}
+
+impl Upscaler for EwaLanczos {
+    fn upscale(&self, image: &Image, scale_factor: f32) -> Image {
+        self.upscale_with_sampling(image, scale_factor, &SamplingConfig::default())
+    }
+
+    fn upscale_with_sampling(&self, image: &Image, scale_factor: f32, sampling: &SamplingConfig) -> Image {
+        let new_width = (image.width as f32 * scale_factor).round() as usize;
+        let new_height = (image.height as f32 * scale_factor).round() as usize;
+
+        // When shrinking, stretch the kernel over the larger source footprint
+        let support = (1.0 / scale_factor).max(1.0);
+
+        let mut result = Image::new(new_width, new_height);
+
+        for y in 0..new_height {
+            for x in 0..new_width {
+                let src_x = sampling.alignment.map(x, scale_factor);
+                let src_y = sampling.alignment.map(y, scale_factor);
+
+                let pixel = self.sample_ewa(image, src_x, src_y, support, sampling.edge_mode);
+                result.set_pixel(x, y, pixel);
+            }
+        }
+
+        result
+    }
+
+    fn name(&self) -> &str {
+        if self.blur < 1.0 {
+            "EWA Lanczos Sharp (Jinc)"
+        } else {
+            "EWA Lanczos (Jinc)"
+        }
+    }
+
+    fn supports_sampling(&self) -> bool {
+        true
+    }
+
+    fn tier(&self) -> UpscaleTier {
+        UpscaleTier::Fast
+    }
+}
+
+impl Default for EwaLanczos {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -240,4 +494,64 @@ mod tests {
         let _ = normal.upscale(&img, 2.0);
         let _ = hq.upscale(&img, 2.0);
     }
+
+    #[test]
+    fn test_ewa_lanczos_upscale() {
+        let img = create_test_image();
+        let upscaler = EwaLanczos::new();
+        let result = upscaler.upscale(&img, 2.0);
+
+        assert_eq!(result.width, 8);
+        assert_eq!(result.height, 8);
+    }
+
+    #[test]
+    fn test_ewa_kernel_shape() {
+        // Jinc is 1 at the origin, crosses zero at ~1.2197 and vanishes past the radius
+        assert!((EwaLanczos::jinc(0.0) - 1.0).abs() < 1e-6);
+        assert!(EwaLanczos::jinc(EwaLanczos::JINC_FIRST_ZERO).abs() < 1e-4);
+
+        let ewa = EwaLanczos::new();
+        assert!((ewa.weight(0.0) - 1.0).abs() < 1e-6);
+        assert_eq!(ewa.weight(ewa.radius * ewa.radius), 0.0);
+    }
+
+    #[test]
+    fn test_ewa_flat_image_stays_flat() {
+        let mut img = Image::new(5, 5);
+        for pixel in img.pixels.iter_mut() {
+            *pixel = Pixel::new(100, 150, 200);
+        }
+
+        // Weights are normalised, so a constant input stays constant (up to truncation)
+        let result = EwaLanczos::sharp().upscale(&img, 3.0);
+        for pixel in &result.pixels {
+            assert!(pixel.r.abs_diff(100) <= 1);
+            assert!(pixel.g.abs_diff(150) <= 1);
+            assert!(pixel.b.abs_diff(200) <= 1);
+        }
+    }
+
+    #[test]
+    fn test_wrap_edge_mode_is_seamless() {
+        // With wrapped borders, shifting a tile by one source pixel must shift
+        // the upscaled tile by exactly `scale` output pixels
+        let img = create_test_image();
+        let mut rolled = Image::new(4, 4);
+        for y in 0..4 {
+            for x in 0..4 {
+                rolled.set_pixel((x + 1) % 4, y, img.get_pixel(x, y).unwrap());
+            }
+        }
+
+        let sampling = SamplingConfig::new().with_edge_mode(EdgeMode::Wrap);
+        let a = Bicubic.upscale_with_sampling(&img, 2.0, &sampling);
+        let b = Bicubic.upscale_with_sampling(&rolled, 2.0, &sampling);
+
+        for y in 0..8 {
+            for x in 0..8 {
+                assert_eq!(a.get_pixel(x, y), b.get_pixel((x + 2) % 8, y));
+            }
+        }
+    }
 }
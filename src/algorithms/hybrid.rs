@@ -0,0 +1,186 @@
+use crate::algorithms::fast::Lanczos;
+use crate::algorithms::image::{Image, Pixel};
+use crate::algorithms::medium::EdgeDirected;
+use crate::algorithms::patch::Plane;
+use crate::algorithms::sampling::SamplingConfig;
+use crate::algorithms::upscaler::{Upscaler, UpscaleTier};
+
+/// Per-pixel blend of an edge algorithm and a flat-region algorithm
+///
+/// Both algorithms upscale the whole image. An edge map is built from
+/// `EdgeDirected`'s gradient magnitude, widened to the 3x3 maximum so the
+/// ringing either side of an edge counts as edge too, and mapped to a weight
+/// with a smoothstep between the `low` and `high` gradient thresholds. The
+/// weight is interpolated bilinearly to the output grid and each output pixel
+/// mixes the two results by it: edge-directed sharpness on contours, Lanczos
+/// detail in flat and textured areas, with no seams between them.
+///
+/// Gradients are summed over the RGB central differences, so a one-pixel step
+/// of `d` levels in a grey image scores `3 * d`.
+///
+/// Time complexity: O(edge + flat) plus O(n) for the edge map
+/// Space complexity: O(n) for both results and the weights
+pub struct EdgeHybrid {
+    edge: Box<dyn Upscaler>,
+    flat: Box<dyn Upscaler>,
+    low: f32,
+    high: f32,
+}
+
+impl EdgeHybrid {
+    /// Edge-directed on edges, Lanczos3 elsewhere, ramping from 48 to 192
+    pub fn new() -> Self {
+        Self {
+            edge: Box::new(EdgeDirected),
+            flat: Box::new(Lanczos::new()),
+            low: 48.0,
+            high: 192.0,
+        }
+    }
+
+    /// Look both algorithms up by registry name, e.g. `xbr` and `bicubic`
+    pub fn from_names(edge: &str, flat: &str) -> Result<Self, String> {
+        let lookup = |name: &str| crate::get_upscaler(name).ok_or_else(|| format!("Unknown algorithm: {}", name));
+        Ok(Self::new().with_algorithms(lookup(edge)?, lookup(flat)?))
+    }
+
+    pub fn with_algorithms(mut self, edge: Box<dyn Upscaler>, flat: Box<dyn Upscaler>) -> Self {
+        self.edge = edge;
+        self.flat = flat;
+        self
+    }
+
+    /// Gradients at or below `low` use only the flat algorithm, at or above
+    /// `high` only the edge algorithm
+    pub fn with_thresholds(mut self, low: f32, high: f32) -> Self {
+        self.low = low.max(0.0);
+        self.high = high.max(self.low + 1.0);
+        self
+    }
+
+    /// Edge weight of every source pixel, 0 (flat) to 1 (edge)
+    pub fn edge_weights(&self, image: &Image) -> Plane {
+        let (w, h) = (image.width, image.height);
+        let mut gradient = Plane::new(w, h);
+        for y in 0..h {
+            for x in 0..w {
+                gradient.data[y * w + x] = EdgeDirected::gradient_magnitude(image, x as i32, y as i32);
+            }
+        }
+
+        let mut weights = Plane::new(w, h);
+        for y in 0..h as i32 {
+            for x in 0..w as i32 {
+                let widest = (-1..=1)
+                    .flat_map(|dy| (-1..=1).map(move |dx| (dx, dy)))
+                    .map(|(dx, dy)| gradient.get_clamped(x + dx, y + dy))
+                    .fold(0.0f32, f32::max);
+                let t = ((widest - self.low) / (self.high - self.low)).clamp(0.0, 1.0);
+                weights.data[y as usize * w + x as usize] = t * t * (3.0 - 2.0 * t);
+            }
+        }
+        weights
+    }
+
+    fn blend(&self, image: &Image, scale_factor: f32, edge: Image, flat: Image, sampling: &SamplingConfig) -> Image {
+        let weights = self.edge_weights(image);
+        let mut result = Image::new(edge.width, edge.height);
+
+        for y in 0..edge.height {
+            let sy = sampling.alignment.map(y, scale_factor);
+            for x in 0..edge.width {
+                let sx = sampling.alignment.map(x, scale_factor);
+                let (x0, y0) = (sx.floor(), sy.floor());
+                let (fx, fy) = (sx - x0, sy - y0);
+                let (x0, y0) = (x0 as i32, y0 as i32);
+                let top = weights.get_clamped(x0, y0) * (1.0 - fx) + weights.get_clamped(x0 + 1, y0) * fx;
+                let bottom = weights.get_clamped(x0, y0 + 1) * (1.0 - fx) + weights.get_clamped(x0 + 1, y0 + 1) * fx;
+                let weight = top * (1.0 - fy) + bottom * fy;
+
+                // The two results normally share a size; fall back to the nearest flat pixel if not
+                let flat_pixel = flat
+                    .get_pixel(x * flat.width / edge.width, y * flat.height / edge.height)
+                    .unwrap_or_else(Pixel::black);
+                let edge_pixel = edge.get_pixel(x, y).unwrap_or_else(Pixel::black);
+                result.set_pixel(x, y, Self::mix(flat_pixel, edge_pixel, weight));
+            }
+        }
+        result
+    }
+
+    /// `Pixel::lerp` truncates; round so a weight of 0 or 1 reproduces its input exactly
+    fn mix(a: Pixel, b: Pixel, t: f32) -> Pixel {
+        let channel = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round().clamp(0.0, 255.0) as u8;
+        Pixel::new(channel(a.r, b.r), channel(a.g, b.g), channel(a.b, b.b))
+    }
+}
+
+impl Upscaler for EdgeHybrid {
+    fn upscale(&self, image: &Image, scale_factor: f32) -> Image {
+        let edge = self.edge.upscale(image, scale_factor);
+        let flat = self.flat.upscale(image, scale_factor);
+        self.blend(image, scale_factor, edge, flat, &SamplingConfig::default())
+    }
+
+    fn upscale_with_sampling(&self, image: &Image, scale_factor: f32, sampling: &SamplingConfig) -> Image {
+        let edge = self.edge.upscale_with_sampling(image, scale_factor, sampling);
+        let flat = self.flat.upscale_with_sampling(image, scale_factor, sampling);
+        self.blend(image, scale_factor, edge, flat, sampling)
+    }
+
+    fn name(&self) -> &str {
+        "Edge-Map Hybrid"
+    }
+
+    fn supports_sampling(&self) -> bool {
+        self.edge.supports_sampling() && self.flat.supports_sampling()
+    }
+
+    fn tier(&self) -> UpscaleTier {
+        self.edge.tier().max(self.flat.tier())
+    }
+}
+
+impl Default for EdgeHybrid {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::fast::Bicubic;
+    use crate::algorithms::instant::NearestNeighbor;
+
+    #[test]
+    fn test_hybrid_uses_each_algorithm_where_it_belongs() {
+        // A gentle ramp on the left, a hard step on the right
+        let mut img = Image::new(16, 8);
+        for y in 0..8 {
+            for x in 0..16 {
+                let v = if x < 12 { 60 + x as u8 * 4 } else { 250 };
+                img.set_pixel(x, y, Pixel::new(v, v, v));
+            }
+        }
+
+        let hybrid = EdgeHybrid::new().with_algorithms(Box::new(NearestNeighbor), Box::new(Bicubic));
+        let weights = hybrid.edge_weights(&img);
+        assert_eq!(weights.get(2, 4), 0.0);
+        assert_eq!(weights.get(11, 4), 1.0);
+
+        let result = hybrid.upscale(&img, 2.0);
+        let bicubic = Bicubic.upscale(&img, 2.0);
+        let nearest = NearestNeighbor.upscale(&img, 2.0);
+        assert_eq!(result.get_pixel(8, 8), bicubic.get_pixel(8, 8));
+        assert_eq!(result.get_pixel(23, 8), nearest.get_pixel(23, 8));
+    }
+
+    #[test]
+    fn test_from_names() {
+        let hybrid = EdgeHybrid::from_names("xbr", "bicubic").unwrap().with_thresholds(10.0, 5.0);
+        assert_eq!(hybrid.tier(), UpscaleTier::Medium);
+        assert_eq!((hybrid.low, hybrid.high), (10.0, 11.0));
+        assert!(EdgeHybrid::from_names("edi", "nonsense").is_err());
+    }
+}
@@ -1,3 +1,5 @@
+use crate::algorithms::sampling::EdgeMode;
+
 /// Simple RGB pixel representation
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Pixel {
@@ -142,6 +144,18 @@ impl Image {
         self.pixels[y * self.width + x]
     }
 
+    /// Get pixel with out-of-bounds coordinates resolved by the given edge mode
+    pub fn get_pixel_edge(&self, x: i32, y: i32, edge_mode: EdgeMode) -> Pixel {
+        match (edge_mode.resolve(x, self.width), edge_mode.resolve(y, self.height)) {
+            (Some(x), Some(y)) => self.pixels[y * self.width + x],
+            _ => match edge_mode {
+                EdgeMode::Constant(pixel) => pixel,
+                // Other modes only fail to resolve on an empty image
+                _ => Pixel::black(),
+            },
+        }
+    }
+
     /// Sample pixel at floating-point coordinates using nearest neighbor
     pub fn sample_nearest(&self, x: f32, y: f32) -> Pixel {
         let x = x.round().clamp(0.0, self.width as f32 - 1.0) as usize;
@@ -149,3 +163,87 @@ impl Image {
         self.pixels[y * self.width + x]
     }
 }
+
+/// RGB image with f32 channels, for iterative algorithms that must not
+/// quantise or clip intermediate values
+#[derive(Debug, Clone)]
+pub struct FloatImage {
+    pub width: usize,
+    pub height: usize,
+    pub data: Vec<[f32; 3]>,
+}
+
+impl FloatImage {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            data: vec![[0.0; 3]; width * height],
+        }
+    }
+
+    pub fn from_image(image: &Image) -> Self {
+        Self {
+            width: image.width,
+            height: image.height,
+            data: image.pixels.iter().map(|p| [p.r as f32, p.g as f32, p.b as f32]).collect(),
+        }
+    }
+
+    /// Round and clamp back to 8-bit pixels
+    pub fn to_image(&self) -> Image {
+        let to_u8 = |v: f32| v.round().clamp(0.0, 255.0) as u8;
+        Image {
+            width: self.width,
+            height: self.height,
+            pixels: self.data.iter().map(|c| Pixel::new(to_u8(c[0]), to_u8(c[1]), to_u8(c[2]))).collect(),
+        }
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> [f32; 3] {
+        self.data[y * self.width + x]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, value: [f32; 3]) {
+        self.data[y * self.width + x] = value;
+    }
+
+    pub fn get_clamped(&self, x: i32, y: i32) -> [f32; 3] {
+        let x = x.clamp(0, self.width as i32 - 1) as usize;
+        let y = y.clamp(0, self.height as i32 - 1) as usize;
+        self.data[y * self.width + x]
+    }
+
+    /// Per-channel `self - other`
+    pub fn difference(&self, other: &FloatImage) -> FloatImage {
+        let data = self.data.iter().zip(&other.data)
+            .map(|(a, b)| [a[0] - b[0], a[1] - b[1], a[2] - b[2]])
+            .collect();
+        FloatImage { width: self.width, height: self.height, data }
+    }
+
+    /// `self += scale * other`
+    pub fn add_scaled(&mut self, other: &FloatImage, scale: f32) {
+        for (a, b) in self.data.iter_mut().zip(&other.data) {
+            for c in 0..3 {
+                a[c] += scale * b[c];
+            }
+        }
+    }
+
+    /// Clamp every channel to the displayable 0..=255 range
+    pub fn clamp_to_range(&mut self) {
+        for value in self.data.iter_mut().flatten() {
+            *value = value.clamp(0.0, 255.0);
+        }
+    }
+
+    /// Root mean square over all channels
+    pub fn rms(&self) -> f32 {
+        if self.data.is_empty() {
+            return 0.0;
+        }
+        let sum: f64 = self.data.iter().flatten().map(|&v| (v as f64).powi(2)).sum();
+        (sum / (self.data.len() * 3) as f64).sqrt() as f32
+    }
+}
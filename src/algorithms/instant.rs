@@ -1,15 +1,54 @@
 use crate::algorithms::image::{Image, Pixel};
+use crate::algorithms::sampling::{EdgeMode, SamplingConfig};
 use crate::algorithms::upscaler::{Upscaler, UpscaleTier};
 
 /// Nearest neighbor upscaling - the fastest possible algorithm
 ///
 /// Simply replicates pixels. No interpolation. Perfect for pixel art.
+/// Centre-aligned like every other upscaler unless given a sampling config.
 /// Time complexity: O(n) where n is output pixels
 /// Space complexity: O(1) working memory
 pub struct NearestNeighbor;
 
+impl NearestNeighbor {
+    /// Replicate every pixel into a `factor` x `factor` block, copying whole rows
+    pub fn upscale_integer(image: &Image, factor: usize) -> Image {
+        let factor = factor.max(1);
+        let width = image.width * factor;
+        let mut pixels = Vec::with_capacity(width * image.height * factor);
+        for row in image.pixels.chunks_exact(image.width.max(1)) {
+            let start = pixels.len();
+            for &pixel in row {
+                pixels.extend(std::iter::repeat_n(pixel, factor));
+            }
+            for _ in 1..factor {
+                pixels.extend_from_within(start..start + width);
+            }
+        }
+        Image { width, height: image.height * factor, pixels }
+    }
+}
+
+/// Whole-number value of `scale_factor`, if it is one
+pub(crate) fn integer_factor(scale_factor: f32) -> Option<usize> {
+    let rounded = scale_factor.round();
+    ((scale_factor - rounded).abs() < 1e-4 && rounded >= 1.0).then_some(rounded as usize)
+}
+
 impl Upscaler for NearestNeighbor {
     fn upscale(&self, image: &Image, scale_factor: f32) -> Image {
+        self.upscale_with_sampling(image, scale_factor, &SamplingConfig::default())
+    }
+
+    fn upscale_with_sampling(&self, image: &Image, scale_factor: f32, sampling: &SamplingConfig) -> Image {
+        // At whole factors the mapping repeats every `factor` pixels; when the
+        // first period all reads pixel 0, every output reads floor(x / factor)
+        if let Some(factor) = integer_factor(scale_factor)
+            && (0..factor).all(|x| sampling.alignment.map_nearest(x, scale_factor) == 0)
+        {
+            return Self::upscale_integer(image, factor);
+        }
+
         let new_width = (image.width as f32 * scale_factor).round() as usize;
         let new_height = (image.height as f32 * scale_factor).round() as usize;
 
@@ -18,14 +57,10 @@ impl Upscaler for NearestNeighbor {
         for y in 0..new_height {
             for x in 0..new_width {
                 // Map output coordinates back to input coordinates
-                let src_x = (x as f32 / scale_factor).floor() as usize;
-                let src_y = (y as f32 / scale_factor).floor() as usize;
-
-                // Clamp to valid range
-                let src_x = src_x.min(image.width - 1);
-                let src_y = src_y.min(image.height - 1);
+                let src_x = sampling.alignment.map_nearest(x, scale_factor);
+                let src_y = sampling.alignment.map_nearest(y, scale_factor);
 
-                result.set_pixel(x, y, image.get_pixel(src_x, src_y).unwrap());
+                result.set_pixel(x, y, image.get_pixel_edge(src_x, src_y, sampling.edge_mode));
             }
         }
 
@@ -36,6 +71,10 @@ impl Upscaler for NearestNeighbor {
         "Nearest Neighbor"
     }
 
+    fn supports_sampling(&self) -> bool {
+        true
+    }
+
     fn tier(&self) -> UpscaleTier {
         UpscaleTier::Instant
     }
@@ -51,7 +90,7 @@ pub struct Bilinear;
 
 impl Bilinear {
     /// Sample a pixel using bilinear interpolation at floating-point coordinates
-    fn sample_bilinear(image: &Image, x: f32, y: f32) -> Pixel {
+    pub(crate) fn sample_bilinear(image: &Image, x: f32, y: f32, edge_mode: EdgeMode) -> Pixel {
         // Get the four surrounding pixels
         let x0 = x.floor() as i32;
         let y0 = y.floor() as i32;
@@ -62,11 +101,11 @@ impl Bilinear {
         let fx = x - x0 as f32;
         let fy = y - y0 as f32;
 
-        // Get the four corner pixels (with clamping for edge cases)
-        let p00 = image.get_pixel_clamped(x0, y0);
-        let p10 = image.get_pixel_clamped(x1, y0);
-        let p01 = image.get_pixel_clamped(x0, y1);
-        let p11 = image.get_pixel_clamped(x1, y1);
+        // Get the four corner pixels (edge mode resolves out-of-bounds reads)
+        let p00 = image.get_pixel_edge(x0, y0, edge_mode);
+        let p10 = image.get_pixel_edge(x1, y0, edge_mode);
+        let p01 = image.get_pixel_edge(x0, y1, edge_mode);
+        let p11 = image.get_pixel_edge(x1, y1, edge_mode);
 
         // Interpolate in X direction
         let top = Pixel::lerp(p00, p10, fx);
@@ -79,6 +118,10 @@ impl Bilinear {
 
 impl Upscaler for Bilinear {
     fn upscale(&self, image: &Image, scale_factor: f32) -> Image {
+        self.upscale_with_sampling(image, scale_factor, &SamplingConfig::default())
+    }
+
+    fn upscale_with_sampling(&self, image: &Image, scale_factor: f32, sampling: &SamplingConfig) -> Image {
         let new_width = (image.width as f32 * scale_factor).round() as usize;
         let new_height = (image.height as f32 * scale_factor).round() as usize;
 
@@ -87,10 +130,10 @@ impl Upscaler for Bilinear {
         for y in 0..new_height {
             for x in 0..new_width {
                 // Map output coordinates to input space (continuous)
-                let src_x = (x as f32 + 0.5) / scale_factor - 0.5;
-                let src_y = (y as f32 + 0.5) / scale_factor - 0.5;
+                let src_x = sampling.alignment.map(x, scale_factor);
+                let src_y = sampling.alignment.map(y, scale_factor);
 
-                let pixel = Self::sample_bilinear(image, src_x, src_y);
+                let pixel = Self::sample_bilinear(image, src_x, src_y, sampling.edge_mode);
                 result.set_pixel(x, y, pixel);
             }
         }
@@ -102,11 +145,165 @@ impl Upscaler for Bilinear {
         "Bilinear"
     }
 
+    fn supports_sampling(&self) -> bool {
+        true
+    }
+
     fn tier(&self) -> UpscaleTier {
         UpscaleTier::Instant
     }
 }
 
+/// What integer-only scaling does with a fractional factor
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IntegerFit {
+    /// Reject it
+    Refuse,
+    /// Scale by the whole part and centre the result on a border of this colour
+    Pad(Pixel),
+}
+
+impl IntegerFit {
+    /// Parse `refuse`, `pad` (black) or `pad:R,G,B`
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "refuse" => Some(IntegerFit::Refuse),
+            "pad" => Some(IntegerFit::Pad(Pixel::black())),
+            other => match EdgeMode::parse(&format!("constant:{}", other.strip_prefix("pad:")?)) {
+                Some(EdgeMode::Constant(border)) => Some(IntegerFit::Pad(border)),
+                _ => None,
+            },
+        }
+    }
+}
+
+/// Sharp bilinear: integer nearest-neighbour prescale, then bilinear for the rest
+///
+/// Prescales by the largest whole factor not above the target with block
+/// copies, then bilinear interpolation covers the fractional remainder. Every
+/// source pixel keeps an almost equal width, with blending confined to a
+/// single output pixel at each pixel boundary, so retro graphics stay crisp
+/// without the uneven columns of plain nearest neighbour at 2.5x or 3.75x.
+///
+/// In integer-only mode the bilinear pass is skipped entirely: whole factors
+/// are pure block copies and fractional ones are refused or letterboxed.
+/// Time complexity: O(n) where n is output pixels
+/// Space complexity: O(n) for the prescaled image
+pub struct SharpBilinear {
+    integer_only: Option<IntegerFit>,
+}
+
+impl SharpBilinear {
+    pub fn new() -> Self {
+        Self { integer_only: None }
+    }
+
+    /// Only ever scale by whole factors
+    pub fn integer_only(fit: IntegerFit) -> Self {
+        Self { integer_only: Some(fit) }
+    }
+
+    /// `sharp_bilinear`, or `integer` for integer-only scaling that refuses fractions
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "sharp_bilinear" | "sharp-bilinear" => Some(Self::new()),
+            "integer" | "integer_scale" => Some(Self::integer_only(IntegerFit::Refuse)),
+            _ => None,
+        }
+    }
+
+    /// Refusing or letterboxing fractions; has no effect unless integer-only
+    pub fn with_fit(mut self, fit: IntegerFit) -> Self {
+        if self.integer_only.is_some() {
+            self.integer_only = Some(fit);
+        }
+        self
+    }
+
+    /// Upscale, or explain why an integer-only upscale cannot reach `scale_factor`
+    pub fn try_upscale(&self, image: &Image, scale_factor: f32, sampling: &SamplingConfig) -> Result<Image, String> {
+        let new_width = (image.width as f32 * scale_factor).round() as usize;
+        let new_height = (image.height as f32 * scale_factor).round() as usize;
+        let whole = (scale_factor.floor() as usize).max(1);
+        let prescaled = NearestNeighbor::upscale_integer(image, whole);
+
+        match self.integer_only {
+            None if prescaled.width == new_width && prescaled.height == new_height => Ok(prescaled),
+            None => {
+                // Bilinear over the block copy, mapped straight to the exact output size
+                let (sx, sy) = (prescaled.width as f32 / new_width as f32, prescaled.height as f32 / new_height as f32);
+                let mut result = Image::new(new_width, new_height);
+                for y in 0..new_height {
+                    let src_y = (y as f32 + 0.5) * sy - 0.5;
+                    for x in 0..new_width {
+                        let src_x = (x as f32 + 0.5) * sx - 0.5;
+                        result.set_pixel(x, y, Bilinear::sample_bilinear(&prescaled, src_x, src_y, sampling.edge_mode));
+                    }
+                }
+                Ok(result)
+            }
+            Some(_) if integer_factor(scale_factor).is_some() => Ok(prescaled),
+            Some(IntegerFit::Refuse) => Err(format!(
+                "Integer-only scaling needs a whole scale factor, got {}x (pad instead to letterbox at {}x)",
+                scale_factor, whole
+            )),
+            Some(IntegerFit::Pad(border)) => {
+                let mut result = Image::from_pixels(new_width, new_height, vec![border; new_width * new_height])
+                    .unwrap_or_else(|| Image::new(new_width, new_height));
+                let left = (new_width as i64 - prescaled.width as i64) / 2;
+                let top = (new_height as i64 - prescaled.height as i64) / 2;
+                for y in 0..prescaled.height {
+                    for x in 0..prescaled.width {
+                        let (ox, oy) = (x as i64 + left, y as i64 + top);
+                        if ox >= 0 && oy >= 0 {
+                            result.set_pixel(ox as usize, oy as usize, prescaled.pixels[y * prescaled.width + x]);
+                        }
+                    }
+                }
+                Ok(result)
+            }
+        }
+    }
+}
+
+impl Upscaler for SharpBilinear {
+    fn upscale(&self, image: &Image, scale_factor: f32) -> Image {
+        self.upscale_with_sampling(image, scale_factor, &SamplingConfig::default())
+    }
+
+    /// The trait cannot fail, so a refused fraction is letterboxed on black;
+    /// call `try_upscale` to get the refusal instead
+    fn upscale_with_sampling(&self, image: &Image, scale_factor: f32, sampling: &SamplingConfig) -> Image {
+        match self.try_upscale(image, scale_factor, sampling) {
+            Ok(result) => result,
+            Err(_) => Self::integer_only(IntegerFit::Pad(Pixel::black()))
+                .try_upscale(image, scale_factor, sampling)
+                .unwrap_or_else(|_| image.clone()),
+        }
+    }
+
+    fn name(&self) -> &str {
+        match self.integer_only {
+            None => "Sharp Bilinear",
+            Some(_) => "Integer Scaling",
+        }
+    }
+
+    fn supports_sampling(&self) -> bool {
+        true
+    }
+
+    fn tier(&self) -> UpscaleTier {
+        UpscaleTier::Instant
+    }
+}
+
+impl Default for SharpBilinear {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -147,4 +344,38 @@ mod tests {
         // We can't test exact values due to rounding, but verify it ran
         assert!(result.get_pixel(1, 1).is_some());
     }
+
+    #[test]
+    fn test_nearest_block_copy_matches_sampling() {
+        let img = create_test_image();
+        let fast = NearestNeighbor.upscale(&img, 3.0);
+        let mut slow = Image::new(6, 6);
+        for y in 0..6 {
+            for x in 0..6 {
+                slow.set_pixel(x, y, img.get_pixel(x / 3, y / 3).unwrap());
+            }
+        }
+        assert_eq!(fast.pixels, slow.pixels);
+    }
+
+    #[test]
+    fn test_sharp_bilinear_and_integer_only() {
+        let img = create_test_image();
+        let sampling = SamplingConfig::default();
+
+        // 2.5x: interiors of the 2x blocks stay exact, boundaries blend over one pixel
+        let result = SharpBilinear::new().upscale(&img, 2.5);
+        assert_eq!((result.width, result.height), (5, 5));
+        assert_eq!(result.get_pixel(0, 0), Some(Pixel::new(0, 0, 0)));
+        assert_eq!(result.get_pixel(4, 4), Some(Pixel::new(0, 255, 0)));
+        assert_eq!(SharpBilinear::new().upscale(&img, 3.0).pixels, NearestNeighbor.upscale(&img, 3.0).pixels);
+
+        let integer = SharpBilinear::from_name("integer").unwrap();
+        assert!(integer.try_upscale(&img, 2.5, &sampling).is_err());
+        let padded = integer.with_fit(IntegerFit::Pad(Pixel::white())).try_upscale(&img, 2.5, &sampling).unwrap();
+        assert_eq!((padded.width, padded.height), (5, 5));
+        assert_eq!(padded.get_pixel(4, 4), Some(Pixel::white()));
+        assert_eq!(padded.get_pixel(0, 0), Some(Pixel::new(0, 0, 0)));
+        assert_eq!(IntegerFit::parse("pad:255,255,255"), Some(IntegerFit::Pad(Pixel::white())));
+    }
 }
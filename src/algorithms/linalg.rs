@@ -0,0 +1,66 @@
+/// Solve the symmetric positive-definite system `a x = b` (row-major `n` x `n`)
+/// by Cholesky factorisation
+///
+/// Returns `None` if `a` is not positive definite. Callers fitting noisy data
+/// should add a small ridge to the diagonal first.
+pub fn cholesky_solve(a: &[f64], b: &[f64], n: usize) -> Option<Vec<f64>> {
+    // Lower-triangular factor L with a = L Lᵀ
+    let mut l = vec![0.0; n * n];
+    for i in 0..n {
+        for j in 0..=i {
+            let sum: f64 = (0..j).map(|k| l[i * n + k] * l[j * n + k]).sum();
+            if i == j {
+                let d = a[i * n + i] - sum;
+                if d <= 0.0 || !d.is_finite() {
+                    return None;
+                }
+                l[i * n + i] = d.sqrt();
+            } else {
+                l[i * n + j] = (a[i * n + j] - sum) / l[j * n + j];
+            }
+        }
+    }
+
+    // Forward substitution L y = b, then back substitution Lᵀ x = y
+    let mut y = vec![0.0; n];
+    for i in 0..n {
+        let sum: f64 = (0..i).map(|k| l[i * n + k] * y[k]).sum();
+        y[i] = (b[i] - sum) / l[i * n + i];
+    }
+    let mut x = vec![0.0; n];
+    for i in (0..n).rev() {
+        let sum: f64 = (i + 1..n).map(|k| l[k * n + i] * x[k]).sum();
+        x[i] = (y[i] - sum) / l[i * n + i];
+    }
+
+    Some(x)
+}
+
+/// Solve the ridge-regularised normal equations `(AᵀA + ridge·I) x = Aᵀb`
+///
+/// `ata` is `AᵀA` (row-major `n` x `n`) and `atb` is `Aᵀb`. The ridge is scaled
+/// by the mean diagonal so it behaves the same for any data range.
+pub fn ridge_solve(ata: &[f64], atb: &[f64], n: usize, ridge: f64) -> Option<Vec<f64>> {
+    let mean_diagonal = (0..n).map(|i| ata[i * n + i]).sum::<f64>() / n.max(1) as f64;
+    let lambda = ridge * mean_diagonal.max(1e-12);
+
+    let mut regularised = ata.to_vec();
+    for i in 0..n {
+        regularised[i * n + i] += lambda;
+    }
+    cholesky_solve(&regularised, atb, n)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cholesky_solve() {
+        // [4 2; 2 3] x = [2; 1]  =>  x = [0.5, 0]
+        let x = cholesky_solve(&[4.0, 2.0, 2.0, 3.0], &[2.0, 1.0], 2).unwrap();
+        assert!((x[0] - 0.5).abs() < 1e-12);
+        assert!(x[1].abs() < 1e-12);
+        assert!(cholesky_solve(&[1.0, 2.0, 2.0, 1.0], &[1.0, 1.0], 2).is_none());
+    }
+}
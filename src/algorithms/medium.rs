@@ -1,4 +1,7 @@
-use crate::algorithms::image::{Image, Pixel};
+use crate::algorithms::image::{FloatImage, Image, Pixel};
+use crate::algorithms::fast::Bicubic;
+use crate::algorithms::patch::{patch_origins, Plane};
+use crate::algorithms::sampling::{EdgeMode, SamplingConfig};
 use crate::algorithms::upscaler::{Upscaler, UpscaleTier};
 
 /// Edge-Directed Interpolation (EDI)
@@ -11,7 +14,7 @@ pub struct EdgeDirected;
 
 impl EdgeDirected {
     /// Calculate gradient magnitude at a pixel
-    fn gradient_magnitude(image: &Image, x: i32, y: i32) -> f32 {
+    pub(crate) fn gradient_magnitude(image: &Image, x: i32, y: i32) -> f32 {
         let _center = image.get_pixel_clamped(x, y);
 
         let left = image.get_pixel_clamped(x - 1, y);
@@ -49,7 +52,7 @@ impl EdgeDirected {
     }
 
     /// Sample with edge-aware interpolation
-    fn sample_edge_directed(image: &Image, x: f32, y: f32) -> Pixel {
+    fn sample_edge_directed(image: &Image, x: f32, y: f32, edge_mode: EdgeMode) -> Pixel {
         let x0 = x.floor() as i32;
         let y0 = y.floor() as i32;
 
@@ -59,7 +62,7 @@ impl EdgeDirected {
 
         // If gradient is low (flat region), use bilinear
         if gradient < 10.0 {
-            return Self::bilinear_sample(image, x, y);
+            return Self::bilinear_sample(image, x, y, edge_mode);
         }
 
         // Otherwise, interpolate along the edge direction
@@ -78,7 +81,7 @@ impl EdgeDirected {
             let sample_x = x + cos_angle * offset;
             let sample_y = y + sin_angle * offset;
 
-            let px = image.get_pixel_clamped(sample_x.round() as i32, sample_y.round() as i32);
+            let px = image.get_pixel_edge(sample_x.round() as i32, sample_y.round() as i32, edge_mode);
             let weight = 1.0 - (i.abs() as f32 * 0.3);
             pixels.push((px, weight));
         }
@@ -87,17 +90,17 @@ impl EdgeDirected {
     }
 
     /// Fallback bilinear sampling
-    fn bilinear_sample(image: &Image, x: f32, y: f32) -> Pixel {
+    fn bilinear_sample(image: &Image, x: f32, y: f32, edge_mode: EdgeMode) -> Pixel {
         let x0 = x.floor() as i32;
         let y0 = y.floor() as i32;
 
         let fx = x - x0 as f32;
         let fy = y - y0 as f32;
 
-        let p00 = image.get_pixel_clamped(x0, y0);
-        let p10 = image.get_pixel_clamped(x0 + 1, y0);
-        let p01 = image.get_pixel_clamped(x0, y0 + 1);
-        let p11 = image.get_pixel_clamped(x0 + 1, y0 + 1);
+        let p00 = image.get_pixel_edge(x0, y0, edge_mode);
+        let p10 = image.get_pixel_edge(x0 + 1, y0, edge_mode);
+        let p01 = image.get_pixel_edge(x0, y0 + 1, edge_mode);
+        let p11 = image.get_pixel_edge(x0 + 1, y0 + 1, edge_mode);
 
         let top = Pixel::lerp(p00, p10, fx);
         let bottom = Pixel::lerp(p01, p11, fx);
@@ -107,6 +110,10 @@ impl EdgeDirected {
 
 impl Upscaler for EdgeDirected {
     fn upscale(&self, image: &Image, scale_factor: f32) -> Image {
+        self.upscale_with_sampling(image, scale_factor, &SamplingConfig::default())
+    }
+
+    fn upscale_with_sampling(&self, image: &Image, scale_factor: f32, sampling: &SamplingConfig) -> Image {
         let new_width = (image.width as f32 * scale_factor).round() as usize;
         let new_height = (image.height as f32 * scale_factor).round() as usize;
 
@@ -114,10 +121,10 @@ impl Upscaler for EdgeDirected {
 
         for y in 0..new_height {
             for x in 0..new_width {
-                let src_x = (x as f32 + 0.5) / scale_factor - 0.5;
-                let src_y = (y as f32 + 0.5) / scale_factor - 0.5;
+                let src_x = sampling.alignment.map(x, scale_factor);
+                let src_y = sampling.alignment.map(y, scale_factor);
 
-                let pixel = Self::sample_edge_directed(image, src_x, src_y);
+                let pixel = Self::sample_edge_directed(image, src_x, src_y, sampling.edge_mode);
                 result.set_pixel(x, y, pixel);
             }
         }
@@ -129,6 +136,10 @@ impl Upscaler for EdgeDirected {
         "Edge-Directed Interpolation"
     }
 
+    fn supports_sampling(&self) -> bool {
+        true
+    }
+
     fn tier(&self) -> UpscaleTier {
         UpscaleTier::Medium
     }
@@ -153,7 +164,7 @@ impl ScaleByRules {
     }
 
     /// Upscale 2x using pattern matching
-    fn upscale_2x(image: &Image) -> Image {
+    fn upscale_2x(image: &Image, edge_mode: EdgeMode) -> Image {
         let mut result = Image::new(image.width * 2, image.height * 2);
 
         for y in 0..image.height {
@@ -161,15 +172,16 @@ impl ScaleByRules {
                 let center = image.get_pixel(x, y).unwrap();
 
                 // Get 3x3 neighborhood
+                let (x0, y0) = (x as i32, y as i32);
                 let neighbors = [
-                    image.get_pixel_clamped(x as i32 - 1, y as i32 - 1), // top-left
-                    image.get_pixel_clamped(x as i32, y as i32 - 1),     // top
-                    image.get_pixel_clamped(x as i32 + 1, y as i32 - 1), // top-right
-                    image.get_pixel_clamped(x as i32 - 1, y as i32),     // left
-                    image.get_pixel_clamped(x as i32 + 1, y as i32),     // right
-                    image.get_pixel_clamped(x as i32 - 1, y as i32 + 1), // bottom-left
-                    image.get_pixel_clamped(x as i32, y as i32 + 1),     // bottom
-                    image.get_pixel_clamped(x as i32 + 1, y as i32 + 1), // bottom-right
+                    image.get_pixel_edge(x0 - 1, y0 - 1, edge_mode), // top-left
+                    image.get_pixel_edge(x0, y0 - 1, edge_mode),     // top
+                    image.get_pixel_edge(x0 + 1, y0 - 1, edge_mode), // top-right
+                    image.get_pixel_edge(x0 - 1, y0, edge_mode),     // left
+                    image.get_pixel_edge(x0 + 1, y0, edge_mode),     // right
+                    image.get_pixel_edge(x0 - 1, y0 + 1, edge_mode), // bottom-left
+                    image.get_pixel_edge(x0, y0 + 1, edge_mode),     // bottom
+                    image.get_pixel_edge(x0 + 1, y0 + 1, edge_mode), // bottom-right
                 ];
 
                 // Output 2x2 block
@@ -217,9 +229,14 @@ impl ScaleByRules {
 
 impl Upscaler for ScaleByRules {
     fn upscale(&self, image: &Image, scale_factor: f32) -> Image {
+        self.upscale_with_sampling(image, scale_factor, &SamplingConfig::default())
+    }
+
+    /// The 2x lattice fixes the alignment; the edge mode sets what border pixels see
+    fn upscale_with_sampling(&self, image: &Image, scale_factor: f32, sampling: &SamplingConfig) -> Image {
         // Only supports 2x for now
         if scale_factor == 2.0 {
-            Self::upscale_2x(image)
+            Self::upscale_2x(image, sampling.edge_mode)
         } else {
             // For other scales, do multiple 2x passes or fall back
             let target_width = (image.width as f32 * scale_factor).round() as usize;
@@ -229,7 +246,7 @@ impl Upscaler for ScaleByRules {
 
             // Do 2x passes until we reach or exceed target
             while current.width < target_width || current.height < target_height {
-                current = Self::upscale_2x(&current);
+                current = Self::upscale_2x(&current, sampling.edge_mode);
             }
 
             // If we overshot, downscale (simple for now)
@@ -254,11 +271,721 @@ impl Upscaler for ScaleByRules {
         "Scale-by-Rules (xBR-like)"
     }
 
+    fn supports_sampling(&self) -> bool {
+        true
+    }
+
+    fn tier(&self) -> UpscaleTier {
+        UpscaleTier::Medium
+    }
+}
+
+/// Directional Cubic Convolution Interpolation (DCCI)
+///
+/// Doubles the image in two passes. Diagonal pixels are interpolated by cubic
+/// convolution along the diagonal with the weaker gradient, then the remaining
+/// pixels along the weaker of the horizontal and vertical directions. Where
+/// neither direction dominates, both estimates are blended by edge strength.
+/// Time complexity: O(n) per 2x pass
+/// Space complexity: O(n) for the diagonal pass
+pub struct DirectionalCubicConvolution;
+
+impl DirectionalCubicConvolution {
+    /// Gradient ratio above which one direction is treated as an edge
+    const EDGE_THRESHOLD: f32 = 1.15;
+
+    /// Exponent applied to gradients when blending both directions
+    const WEIGHT_EXPONENT: i32 = 5;
+
+    /// Sum of absolute channel differences
+    fn diff(a: Pixel, b: Pixel) -> f32 {
+        (a.r as f32 - b.r as f32).abs()
+            + (a.g as f32 - b.g as f32).abs()
+            + (a.b as f32 - b.b as f32).abs()
+    }
+
+    /// Cubic convolution of four equally spaced samples at their midpoint
+    fn cubic_midpoint(p: [Pixel; 4]) -> [f32; 3] {
+        let channel = |f: fn(&Pixel) -> u8| {
+            (-(f(&p[0]) as f32) + 9.0 * f(&p[1]) as f32 + 9.0 * f(&p[2]) as f32 - f(&p[3]) as f32)
+                / 16.0
+        };
+        [channel(|p| p.r), channel(|p| p.g), channel(|p| p.b)]
+    }
+
+    /// Choose or blend two directional estimates by their gradient sums
+    ///
+    /// `g1` is the gradient along the direction `p1` was interpolated in, so
+    /// the estimate along the smoother direction wins.
+    fn blend(g1: f32, g2: f32, p1: [f32; 3], p2: [f32; 3]) -> Pixel {
+        let value = if (1.0 + g1) / (1.0 + g2) > Self::EDGE_THRESHOLD {
+            p2
+        } else if (1.0 + g2) / (1.0 + g1) > Self::EDGE_THRESHOLD {
+            p1
+        } else {
+            let w1 = 1.0 / (1.0 + g1.powi(Self::WEIGHT_EXPONENT));
+            let w2 = 1.0 / (1.0 + g2.powi(Self::WEIGHT_EXPONENT));
+            let mut mixed = [0.0; 3];
+            for c in 0..3 {
+                mixed[c] = (w1 * p1[c] + w2 * p2[c]) / (w1 + w2);
+            }
+            mixed
+        };
+
+        Pixel::new(
+            value[0].round().clamp(0.0, 255.0) as u8,
+            value[1].round().clamp(0.0, 255.0) as u8,
+            value[2].round().clamp(0.0, 255.0) as u8,
+        )
+    }
+
+    /// Interpolate the pixel at the centre of the 2x2 block whose top-left is (x, y)
+    fn diagonal(image: &Image, x: i32, y: i32, edge_mode: EdgeMode) -> Pixel {
+        let p = |dx: i32, dy: i32| image.get_pixel_edge(x + dx, y + dy, edge_mode);
+
+        // Gradients along the up-right and down-right diagonals of the 4x4 block
+        let mut up_right_grad = 0.0;
+        let mut down_right_grad = 0.0;
+        for j in 0..3 {
+            for i in -1..=1 {
+                up_right_grad += Self::diff(p(i, j), p(i + 1, j - 1));
+                down_right_grad += Self::diff(p(i, j - 1), p(i + 1, j));
+            }
+        }
+
+        let up_right = Self::cubic_midpoint([p(-1, 2), p(0, 1), p(1, 0), p(2, -1)]);
+        let down_right = Self::cubic_midpoint([p(-1, -1), p(0, 0), p(1, 1), p(2, 2)]);
+
+        Self::blend(up_right_grad, down_right_grad, up_right, down_right)
+    }
+
+    /// Upscale 2x: originals on even coordinates, diagonals first, then the rest
+    fn upscale_2x(image: &Image, edge_mode: EdgeMode) -> Image {
+        let (w, h) = (image.width as i32, image.height as i32);
+
+        let mut diagonals = Image::new(image.width, image.height);
+        for y in 0..h {
+            for x in 0..w {
+                diagonals.set_pixel(x as usize, y as usize, Self::diagonal(image, x, y, edge_mode));
+            }
+        }
+        let d = |x: i32, y: i32| diagonals.get_pixel_edge(x, y, edge_mode);
+        let i = |x: i32, y: i32| image.get_pixel_edge(x, y, edge_mode);
+
+        let mut result = Image::new(image.width * 2, image.height * 2);
+
+        for y in 0..h {
+            for x in 0..w {
+                let (ox, oy) = (x as usize * 2, y as usize * 2);
+                result.set_pixel(ox, oy, i(x, y));
+                result.set_pixel(ox + 1, oy + 1, d(x, y));
+
+                // Between (x, y) and (x + 1, y)
+                let horizontal = Self::cubic_midpoint([i(x - 1, y), i(x, y), i(x + 1, y), i(x + 2, y)]);
+                let vertical = Self::cubic_midpoint([d(x, y - 2), d(x, y - 1), d(x, y), d(x, y + 1)]);
+                let grad_h = Self::diff(i(x - 1, y), i(x, y))
+                    + Self::diff(i(x, y), i(x + 1, y))
+                    + Self::diff(i(x + 1, y), i(x + 2, y))
+                    + Self::diff(d(x - 1, y - 1), d(x, y - 1))
+                    + Self::diff(d(x, y - 1), d(x + 1, y - 1))
+                    + Self::diff(d(x - 1, y), d(x, y))
+                    + Self::diff(d(x, y), d(x + 1, y));
+                let grad_v = Self::diff(d(x, y - 2), d(x, y - 1))
+                    + Self::diff(d(x, y - 1), d(x, y))
+                    + Self::diff(d(x, y), d(x, y + 1))
+                    + Self::diff(i(x, y - 1), i(x, y))
+                    + Self::diff(i(x, y), i(x, y + 1))
+                    + Self::diff(i(x + 1, y - 1), i(x + 1, y))
+                    + Self::diff(i(x + 1, y), i(x + 1, y + 1));
+                result.set_pixel(ox + 1, oy, Self::blend(grad_h, grad_v, horizontal, vertical));
+
+                // Between (x, y) and (x, y + 1)
+                let vertical = Self::cubic_midpoint([i(x, y - 1), i(x, y), i(x, y + 1), i(x, y + 2)]);
+                let horizontal = Self::cubic_midpoint([d(x - 2, y), d(x - 1, y), d(x, y), d(x + 1, y)]);
+                let grad_v = Self::diff(i(x, y - 1), i(x, y))
+                    + Self::diff(i(x, y), i(x, y + 1))
+                    + Self::diff(i(x, y + 1), i(x, y + 2))
+                    + Self::diff(d(x - 1, y - 1), d(x - 1, y))
+                    + Self::diff(d(x - 1, y), d(x - 1, y + 1))
+                    + Self::diff(d(x, y - 1), d(x, y))
+                    + Self::diff(d(x, y), d(x, y + 1));
+                let grad_h = Self::diff(d(x - 2, y), d(x - 1, y))
+                    + Self::diff(d(x - 1, y), d(x, y))
+                    + Self::diff(d(x, y), d(x + 1, y))
+                    + Self::diff(i(x - 1, y), i(x, y))
+                    + Self::diff(i(x, y), i(x + 1, y))
+                    + Self::diff(i(x - 1, y + 1), i(x, y + 1))
+                    + Self::diff(i(x, y + 1), i(x + 1, y + 1));
+                result.set_pixel(ox, oy + 1, Self::blend(grad_v, grad_h, vertical, horizontal));
+            }
+        }
+
+        result
+    }
+}
+
+impl Upscaler for DirectionalCubicConvolution {
+    fn upscale(&self, image: &Image, scale_factor: f32) -> Image {
+        self.upscale_with_sampling(image, scale_factor, &SamplingConfig::default())
+    }
+
+    /// The 2x lattice fixes the alignment; the edge mode sets what border pixels see
+    fn upscale_with_sampling(&self, image: &Image, scale_factor: f32, sampling: &SamplingConfig) -> Image {
+        upscale_by_doubling(image, scale_factor, |img| Self::upscale_2x(img, sampling.edge_mode))
+    }
+
+    fn name(&self) -> &str {
+        "Directional Cubic Convolution (DCCI)"
+    }
+
+    fn supports_sampling(&self) -> bool {
+        true
+    }
+
+    fn tier(&self) -> UpscaleTier {
+        UpscaleTier::Medium
+    }
+}
+
+/// Iterative Curvature-Based Interpolation (ICBI)
+///
+/// Doubles the image in two passes like DCCI. Each new pixel starts as the
+/// average along the direction with the lower second derivative, then is
+/// refined greedily to minimise an energy made of curvature continuity,
+/// curvature enhancement and isophote smoothing terms.
+/// Time complexity: O(n * iterations) per 2x pass
+/// Space complexity: O(n) for the working planes
+pub struct IterativeCurvature {
+    iterations: usize,
+}
+
+/// Single-channel working plane for ICBI
+///
+/// Wrapped and constant borders follow the edge mode. Clamp and mirror both
+/// mirror about the border pixel, the only reflection that keeps the parity of
+/// original and new pixels intact on the doubled lattice.
+struct CurvaturePlane {
+    width: i32,
+    height: i32,
+    values: Vec<f32>,
+    edge: PlaneEdge,
+}
+
+#[derive(Clone, Copy)]
+enum PlaneEdge {
+    Mirror,
+    Wrap,
+    Constant(f32),
+}
+
+impl CurvaturePlane {
+    fn at(&self, x: i32, y: i32) -> f32 {
+        let inside = (0..self.width).contains(&x) && (0..self.height).contains(&y);
+        let (x, y) = match self.edge {
+            _ if inside => (x, y),
+            PlaneEdge::Constant(value) => return value,
+            PlaneEdge::Wrap => (x.rem_euclid(self.width), y.rem_euclid(self.height)),
+            PlaneEdge::Mirror => {
+                let mirror = |v: i32, len: i32| {
+                    let v = if v < 0 { -v } else { v };
+                    let v = if v >= len { 2 * (len - 1) - v } else { v };
+                    v.clamp(0, len - 1)
+                };
+                (mirror(x, self.width), mirror(y, self.height))
+            }
+        };
+        self.values[(y * self.width + x) as usize]
+    }
+
+    fn set(&mut self, x: i32, y: i32, value: f32) {
+        self.values[(y * self.width + x) as usize] = value;
+    }
+
+    /// Second derivative along `d` at `p`
+    fn second(&self, p: (i32, i32), d: (i32, i32)) -> f32 {
+        self.at(p.0 + d.0, p.1 + d.1) + self.at(p.0 - d.0, p.1 - d.1) - 2.0 * self.at(p.0, p.1)
+    }
+}
+
+impl IterativeCurvature {
+    /// Weight of the curvature continuity term
+    const ALPHA: f32 = 1.0;
+    /// Weight of the curvature enhancement term
+    const BETA: f32 = 0.5;
+    /// Weight of the isophote smoothing term
+    const GAMMA: f32 = 5.0;
+    /// Greedy step applied to a pixel per iteration
+    const STEP: f32 = 2.0;
+
+    /// Create with default parameters (8 refinement iterations per pass)
+    pub fn new() -> Self {
+        Self { iterations: 8 }
+    }
+
+    /// Create with a custom number of refinement iterations per pass
+    pub fn with_iterations(iterations: usize) -> Self {
+        Self { iterations }
+    }
+
+    /// Local energy at `p` for the lattice spanned by `d1` and `d2`
+    fn energy(plane: &CurvaturePlane, p: (i32, i32), d1: (i32, i32), d2: (i32, i32)) -> f32 {
+        let mut continuity = 0.0;
+        let mut enhancement = 0.0;
+        for d in [d1, d2] {
+            let here = plane.second(p, d);
+            continuity += (plane.second((p.0 + d.0, p.1 + d.1), d) - here).abs()
+                + (plane.second((p.0 - d.0, p.1 - d.1), d) - here).abs();
+            enhancement -= here.abs();
+        }
+
+        let i1 = (plane.at(p.0 + d1.0, p.1 + d1.1) - plane.at(p.0 - d1.0, p.1 - d1.1)) / 2.0;
+        let i2 = (plane.at(p.0 + d2.0, p.1 + d2.1) - plane.at(p.0 - d2.0, p.1 - d2.1)) / 2.0;
+        let i11 = plane.second(p, d1);
+        let i22 = plane.second(p, d2);
+        let i12 = (plane.at(p.0 + d1.0 + d2.0, p.1 + d1.1 + d2.1)
+            - plane.at(p.0 + d1.0 - d2.0, p.1 + d1.1 - d2.1)
+            - plane.at(p.0 - d1.0 + d2.0, p.1 - d1.1 + d2.1)
+            + plane.at(p.0 - d1.0 - d2.0, p.1 - d1.1 - d2.1))
+            / 4.0;
+        let isophote = (i1 * i1 * i22 - 2.0 * i1 * i2 * i12 + i2 * i2 * i11).abs()
+            / ((i1 * i1 + i2 * i2).sqrt() + 1.0);
+
+        Self::ALPHA * continuity + Self::BETA * enhancement + Self::GAMMA * isophote
+    }
+
+    /// Fill the pixels selected by `is_new`, then refine them iteratively
+    fn fill_and_refine(
+        &self,
+        plane: &mut CurvaturePlane,
+        is_new: impl Fn(i32, i32) -> bool,
+        d1: (i32, i32),
+        d2: (i32, i32),
+    ) {
+        let positions: Vec<(i32, i32)> = (0..plane.height)
+            .flat_map(|y| (0..plane.width).map(move |x| (x, y)))
+            .filter(|&(x, y)| is_new(x, y))
+            .collect();
+
+        // Initial guess along the direction with the lower second derivative
+        for &(x, y) in &positions {
+            let along = |d: (i32, i32)| {
+                let outer = [plane.at(x - 3 * d.0, y - 3 * d.1), plane.at(x + 3 * d.0, y + 3 * d.1)];
+                let inner = [plane.at(x - d.0, y - d.1), plane.at(x + d.0, y + d.1)];
+                ((outer[0] - inner[0] - inner[1] + outer[1]).abs(), (inner[0] + inner[1]) / 2.0)
+            };
+            let (curv1, avg1) = along(d1);
+            let (curv2, avg2) = along(d2);
+            plane.set(x, y, if curv1 <= curv2 { avg1 } else { avg2 });
+        }
+
+        // Greedy energy minimisation
+        for _ in 0..self.iterations {
+            let mut changed = false;
+            for &(x, y) in &positions {
+                let current = plane.at(x, y);
+                let mut best = (Self::energy(plane, (x, y), d1, d2), current);
+
+                for candidate in [current + Self::STEP, current - Self::STEP] {
+                    let candidate = candidate.clamp(0.0, 255.0);
+                    plane.set(x, y, candidate);
+                    let energy = Self::energy(plane, (x, y), d1, d2);
+                    if energy < best.0 {
+                        best = (energy, candidate);
+                    }
+                }
+
+                plane.set(x, y, best.1);
+                changed |= best.1 != current;
+            }
+            if !changed {
+                break;
+            }
+        }
+    }
+
+    /// Upscale 2x: diagonal pixels first, then horizontal/vertical ones
+    fn upscale_2x(&self, image: &Image, edge_mode: EdgeMode) -> Image {
+        let (width, height) = (image.width * 2, image.height * 2);
+        let channels: [fn(&Pixel) -> u8; 3] = [|p| p.r, |p| p.g, |p| p.b];
+
+        let planes: Vec<CurvaturePlane> = channels
+            .iter()
+            .map(|channel| {
+                let mut plane = CurvaturePlane {
+                    width: width as i32,
+                    height: height as i32,
+                    values: vec![0.0; width * height],
+                    edge: match edge_mode {
+                        EdgeMode::Clamp | EdgeMode::Mirror => PlaneEdge::Mirror,
+                        EdgeMode::Wrap => PlaneEdge::Wrap,
+                        EdgeMode::Constant(p) => PlaneEdge::Constant(channel(&p) as f32),
+                    },
+                };
+                for y in 0..image.height {
+                    for x in 0..image.width {
+                        let p = image.get_pixel(x, y).unwrap();
+                        plane.set(x as i32 * 2, y as i32 * 2, channel(&p) as f32);
+                    }
+                }
+
+                self.fill_and_refine(&mut plane, |x, y| x % 2 == 1 && y % 2 == 1, (1, 1), (1, -1));
+                self.fill_and_refine(&mut plane, |x, y| (x + y) % 2 == 1, (1, 0), (0, 1));
+                plane
+            })
+            .collect();
+
+        let mut result = Image::new(width, height);
+        for (i, pixel) in result.pixels.iter_mut().enumerate() {
+            let value = |c: usize| planes[c].values[i].round().clamp(0.0, 255.0) as u8;
+            *pixel = Pixel::new(value(0), value(1), value(2));
+        }
+
+        result
+    }
+}
+
+impl Upscaler for IterativeCurvature {
+    fn upscale(&self, image: &Image, scale_factor: f32) -> Image {
+        self.upscale_with_sampling(image, scale_factor, &SamplingConfig::default())
+    }
+
+    /// The 2x lattice fixes the alignment; the edge mode sets what border pixels see
+    fn upscale_with_sampling(&self, image: &Image, scale_factor: f32, sampling: &SamplingConfig) -> Image {
+        upscale_by_doubling(image, scale_factor, |img| self.upscale_2x(img, sampling.edge_mode))
+    }
+
+    fn name(&self) -> &str {
+        "Iterative Curvature-Based Interpolation (ICBI)"
+    }
+
+    fn supports_sampling(&self) -> bool {
+        true
+    }
+
+    fn tier(&self) -> UpscaleTier {
+        UpscaleTier::Medium
+    }
+}
+
+impl Default for IterativeCurvature {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Maps a range block's local coordinates onto its domain block
+///
+/// The eight isometries of the square: identity, mirrors, transposes and the
+/// rotations they compose to.
+fn isometry(index: u8, u: f32, v: f32) -> (f32, f32) {
+    match index {
+        0 => (u, v),
+        1 => (1.0 - u, v),
+        2 => (u, 1.0 - v),
+        3 => (1.0 - u, 1.0 - v),
+        4 => (v, u),
+        5 => (1.0 - v, u),
+        6 => (v, 1.0 - u),
+        _ => (1.0 - v, 1.0 - u),
+    }
+}
+
+/// Contrast bound that keeps every block map contractive
+const MAX_CONTRAST: f32 = 0.9;
+
+/// One range block's self-similarity transform
+#[derive(Debug, Clone, Copy)]
+struct BlockTransform {
+    domain_x: usize,
+    domain_y: usize,
+    isometry: u8,
+    contrast: [f32; 3],
+    brightness: [f32; 3],
+}
+
+/// Resolution-independent fractal code of an image
+///
+/// Range blocks tile the source; each is approximated by a twice-as-large
+/// domain block elsewhere in the image, shrunk, turned by one of eight
+/// isometries and adjusted by a per-channel contrast and brightness. Decoding
+/// iterates those maps from any starting image at any resolution.
+pub struct FractalCode {
+    pub width: usize,
+    pub height: usize,
+    block_size: usize,
+    range_xs: Vec<usize>,
+    range_ys: Vec<usize>,
+    transforms: Vec<BlockTransform>,
+}
+
+impl FractalCode {
+    /// Decode at `width` x `height`, starting from `initial` (resized to fit)
+    pub fn decode(&self, initial: &Image, width: usize, height: usize, iterations: usize) -> Image {
+        let start = if initial.width == width && initial.height == height {
+            initial.clone()
+        } else {
+            Bicubic::resize(initial, width, height)
+        };
+        let mut current = FloatImage::from_image(&start);
+        let (kx, ky) = (width as f32 / self.width as f32, height as f32 / self.height as f32);
+        let b = self.block_size as f32;
+
+        // Range block and local coordinate of every output row and column
+        let locate = |origins: &[usize], count: usize, k: f32| -> Vec<(usize, f32)> {
+            (0..count)
+                .map(|i| {
+                    let u = (i as f32 + 0.5) / k;
+                    let index = ((u / b) as usize).min(origins.len() - 1);
+                    (index, ((u - origins[index] as f32) / b).clamp(0.0, 0.9999))
+                })
+                .collect()
+        };
+        let columns = locate(&self.range_xs, width, kx);
+        let rows = locate(&self.range_ys, height, ky);
+
+        let bilinear = |image: &FloatImage, x: f32, y: f32| -> [f32; 3] {
+            let (x0, y0) = (x.floor(), y.floor());
+            let (fx, fy) = (x - x0, y - y0);
+            let (x0, y0) = (x0 as i32, y0 as i32);
+            let [a, b, c, d] = [
+                image.get_clamped(x0, y0),
+                image.get_clamped(x0 + 1, y0),
+                image.get_clamped(x0, y0 + 1),
+                image.get_clamped(x0 + 1, y0 + 1),
+            ];
+            [0, 1, 2].map(|ch| {
+                let top = a[ch] + (b[ch] - a[ch]) * fx;
+                let bottom = c[ch] + (d[ch] - c[ch]) * fx;
+                top + (bottom - top) * fy
+            })
+        };
+
+        for _ in 0..iterations {
+            let mut next = FloatImage::new(width, height);
+            for (y, &(ry, v)) in rows.iter().enumerate() {
+                for (x, &(rx, u)) in columns.iter().enumerate() {
+                    let t = &self.transforms[ry * self.range_xs.len() + rx];
+                    let (du, dv) = isometry(t.isometry, u, v);
+
+                    // Domain pixels are twice the size of range pixels, so
+                    // average a two-pixel footprint in output space
+                    let cx = (t.domain_x as f32 + du * 2.0 * b) * kx - 0.5;
+                    let cy = (t.domain_y as f32 + dv * 2.0 * b) * ky - 0.5;
+                    let mut sample = [0.0; 3];
+                    for (ox, oy) in [(-0.5, -0.5), (0.5, -0.5), (-0.5, 0.5), (0.5, 0.5)] {
+                        let s = bilinear(&current, cx + ox, cy + oy);
+                        for c in 0..3 {
+                            sample[c] += s[c] * 0.25;
+                        }
+                    }
+
+                    next.set(x, y, [0, 1, 2].map(|c| t.contrast[c] * sample[c] + t.brightness[c]));
+                }
+            }
+            next.clamp_to_range();
+            current = next;
+        }
+
+        current.to_image()
+    }
+}
+
+/// Fractal (iterated function system) upscaling
+///
+/// Encodes the image as range/domain block transforms, searching each range
+/// block's neighbourhood of domain blocks on luma, then decodes the code at
+/// the target resolution starting from a bicubic enlargement. Texture is
+/// synthesised from the image's own self-similarity rather than interpolated.
+/// Time complexity: O(n * search_depth² * 8) to encode, O(n * iterations) to decode
+/// Space complexity: O(n) for the domain pool
+pub struct FractalCoding {
+    block_size: usize,
+    search_depth: usize,
+    iterations: usize,
+}
+
+impl FractalCoding {
+    /// 4x4 range blocks, domains within 8 block steps, 8 decoding iterations
+    pub fn new() -> Self {
+        Self {
+            block_size: 4,
+            search_depth: 8,
+            iterations: 8,
+        }
+    }
+
+    /// Range block edge in source pixels; domain blocks are twice this
+    pub fn with_block_size(mut self, block_size: usize) -> Self {
+        self.block_size = block_size.clamp(2, 32);
+        self
+    }
+
+    /// Radius, in block steps, of the domain neighbourhood searched for each
+    /// range block; 0 searches the whole image
+    pub fn with_search_depth(mut self, search_depth: usize) -> Self {
+        self.search_depth = search_depth;
+        self
+    }
+
+    pub fn with_iterations(mut self, iterations: usize) -> Self {
+        self.iterations = iterations.max(1);
+        self
+    }
+
+    /// Encode `image`, or `None` if it is too small to hold a domain block
+    pub fn encode(&self, image: &Image) -> Option<FractalCode> {
+        let b = self.block_size;
+        let (w, h) = (image.width, image.height);
+        if w < 2 * b || h < 2 * b {
+            return None;
+        }
+
+        let source = FloatImage::from_image(image);
+        let luma = Plane::luma(&source);
+
+        // Shrink a domain block 2:1 by box averaging
+        let shrink = |plane: &dyn Fn(usize, usize) -> f32, dx: usize, dy: usize| -> Vec<f32> {
+            let mut out = Vec::with_capacity(b * b);
+            for y in 0..b {
+                for x in 0..b {
+                    let (sx, sy) = (dx + 2 * x, dy + 2 * y);
+                    out.push((plane(sx, sy) + plane(sx + 1, sy) + plane(sx, sy + 1) + plane(sx + 1, sy + 1)) * 0.25);
+                }
+            }
+            out
+        };
+        let transform = |block: &[f32], iso: u8| -> Vec<f32> {
+            let mut out = Vec::with_capacity(b * b);
+            for y in 0..b {
+                for x in 0..b {
+                    let (u, v) = isometry(iso, (x as f32 + 0.5) / b as f32, (y as f32 + 0.5) / b as f32);
+                    let (sx, sy) = ((u * b as f32) as usize, (v * b as f32) as usize);
+                    out.push(block[sy.min(b - 1) * b + sx.min(b - 1)]);
+                }
+            }
+            out
+        };
+
+        // Domain pool on a grid with the range block's step, all isometries
+        let domain_xs: Vec<usize> = (0..=w - 2 * b).step_by(b).collect();
+        let domain_ys: Vec<usize> = (0..=h - 2 * b).step_by(b).collect();
+        let luma_at = |x: usize, y: usize| luma.get(x, y);
+        let mut pool = Vec::with_capacity(domain_xs.len() * domain_ys.len());
+        for &dy in &domain_ys {
+            for &dx in &domain_xs {
+                let block = shrink(&luma_at, dx, dy);
+                pool.push((0..8u8).map(|iso| transform(&block, iso)).collect::<Vec<_>>());
+            }
+        }
+
+        // Least-squares contrast and brightness of `domain` onto `range`
+        let n = (b * b) as f32;
+        let fit = |domain: &[f32], range: &[f32]| -> (f32, f32, f32) {
+            let (sd, sr) = (domain.iter().sum::<f32>(), range.iter().sum::<f32>());
+            let sdd: f32 = domain.iter().map(|d| d * d).sum();
+            let srd: f32 = domain.iter().zip(range).map(|(d, r)| d * r).sum();
+            let denom = n * sdd - sd * sd;
+            let s = if denom.abs() > 1e-6 { ((n * srd - sd * sr) / denom).clamp(-MAX_CONTRAST, MAX_CONTRAST) } else { 0.0 };
+            let o = (sr - s * sd) / n;
+            let err = domain.iter().zip(range).map(|(d, r)| (s * d + o - r).powi(2)).sum();
+            (s, o, err)
+        };
+
+        let range_xs = patch_origins(w, b, b);
+        let range_ys = patch_origins(h, b, b);
+        let mut transforms = Vec::with_capacity(range_xs.len() * range_ys.len());
+        for &ry in &range_ys {
+            for &rx in &range_xs {
+                let range = luma.patch(rx as i32, ry as i32, b);
+
+                let near = |origins: &[usize], r: usize| -> std::ops::Range<usize> {
+                    if self.search_depth == 0 {
+                        return 0..origins.len();
+                    }
+                    let centre = (r / b).min(origins.len() - 1);
+                    centre.saturating_sub(self.search_depth)..(centre + self.search_depth + 1).min(origins.len())
+                };
+
+                let mut best = (f32::INFINITY, 0, 0, 0u8);
+                'search: for gy in near(&domain_ys, ry) {
+                    for gx in near(&domain_xs, rx) {
+                        for (iso, candidate) in pool[gy * domain_xs.len() + gx].iter().enumerate() {
+                            let (_, _, err) = fit(candidate, &range);
+                            if err < best.0 {
+                                best = (err, gx, gy, iso as u8);
+                                if err < 1e-3 {
+                                    break 'search;
+                                }
+                            }
+                        }
+                    }
+                }
+
+                // Per-channel maps for the chosen domain and isometry
+                let (_, gx, gy, iso) = best;
+                let (domain_x, domain_y) = (domain_xs[gx], domain_ys[gy]);
+                let mut contrast = [0.0; 3];
+                let mut brightness = [0.0; 3];
+                for c in 0..3 {
+                    let channel = |x: usize, y: usize| source.get(x, y)[c];
+                    let domain = transform(&shrink(&channel, domain_x, domain_y), iso);
+                    let range: Vec<f32> = (0..b * b).map(|i| source.get(rx + i % b, ry + i / b)[c]).collect();
+                    let (s, o, _) = fit(&domain, &range);
+                    contrast[c] = s;
+                    brightness[c] = o;
+                }
+
+                transforms.push(BlockTransform { domain_x, domain_y, isometry: iso, contrast, brightness });
+            }
+        }
+
+        Some(FractalCode { width: w, height: h, block_size: b, range_xs, range_ys, transforms })
+    }
+}
+
+impl Upscaler for FractalCoding {
+    fn upscale(&self, image: &Image, scale_factor: f32) -> Image {
+        let target_width = (image.width as f32 * scale_factor).round() as usize;
+        let target_height = (image.height as f32 * scale_factor).round() as usize;
+
+        match self.encode(image) {
+            Some(code) => code.decode(image, target_width, target_height, self.iterations),
+            None => Bicubic::resize(image, target_width, target_height),
+        }
+    }
+
+    fn name(&self) -> &str {
+        "Fractal Coding (IFS)"
+    }
+
     fn tier(&self) -> UpscaleTier {
         UpscaleTier::Medium
     }
 }
 
+impl Default for FractalCoding {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Apply a 2x algorithm until the target size is reached, then resample to it exactly
+fn upscale_by_doubling(image: &Image, scale_factor: f32, double: impl Fn(&Image) -> Image) -> Image {
+    let target_width = (image.width as f32 * scale_factor).round() as usize;
+    let target_height = (image.height as f32 * scale_factor).round() as usize;
+
+    let mut current = image.clone();
+    while current.width < target_width || current.height < target_height {
+        current = double(&current);
+    }
+
+    if current.width != target_width || current.height != target_height {
+        Bicubic::resize(&current, target_width, target_height)
+    } else {
+        current
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -296,4 +1023,67 @@ mod tests {
         assert_eq!(result.width, 16);
         assert_eq!(result.height, 16);
     }
+
+    #[test]
+    fn test_dcci_keeps_source_pixels() {
+        let img = create_edge_image();
+        let upscaler = DirectionalCubicConvolution;
+        let result = upscaler.upscale(&img, 2.0);
+
+        assert_eq!(result.width, 16);
+        assert_eq!(result.height, 16);
+        for y in 0..8 {
+            for x in 0..8 {
+                assert_eq!(result.get_pixel(x * 2, y * 2), img.get_pixel(x, y));
+            }
+        }
+    }
+
+    #[test]
+    fn test_wrapped_edges_tile() {
+        // With wrapped borders, shifting the source one pixel shifts the 2x output by two
+        let mut img = Image::new(8, 8);
+        for (i, p) in img.pixels.iter_mut().enumerate() {
+            *p = Pixel::new((i * 37 % 256) as u8, (i * 11 % 256) as u8, 90);
+        }
+        let shifted = Image::from_pixels(8, 8, (0..64).map(|i| img.pixels[(i / 8) * 8 + (i % 8 + 7) % 8]).collect()).unwrap();
+        let wrap = SamplingConfig::new().with_edge_mode(EdgeMode::Wrap);
+
+        let upscalers: [&dyn Upscaler; 2] = [&DirectionalCubicConvolution, &ScaleByRules];
+        for upscaler in upscalers {
+            let a = upscaler.upscale_with_sampling(&img, 2.0, &wrap);
+            let b = upscaler.upscale_with_sampling(&shifted, 2.0, &wrap);
+            for y in 0..16 {
+                for x in 0..16 {
+                    assert_eq!(b.get_pixel((x + 2) % 16, y), a.get_pixel(x, y), "{}", upscaler.name());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_fractal_coding() {
+        // A flat image is its own attractor
+        let flat = Image::from_pixels(8, 8, vec![Pixel::new(90, 120, 150); 64]).unwrap();
+        let result = FractalCoding::new().upscale(&flat, 2.5);
+        assert_eq!((result.width, result.height), (20, 20));
+        assert!(result.pixels.iter().all(|p| p.r.abs_diff(90) <= 1 && p.b.abs_diff(150) <= 1));
+
+        // Decoding at the source size reproduces a self-similar edge closely
+        let img = create_edge_image();
+        let code = FractalCoding::new().with_block_size(2).with_search_depth(0).encode(&img).unwrap();
+        let decoded = code.decode(&Image::new(8, 8), 8, 8, 12);
+        let error: f32 = decoded.pixels.iter().zip(&img.pixels).map(|(a, b)| (a.r as f32 - b.r as f32).abs()).sum::<f32>() / 64.0;
+        assert!(error < 8.0, "mean error {}", error);
+    }
+
+    #[test]
+    fn test_icbi() {
+        let img = create_edge_image();
+        let upscaler = IterativeCurvature::with_iterations(4);
+        let result = upscaler.upscale(&img, 3.0);
+
+        assert_eq!(result.width, 24);
+        assert_eq!(result.height, 24);
+    }
 }
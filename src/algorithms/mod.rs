@@ -3,14 +3,37 @@ pub mod instant;
 pub mod fast;
 pub mod medium;
 pub mod slow;
+pub mod example;
+pub mod degradation;
+pub mod patch;
+pub mod linalg;
+pub mod raisr;
+pub mod sparse;
+pub mod guided;
+pub mod label;
+pub mod sdf;
+pub mod vector;
+pub mod depixelize;
+pub mod trace;
+pub mod hybrid;
+pub mod ensemble;
+pub mod chain;
+pub mod supersample;
+pub mod display;
+pub mod palette;
+pub mod neural;
 pub mod image;
+pub mod sampling;
+pub mod transform;
 pub mod upscaler;
 mod upscale_tier;
+#[cfg(test)]
+mod test_support;
 
 pub mod prelude {
     // Instant tier
     pub use crate::instant::{NearestNeighbor, Bilinear};
 
     // Fast tier
-    pub use crate::fast::{Bicubic, Lanczos};
+    pub use crate::fast::{Bicubic, Lanczos, EwaLanczos};
 }
@@ -0,0 +1,809 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::algorithms::degradation::Psf;
+use crate::algorithms::fast::Lanczos;
+use crate::algorithms::image::{FloatImage, Image};
+use crate::algorithms::patch::Plane;
+use crate::algorithms::upscaler::{Upscaler, UpscaleTier};
+
+/// Fixed super-resolution network architectures
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Architecture {
+    /// Convolutions on a bicubic-enlarged input (Dong et al. 2014)
+    Srcnn,
+    /// Convolutions at low resolution, then a pixel shuffle (Shi et al. 2016)
+    Espcn,
+    /// Convolutions at low resolution, then a strided transposed convolution (Dong et al. 2016)
+    Fsrcnn,
+}
+
+impl Architecture {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "srcnn" => Some(Architecture::Srcnn),
+            "espcn" => Some(Architecture::Espcn),
+            "fsrcnn" => Some(Architecture::Fsrcnn),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Activation {
+    Relu,
+    Prelu,
+    Tanh,
+}
+
+/// Channel-major feature maps
+struct Tensor {
+    channels: usize,
+    height: usize,
+    width: usize,
+    data: Vec<f32>,
+}
+
+impl Tensor {
+    fn new(channels: usize, height: usize, width: usize) -> Self {
+        Self {
+            channels,
+            height,
+            width,
+            data: vec![0.0; channels * height * width],
+        }
+    }
+
+    fn plane(&self, c: usize) -> &[f32] {
+        let size = self.height * self.width;
+        &self.data[c * size..(c + 1) * size]
+    }
+}
+
+/// One convolution (or transposed convolution) with its activation
+#[derive(Clone)]
+struct Layer {
+    in_channels: usize,
+    out_channels: usize,
+    kernel: usize,
+    /// `[out, in, k, k]`, or `[in, out, k, k]` when transposed (PyTorch layout)
+    weight: Vec<f32>,
+    bias: Vec<f32>,
+    /// PReLU slopes, one per output channel (or one shared)
+    alpha: Vec<f32>,
+    activation: Option<Activation>,
+    transposed: bool,
+}
+
+impl Layer {
+    /// Zero-padded "same" convolution
+    fn conv(&self, input: &Tensor) -> Tensor {
+        let (h, w, k) = (input.height, input.width, self.kernel);
+        let pad = (k / 2) as i32;
+        let mut out = Tensor::new(self.out_channels, h, w);
+
+        for oc in 0..self.out_channels {
+            let plane = &mut out.data[oc * h * w..(oc + 1) * h * w];
+            plane.iter_mut().for_each(|v| *v = self.bias[oc]);
+
+            for ic in 0..self.in_channels {
+                let src = input.plane(ic);
+                for ky in 0..k {
+                    for kx in 0..k {
+                        let weight = self.weight[((oc * self.in_channels + ic) * k + ky) * k + kx];
+                        if weight == 0.0 {
+                            continue;
+                        }
+                        let (dy, dx) = (ky as i32 - pad, kx as i32 - pad);
+                        for y in 0..h {
+                            let sy = y as i32 + dy;
+                            if sy < 0 || sy >= h as i32 {
+                                continue;
+                            }
+                            let row = &src[sy as usize * w..(sy as usize + 1) * w];
+                            let x_start = (-dx).max(0) as usize;
+                            let x_end = (w as i32 - dx).min(w as i32).max(0) as usize;
+                            for x in x_start..x_end {
+                                plane[y * w + x] += weight * row[(x as i32 + dx) as usize];
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        out
+    }
+
+    /// Transposed convolution with stride `scale`, padding `k / 2` and
+    /// output padding `scale - 1`, so the output is exactly `scale` times larger
+    fn conv_transposed(&self, input: &Tensor, scale: usize) -> Tensor {
+        let (h, w, k) = (input.height, input.width, self.kernel);
+        let (oh, ow) = (h * scale, w * scale);
+        let pad = (k / 2) as i32;
+        let mut out = Tensor::new(self.out_channels, oh, ow);
+
+        for oc in 0..self.out_channels {
+            out.data[oc * oh * ow..(oc + 1) * oh * ow].iter_mut().for_each(|v| *v = self.bias[oc]);
+        }
+
+        for ic in 0..self.in_channels {
+            let src = input.plane(ic);
+            for oc in 0..self.out_channels {
+                let plane = &mut out.data[oc * oh * ow..(oc + 1) * oh * ow];
+                for ky in 0..k {
+                    for kx in 0..k {
+                        let weight = self.weight[((ic * self.out_channels + oc) * k + ky) * k + kx];
+                        if weight == 0.0 {
+                            continue;
+                        }
+                        for y in 0..h {
+                            let oy = (y * scale + ky) as i32 - pad;
+                            if oy < 0 || oy >= oh as i32 {
+                                continue;
+                            }
+                            for x in 0..w {
+                                let ox = (x * scale + kx) as i32 - pad;
+                                if ox >= 0 && ox < ow as i32 {
+                                    plane[oy as usize * ow + ox as usize] += weight * src[y * w + x];
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        out
+    }
+
+    fn activate(&self, tensor: &mut Tensor) {
+        let size = tensor.height * tensor.width;
+        match self.activation {
+            Some(Activation::Relu) => tensor.data.iter_mut().for_each(|v| *v = v.max(0.0)),
+            Some(Activation::Tanh) => tensor.data.iter_mut().for_each(|v| *v = v.tanh()),
+            Some(Activation::Prelu) => {
+                for (c, plane) in tensor.data.chunks_mut(size).enumerate() {
+                    let a = self.alpha.get(c).or(self.alpha.first()).copied().unwrap_or(0.25);
+                    plane.iter_mut().for_each(|v| {
+                        if *v < 0.0 {
+                            *v *= a;
+                        }
+                    });
+                }
+            }
+            None => {}
+        }
+    }
+}
+
+/// Rearrange `c * s²` channels into `c` channels `s` times larger
+fn pixel_shuffle(input: &Tensor, scale: usize) -> Tensor {
+    let channels = input.channels / (scale * scale);
+    let (h, w) = (input.height, input.width);
+    let mut out = Tensor::new(channels, h * scale, w * scale);
+    for c in 0..channels {
+        for sy in 0..scale {
+            for sx in 0..scale {
+                let src = input.plane(c * scale * scale + sy * scale + sx);
+                for y in 0..h {
+                    for x in 0..w {
+                        let (oy, ox) = (y * scale + sy, x * scale + sx);
+                        out.data[(c * h * scale + oy) * w * scale + ox] = src[y * w + x];
+                    }
+                }
+            }
+        }
+    }
+    out
+}
+
+/// A small super-resolution CNN loaded from a safetensors file
+///
+/// File layout follows the safetensors spec: an 8-byte little-endian header
+/// length, a JSON header, then raw tensor data. Tensors are named
+/// `layers.N.weight` (PyTorch layout), `layers.N.bias` and optionally
+/// `layers.N.alpha` (PReLU slopes), for N = 0, 1, ... in order. Header
+/// `__metadata__` keys:
+///
+/// - `architecture`: `srcnn`, `espcn` or `fsrcnn`
+/// - `scale`: `2`, `3` or `4`
+/// - `activation` (optional): `relu`, `prelu` or `tanh` after every layer but
+///   the last (defaults: relu for SRCNN, tanh for ESPCN, prelu for FSRCNN)
+///
+/// A network with 1 input channel runs on luma in 0..1; with 3 it runs on RGB.
+/// Weights may be stored as F32 or F16.
+pub struct SrNetwork {
+    pub architecture: Architecture,
+    pub scale: usize,
+    pub channels: usize,
+    layers: Vec<Layer>,
+}
+
+impl SrNetwork {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let bytes = std::fs::read(path).map_err(|e| format!("Failed to read model: {}", e))?;
+        Self::from_safetensors(&bytes)
+    }
+
+    pub fn from_safetensors(bytes: &[u8]) -> Result<Self, String> {
+        let (metadata, tensors) = parse_safetensors(bytes)?;
+
+        let architecture = metadata.get("architecture")
+            .and_then(|a| Architecture::parse(a))
+            .ok_or("Model metadata needs an architecture of srcnn, espcn or fsrcnn")?;
+        let scale: usize = metadata.get("scale")
+            .and_then(|s| s.parse().ok())
+            .filter(|s| (2..=4).contains(s))
+            .ok_or("Model metadata needs a scale of 2, 3 or 4")?;
+        let activation = match metadata.get("activation").map(|a| a.to_lowercase()) {
+            Some(a) if a == "relu" => Activation::Relu,
+            Some(a) if a == "prelu" => Activation::Prelu,
+            Some(a) if a == "tanh" => Activation::Tanh,
+            Some(a) => return Err(format!("Unknown activation: {}", a)),
+            None => match architecture {
+                Architecture::Srcnn => Activation::Relu,
+                Architecture::Espcn => Activation::Tanh,
+                Architecture::Fsrcnn => Activation::Prelu,
+            },
+        };
+
+        let count = (0..).take_while(|i| tensors.contains_key(&format!("layers.{}.weight", i))).count();
+        if count == 0 {
+            return Err("Model has no layers.0.weight tensor".to_string());
+        }
+
+        let mut layers = Vec::with_capacity(count);
+        for i in 0..count {
+            let (shape, weight) = &tensors[&format!("layers.{}.weight", i)];
+            let last = i + 1 == count;
+            let transposed = last && architecture == Architecture::Fsrcnn;
+
+            let [a, b, kh, kw] = shape[..] else {
+                return Err(format!("layers.{}.weight must be 4-dimensional", i));
+            };
+            if kh != kw || kh % 2 == 0 {
+                return Err(format!("layers.{}.weight needs an odd square kernel", i));
+            }
+            let (in_channels, out_channels) = if transposed { (a, b) } else { (b, a) };
+
+            let bias = match tensors.get(&format!("layers.{}.bias", i)) {
+                Some((_, bias)) if bias.len() == out_channels => bias.clone(),
+                Some(_) => return Err(format!("layers.{}.bias has the wrong length", i)),
+                None => vec![0.0; out_channels],
+            };
+            let alpha = tensors.get(&format!("layers.{}.alpha", i)).map(|(_, a)| a.clone()).unwrap_or_default();
+
+            layers.push(Layer {
+                in_channels,
+                out_channels,
+                kernel: kh,
+                weight: weight.clone(),
+                bias,
+                alpha,
+                activation: (!last).then_some(activation),
+                transposed,
+            });
+        }
+
+        // Check that the layers chain and produce the right output
+        let channels = layers[0].in_channels;
+        if channels != 1 && channels != 3 {
+            return Err("Models must take 1 (luma) or 3 (RGB) input channels".to_string());
+        }
+        for pair in layers.windows(2) {
+            if pair[0].out_channels != pair[1].in_channels {
+                return Err("Layer channel counts do not chain".to_string());
+            }
+        }
+        let expected_out = match architecture {
+            Architecture::Espcn => channels * scale * scale,
+            _ => channels,
+        };
+        if layers[count - 1].out_channels != expected_out {
+            return Err(format!("Last layer must output {} channels", expected_out));
+        }
+
+        Ok(Self { architecture, scale, channels, layers })
+    }
+
+    fn forward(&self, input: Tensor) -> Tensor {
+        let mut x = input;
+        for layer in &self.layers {
+            x = if layer.transposed { layer.conv_transposed(&x, self.scale) } else { layer.conv(&x) };
+            layer.activate(&mut x);
+        }
+        if self.architecture == Architecture::Espcn {
+            x = pixel_shuffle(&x, self.scale);
+        }
+        x
+    }
+
+    /// Run the network at its native scale
+    pub fn upscale(&self, image: &Image) -> Image {
+        self.run(image, self.scale)
+    }
+
+    /// `scale` only differs from the native scale for SRCNN, whose layers
+    /// never change the resolution
+    fn run(&self, image: &Image, scale: usize) -> Image {
+        let (w, h) = (image.width * scale, image.height * scale);
+        let low = FloatImage::from_image(image);
+        let base = Psf::Bicubic.resample(&low, w, h);
+
+        // SRCNN refines the bicubic base; the others start from the input
+        let source = if self.architecture == Architecture::Srcnn { &base } else { &low };
+        let mut input = Tensor::new(self.channels, source.height, source.width);
+        if self.channels == 1 {
+            input.data = Plane::luma(source).data.iter().map(|v| v / 255.0).collect();
+        } else {
+            let size = source.width * source.height;
+            for (i, px) in source.data.iter().enumerate() {
+                for (c, v) in px.iter().enumerate() {
+                    input.data[c * size + i] = v / 255.0;
+                }
+            }
+        }
+
+        let output = self.forward(input);
+        let mut result = base;
+        if self.channels == 1 {
+            // Colour follows the luma correction
+            let base_luma = Plane::luma(&result);
+            for (i, px) in result.data.iter_mut().enumerate() {
+                let delta = output.data[i] * 255.0 - base_luma.data[i];
+                px.iter_mut().for_each(|v| *v += delta);
+            }
+        } else {
+            let size = w * h;
+            for (i, px) in result.data.iter_mut().enumerate() {
+                for (c, v) in px.iter_mut().enumerate() {
+                    *v = output.data[c * size + i] * 255.0;
+                }
+            }
+        }
+        result.clamp_to_range();
+        result.to_image()
+    }
+}
+
+/// CNN super-resolution on the CPU
+///
+/// Runs a loaded `SrNetwork` when the requested factor matches its scale; an
+/// SRCNN model also serves any other 2x-4x factor since it only refines a
+/// bicubic base. Everything else falls back to Lanczos3.
+///
+/// Time complexity: O(n * sum of layer channels² * kernel²)
+/// Space complexity: O(n * widest layer channels)
+pub struct NeuralUpscaler {
+    network: SrNetwork,
+}
+
+impl NeuralUpscaler {
+    pub fn new(network: SrNetwork) -> Self {
+        Self { network }
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        SrNetwork::load(path).map(Self::new)
+    }
+}
+
+impl Upscaler for NeuralUpscaler {
+    fn upscale(&self, image: &Image, scale_factor: f32) -> Image {
+        let native = (scale_factor - self.network.scale as f32).abs() < 1e-3;
+        let integer = (scale_factor - scale_factor.round()).abs() < 1e-3 && (2.0..=4.0).contains(&scale_factor.round());
+
+        if native {
+            self.network.upscale(image)
+        } else if integer && self.network.architecture == Architecture::Srcnn {
+            self.network.run(image, scale_factor.round() as usize)
+        } else {
+            Lanczos::new().upscale(image, scale_factor)
+        }
+    }
+
+    fn name(&self) -> &str {
+        match self.network.architecture {
+            Architecture::Srcnn => "SRCNN",
+            Architecture::Espcn => "ESPCN",
+            Architecture::Fsrcnn => "FSRCNN",
+        }
+    }
+
+    fn tier(&self) -> UpscaleTier {
+        UpscaleTier::Slow
+    }
+}
+
+type TensorMap = HashMap<String, (Vec<usize>, Vec<f32>)>;
+
+/// Parse a safetensors file into its string metadata and F32/F16 tensors
+fn parse_safetensors(bytes: &[u8]) -> Result<(HashMap<String, String>, TensorMap), String> {
+    if bytes.len() < 8 {
+        return Err("Model file is too short".to_string());
+    }
+    let header_len = u64::from_le_bytes(bytes[0..8].try_into().unwrap());
+    let header_end = usize::try_from(header_len).ok()
+        .and_then(|len| len.checked_add(8))
+        .ok_or("Model header length overflows")?;
+    let header = bytes.get(8..header_end).ok_or("Model header is truncated")?;
+    let data = &bytes[header_end..];
+    let header = std::str::from_utf8(header).map_err(|_| "Model header is not UTF-8")?;
+
+    let Json::Object(entries) = JsonParser::new(header).parse()? else {
+        return Err("Model header must be a JSON object".to_string());
+    };
+
+    let mut metadata = HashMap::new();
+    let mut tensors = HashMap::new();
+    for (name, entry) in entries {
+        if name == "__metadata__" {
+            if let Json::Object(pairs) = entry {
+                for (key, value) in pairs {
+                    if let Json::String(value) = value {
+                        metadata.insert(key, value);
+                    }
+                }
+            }
+            continue;
+        }
+
+        let dtype = entry.get("dtype").and_then(Json::as_str).ok_or(format!("{}: missing dtype", name))?;
+        let shape: Vec<usize> = entry.get("shape").and_then(Json::as_usizes).ok_or(format!("{}: missing shape", name))?;
+        let offsets = entry.get("data_offsets").and_then(Json::as_usizes).ok_or(format!("{}: missing offsets", name))?;
+        let [start, end] = offsets[..] else {
+            return Err(format!("{}: data_offsets needs two values", name));
+        };
+        let raw = data.get(start..end).ok_or(format!("{}: data is truncated", name))?;
+
+        let values: Vec<f32> = match dtype {
+            "F32" => raw.chunks_exact(4).map(|c| f32::from_le_bytes([c[0], c[1], c[2], c[3]])).collect(),
+            "F16" => raw.chunks_exact(2).map(|c| f16_to_f32(u16::from_le_bytes([c[0], c[1]]))).collect(),
+            other => return Err(format!("{}: unsupported dtype {}", name, other)),
+        };
+        let size = shape.iter()
+            .try_fold(1usize, |size, &dim| size.checked_mul(dim))
+            .ok_or(format!("{}: shape overflows", name))?;
+        if values.len() != size {
+            return Err(format!("{}: shape does not match data size", name));
+        }
+        tensors.insert(name, (shape, values));
+    }
+
+    Ok((metadata, tensors))
+}
+
+fn f16_to_f32(bits: u16) -> f32 {
+    let sign = ((bits >> 15) as u32) << 31;
+    let exponent = ((bits >> 10) & 0x1f) as u32;
+    let mantissa = (bits & 0x3ff) as u32;
+
+    let value = match exponent {
+        0 => {
+            // Subnormal: mantissa * 2^-24
+            let magnitude = mantissa as f32 * 2f32.powi(-24);
+            return if sign != 0 { -magnitude } else { magnitude };
+        }
+        0x1f => sign | 0x7f80_0000 | (mantissa << 13),
+        _ => sign | ((exponent + 112) << 23) | (mantissa << 13),
+    };
+    f32::from_bits(value)
+}
+
+/// Just enough JSON for safetensors headers
+enum Json {
+    Null,
+    Bool,
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(pairs) => pairs.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    fn as_usizes(&self) -> Option<Vec<usize>> {
+        match self {
+            Json::Array(items) => items.iter()
+                .map(|v| match v {
+                    Json::Number(n) if *n >= 0.0 => Some(*n as usize),
+                    _ => None,
+                })
+                .collect(),
+            _ => None,
+        }
+    }
+}
+
+/// Nesting beyond this is rejected rather than risking the stack
+const MAX_JSON_DEPTH: usize = 64;
+
+struct JsonParser<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> JsonParser<'a> {
+    fn new(text: &'a str) -> Self {
+        Self { bytes: text.as_bytes(), pos: 0 }
+    }
+
+    fn parse(mut self) -> Result<Json, String> {
+        let value = self.value(0)?;
+        self.skip_whitespace();
+        if self.pos != self.bytes.len() {
+            return Err("Trailing data after model header".to_string());
+        }
+        Ok(value)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.pos < self.bytes.len() && self.bytes[self.pos].is_ascii_whitespace() {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), String> {
+        self.skip_whitespace();
+        if self.bytes.get(self.pos) == Some(&byte) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(format!("Malformed model header: expected '{}' at byte {}", byte as char, self.pos))
+        }
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Json, String> {
+        if depth > MAX_JSON_DEPTH {
+            return Err(format!("Model header nests deeper than {} levels", MAX_JSON_DEPTH));
+        }
+        self.skip_whitespace();
+        match self.bytes.get(self.pos) {
+            Some(b'{') => {
+                self.pos += 1;
+                let mut pairs = Vec::new();
+                self.skip_whitespace();
+                if self.bytes.get(self.pos) == Some(&b'}') {
+                    self.pos += 1;
+                    return Ok(Json::Object(pairs));
+                }
+                loop {
+                    self.skip_whitespace();
+                    let key = self.string()?;
+                    self.expect(b':')?;
+                    pairs.push((key, self.value(depth + 1)?));
+                    self.skip_whitespace();
+                    match self.bytes.get(self.pos) {
+                        Some(b',') => self.pos += 1,
+                        Some(b'}') => {
+                            self.pos += 1;
+                            return Ok(Json::Object(pairs));
+                        }
+                        _ => return Err("Malformed model header object".to_string()),
+                    }
+                }
+            }
+            Some(b'[') => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                self.skip_whitespace();
+                if self.bytes.get(self.pos) == Some(&b']') {
+                    self.pos += 1;
+                    return Ok(Json::Array(items));
+                }
+                loop {
+                    items.push(self.value(depth + 1)?);
+                    self.skip_whitespace();
+                    match self.bytes.get(self.pos) {
+                        Some(b',') => self.pos += 1,
+                        Some(b']') => {
+                            self.pos += 1;
+                            return Ok(Json::Array(items));
+                        }
+                        _ => return Err("Malformed model header array".to_string()),
+                    }
+                }
+            }
+            Some(b'"') => self.string().map(Json::String),
+            Some(b't') | Some(b'f') | Some(b'n') => {
+                for word in ["true", "false", "null"] {
+                    if self.bytes[self.pos..].starts_with(word.as_bytes()) {
+                        self.pos += word.len();
+                        return Ok(if word == "null" { Json::Null } else { Json::Bool });
+                    }
+                }
+                Err("Malformed model header literal".to_string())
+            }
+            Some(_) => {
+                let start = self.pos;
+                while self.pos < self.bytes.len() && b"+-0123456789.eE".contains(&self.bytes[self.pos]) {
+                    self.pos += 1;
+                }
+                std::str::from_utf8(&self.bytes[start..self.pos])
+                    .ok()
+                    .and_then(|s| s.parse().ok())
+                    .map(Json::Number)
+                    .ok_or_else(|| "Malformed model header number".to_string())
+            }
+            None => Err("Model header ended unexpectedly".to_string()),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.expect(b'"')?;
+        let mut out = String::new();
+        loop {
+            let &byte = self.bytes.get(self.pos).ok_or("Unterminated string in model header")?;
+            self.pos += 1;
+            match byte {
+                b'"' => return Ok(out),
+                b'\\' => {
+                    let &escape = self.bytes.get(self.pos).ok_or("Unterminated escape in model header")?;
+                    self.pos += 1;
+                    match escape {
+                        b'"' | b'\\' | b'/' => out.push(escape as char),
+                        b'b' => out.push('\u{8}'),
+                        b'f' => out.push('\u{c}'),
+                        b'n' => out.push('\n'),
+                        b'r' => out.push('\r'),
+                        b't' => out.push('\t'),
+                        b'u' => {
+                            let hex = self.bytes.get(self.pos..self.pos + 4).ok_or("Bad unicode escape")?;
+                            let code = u32::from_str_radix(std::str::from_utf8(hex).map_err(|_| "Bad unicode escape")?, 16)
+                                .map_err(|_| "Bad unicode escape")?;
+                            out.push(char::from_u32(code).unwrap_or('\u{fffd}'));
+                            self.pos += 4;
+                        }
+                        other => return Err(format!("Bad escape '\\{}' in model header", other as char)),
+                    }
+                }
+                _ => {
+                    // Copy a whole UTF-8 sequence at once
+                    let start = self.pos - 1;
+                    let len = match byte {
+                        0xf0..=0xff => 4,
+                        0xe0..=0xef => 3,
+                        0xc0..=0xdf => 2,
+                        _ => 1,
+                    };
+                    let chunk = self.bytes.get(start..start + len).ok_or("Bad UTF-8 in model header")?;
+                    out.push_str(std::str::from_utf8(chunk).map_err(|_| "Bad UTF-8 in model header")?);
+                    self.pos = start + len;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::image::Pixel;
+
+    /// Serialise F32 tensors and metadata into a safetensors file
+    fn write_safetensors(metadata: &[(&str, &str)], tensors: &[(&str, Vec<usize>, Vec<f32>)]) -> Vec<u8> {
+        let mut entries = Vec::new();
+        let meta: Vec<String> = metadata.iter().map(|(k, v)| format!("\"{}\":\"{}\"", k, v)).collect();
+        entries.push(format!("\"__metadata__\":{{{}}}", meta.join(",")));
+
+        let mut data = Vec::new();
+        for (name, shape, values) in tensors {
+            let start = data.len();
+            values.iter().for_each(|v| data.extend_from_slice(&v.to_le_bytes()));
+            let shape: Vec<String> = shape.iter().map(|s| s.to_string()).collect();
+            entries.push(format!(
+                "\"{}\":{{\"dtype\":\"F32\",\"shape\":[{}],\"data_offsets\":[{},{}]}}",
+                name, shape.join(","), start, data.len()
+            ));
+        }
+
+        let header = format!("{{{}}}", entries.join(","));
+        let mut bytes = (header.len() as u64).to_le_bytes().to_vec();
+        bytes.extend_from_slice(header.as_bytes());
+        bytes.extend(data);
+        bytes
+    }
+
+    fn create_gray_image() -> Image {
+        let mut img = Image::new(3, 2);
+        for (i, px) in img.pixels.iter_mut().enumerate() {
+            let v = (i * 40 + 20) as u8;
+            *px = Pixel::new(v, v, v);
+        }
+        img
+    }
+
+    #[test]
+    fn test_espcn_pixel_shuffle_replicates_pixels() {
+        // One 1x1 conv copying luma into all four sub-pixel channels
+        let bytes = write_safetensors(
+            &[("architecture", "espcn"), ("scale", "2")],
+            &[("layers.0.weight", vec![4, 1, 1, 1], vec![1.0; 4]), ("layers.0.bias", vec![4], vec![0.0; 4])],
+        );
+        let upscaler = NeuralUpscaler::new(SrNetwork::from_safetensors(&bytes).unwrap());
+        let img = create_gray_image();
+
+        let result = upscaler.upscale(&img, 2.0);
+        let nearest = crate::instant::NearestNeighbor.upscale(&img, 2.0);
+        for (a, b) in result.pixels.iter().zip(&nearest.pixels) {
+            assert!((a.r as i32 - b.r as i32).abs() <= 1);
+        }
+    }
+
+    #[test]
+    fn test_fsrcnn_transposed_layer_and_fallback() {
+        // Conv with PReLU, then a 5x5 transposed conv (padding 2) whose
+        // lower-right 3x3 taps splat each pixel into its own 3x3 block
+        let splat: Vec<f32> = (0..25).map(|i| if i / 5 >= 2 && i % 5 >= 2 { 1.0 } else { 0.0 }).collect();
+        let bytes = write_safetensors(
+            &[("architecture", "fsrcnn"), ("scale", "3")],
+            &[
+                ("layers.0.weight", vec![2, 1, 1, 1], vec![1.0, -1.0]),
+                ("layers.0.alpha", vec![2], vec![0.0, 0.0]),
+                ("layers.1.weight", vec![2, 1, 5, 5], [splat, vec![0.0; 25]].concat()),
+            ],
+        );
+        let upscaler = NeuralUpscaler::new(SrNetwork::from_safetensors(&bytes).unwrap());
+        assert_eq!(upscaler.name(), "FSRCNN");
+
+        let img = create_gray_image();
+        let result = upscaler.upscale(&img, 3.0);
+        assert_eq!((result.width, result.height), (9, 6));
+        let nearest = crate::instant::NearestNeighbor.upscale(&img, 3.0);
+        for (a, b) in result.pixels.iter().zip(&nearest.pixels) {
+            assert!((a.r as i32 - b.r as i32).abs() <= 1);
+        }
+
+        // Factors the model was not trained for fall back to Lanczos
+        let fallback = upscaler.upscale(&img, 1.5);
+        assert_eq!(fallback.pixels, Lanczos::new().upscale(&img, 1.5).pixels);
+    }
+
+    #[test]
+    fn test_rejects_malformed_models() {
+        let good = write_safetensors(
+            &[("architecture", "srcnn"), ("scale", "2")],
+            &[("layers.0.weight", vec![1, 1, 1, 1], vec![1.0])],
+        );
+        assert!(SrNetwork::from_safetensors(&good).is_ok());
+        assert!(SrNetwork::from_safetensors(&good[..good.len() - 1]).is_err());
+        let mut huge_header = u64::MAX.to_le_bytes().to_vec();
+        huge_header.extend_from_slice(b"{}");
+        assert!(SrNetwork::from_safetensors(&huge_header).is_err());
+        let huge_shape = write_safetensors(
+            &[("architecture", "srcnn"), ("scale", "2")],
+            &[("layers.0.weight", vec![1 << 40, 1 << 40], vec![1.0])],
+        );
+        assert!(SrNetwork::from_safetensors(&huge_shape).is_err());
+
+        // Nesting is capped and every JSON escape is decoded or refused
+        let nested = |depth: usize| format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+        assert!(JsonParser::new(&nested(MAX_JSON_DEPTH)).parse().is_ok());
+        assert!(JsonParser::new(&nested(100_000)).parse().is_err());
+        let Ok(Json::String(escaped)) = JsonParser::new(r#""\"\\\/\b\f\n\r\t""#).parse() else {
+            panic!("escapes should parse");
+        };
+        assert_eq!(escaped, "\"\\/\u{8}\u{c}\n\r\t");
+        assert!(JsonParser::new(r#""\x""#).parse().is_err());
+
+        let wrong_scale = write_safetensors(
+            &[("architecture", "srcnn"), ("scale", "5")],
+            &[("layers.0.weight", vec![1, 1, 1, 1], vec![1.0])],
+        );
+        assert!(SrNetwork::from_safetensors(&wrong_scale).is_err());
+        assert_eq!(f16_to_f32(0x3c00), 1.0);
+        assert_eq!(f16_to_f32(0xc000), -2.0);
+    }
+}
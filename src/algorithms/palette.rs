@@ -0,0 +1,264 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::algorithms::image::{Image, Pixel};
+
+/// Largest palette an indexed PNG or GIF can hold
+pub const MAX_PALETTE: usize = 256;
+
+/// Largest palette content analysis keeps, in line with the pixel-art colour limit
+pub const MAX_ANALYSIS_PALETTE: usize = 255;
+
+/// 4x4 Bayer matrix for ordered dithering
+const BAYER: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+/// Every colour of the image, most common first, or `None` past `max_colors`
+pub fn extract_palette(image: &Image, max_colors: usize) -> Option<Vec<Pixel>> {
+    let mut counts: HashMap<(u8, u8, u8), (usize, usize)> = HashMap::new();
+    for p in &image.pixels {
+        let next = counts.len();
+        counts.entry((p.r, p.g, p.b)).or_insert((0, next)).0 += 1;
+        if counts.len() > max_colors {
+            return None;
+        }
+    }
+    let mut colours: Vec<_> = counts.into_iter().collect();
+    // Ties keep first-seen order so the palette is deterministic
+    colours.sort_by_key(|&(_, (count, first))| (std::cmp::Reverse(count), first));
+    Some(colours.into_iter().map(|((r, g, b), _)| Pixel::new(r, g, b)).collect())
+}
+
+/// Whether an algorithm blends neighbouring colours into ones not in the palette
+pub fn is_palette_blending(name: &str) -> bool {
+    matches!(
+        name.to_lowercase().as_str(),
+        "bilinear" | "bicubic" | "edge_directed" | "edi" | "scale_by_rules" | "xbr"
+    )
+}
+
+/// sRGB to OKLab, where Euclidean distance follows perceived difference
+fn oklab(p: Pixel) -> [f32; 3] {
+    let linear = |c: u8| {
+        let c = c as f32 / 255.0;
+        if c <= 0.04045 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) }
+    };
+    let (r, g, b) = (linear(p.r), linear(p.g), linear(p.b));
+    let l = (0.412_221_46 * r + 0.536_332_55 * g + 0.051_445_995 * b).cbrt();
+    let m = (0.211_903_5 * r + 0.680_699_5 * g + 0.107_396_96 * b).cbrt();
+    let s = (0.088_302_46 * r + 0.281_718_85 * g + 0.629_978_7 * b).cbrt();
+    [
+        0.210_454_26 * l + 0.793_617_8 * m - 0.004_072_047 * s,
+        1.977_998_5 * l - 2.428_592_2 * m + 0.450_593_7 * s,
+        0.025_904_037 * l + 0.782_771_77 * m - 0.808_675_77 * s,
+    ]
+}
+
+/// Snaps colours to a fixed palette
+///
+/// The nearest entry is found in OKLab, so a blended pixel goes to the
+/// palette colour it looks closest to rather than the closest in RGB. Optional
+/// ordered dithering offsets each pixel by a 4x4 Bayer threshold scaled to the
+/// palette's typical spacing, turning smooth blends into a fixed pattern of
+/// neighbouring entries instead of hard bands.
+///
+/// Time complexity: O(n * palette) worst case, O(n) for repeated colours
+/// Space complexity: O(distinct colours) for the lookup cache
+pub struct PaletteMapper {
+    palette: Vec<Pixel>,
+    lab: Vec<[f32; 3]>,
+    dither: f32,
+}
+
+impl PaletteMapper {
+    /// Fails past 256 colours, which 8-bit indices can't address
+    pub fn new(palette: Vec<Pixel>) -> Result<Self, String> {
+        if palette.len() > MAX_PALETTE {
+            return Err(format!("A palette holds at most {} colours, got {}", MAX_PALETTE, palette.len()));
+        }
+        let lab = palette.iter().map(|&p| oklab(p)).collect();
+        Ok(Self { palette, lab, dither: 0.0 })
+    }
+
+    /// Ordered dithering strength, 0 (off) to 1 (a full palette step)
+    pub fn with_dither(mut self, strength: f32) -> Self {
+        self.dither = strength.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn palette(&self) -> &[Pixel] {
+        &self.palette
+    }
+
+    /// Index of the perceptually nearest palette entry
+    pub fn nearest(&self, p: Pixel) -> usize {
+        let target = oklab(p);
+        self.lab
+            .iter()
+            .map(|lab| (0..3).map(|c| (lab[c] - target[c]).powi(2)).sum::<f32>())
+            .enumerate()
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map_or(0, |(i, _)| i)
+    }
+
+    /// Mean RGB distance from each entry to its closest other entry
+    fn spacing(&self) -> f32 {
+        if self.palette.len() < 2 {
+            return 0.0;
+        }
+        let rgb = |p: Pixel| [p.r as f32, p.g as f32, p.b as f32];
+        let total: f32 = self.palette.iter()
+            .enumerate()
+            .map(|(i, &a)| {
+                self.palette.iter()
+                    .enumerate()
+                    .filter(|&(j, _)| j != i)
+                    .map(|(_, &b)| (0..3).map(|c| (rgb(a)[c] - rgb(b)[c]).powi(2)).sum::<f32>().sqrt())
+                    .fold(f32::MAX, f32::min)
+            })
+            .sum();
+        total / self.palette.len() as f32
+    }
+
+    /// Snap every pixel, dithering if enabled
+    pub fn map(&self, image: &Image) -> IndexedImage {
+        let spread = self.dither * self.spacing();
+        let mut cache: HashMap<(u8, u8, u8), u8> = HashMap::new();
+        let mut indices = Vec::with_capacity(image.pixels.len());
+
+        for (i, &p) in image.pixels.iter().enumerate() {
+            let p = if spread > 0.0 {
+                let (x, y) = (i % image.width, i / image.width);
+                let offset = ((BAYER[y % 4][x % 4] as f32 + 0.5) / 16.0 - 0.5) * spread;
+                let shift = |c: u8| (c as f32 + offset).round().clamp(0.0, 255.0) as u8;
+                Pixel::new(shift(p.r), shift(p.g), shift(p.b))
+            } else {
+                p
+            };
+            let index = *cache.entry((p.r, p.g, p.b)).or_insert_with(|| self.nearest(p) as u8);
+            indices.push(index);
+        }
+
+        IndexedImage {
+            width: image.width,
+            height: image.height,
+            palette: self.palette.clone(),
+            indices,
+        }
+    }
+}
+
+/// Palette image with one byte per pixel, saved as indexed PNG or GIF
+#[derive(Debug, Clone)]
+pub struct IndexedImage {
+    pub width: usize,
+    pub height: usize,
+    pub palette: Vec<Pixel>,
+    pub indices: Vec<u8>,
+}
+
+impl IndexedImage {
+    /// Index an image exactly; fails if it has more than 256 colours
+    pub fn from_image(image: &Image) -> Result<Self, String> {
+        let palette = extract_palette(image, MAX_PALETTE)
+            .ok_or_else(|| format!("Indexed output needs at most {} colours", MAX_PALETTE))?;
+        Ok(PaletteMapper::new(palette)?.map(image))
+    }
+
+    pub fn to_image(&self) -> Image {
+        Image {
+            width: self.width,
+            height: self.height,
+            pixels: self.indices.iter().map(|&i| self.palette[i as usize]).collect(),
+        }
+    }
+
+    /// Save as an indexed PNG or GIF, chosen by extension
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), String> {
+        let path = path.as_ref();
+        let extension = path.extension().and_then(|e| e.to_str()).map(str::to_lowercase);
+        if !matches!(extension.as_deref(), Some("png" | "gif")) {
+            return Err("Indexed output needs a .png or .gif path".to_string());
+        }
+        let rgb: Vec<u8> = self.palette.iter().flat_map(|p| [p.r, p.g, p.b]).collect();
+        let file = std::fs::File::create(path).map_err(|e| format!("Failed to save indexed image: {}", e))?;
+        let writer = std::io::BufWriter::new(file);
+
+        match extension.as_deref() {
+            Some("png") => {
+                let mut encoder = png::Encoder::new(writer, self.width as u32, self.height as u32);
+                encoder.set_color(png::ColorType::Indexed);
+                encoder.set_depth(png::BitDepth::Eight);
+                encoder.set_palette(rgb);
+                encoder.write_header()
+                    .and_then(|mut writer| writer.write_image_data(&self.indices))
+                    .map_err(|e| format!("Failed to save indexed PNG: {}", e))
+            }
+            _ => {
+                let (w, h) = (u16::try_from(self.width), u16::try_from(self.height));
+                let (Ok(w), Ok(h)) = (w, h) else {
+                    return Err("GIF images are limited to 65535x65535".to_string());
+                };
+                let mut encoder = gif::Encoder::new(writer, w, h, &rgb).map_err(|e| format!("Failed to save indexed GIF: {}", e))?;
+                let frame = gif::Frame {
+                    width: w,
+                    height: h,
+                    buffer: std::borrow::Cow::Borrowed(&self.indices),
+                    ..gif::Frame::default()
+                };
+                encoder.write_frame(&frame).map_err(|e| format!("Failed to save indexed GIF: {}", e))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::upscaler::Upscaler;
+
+    fn two_tone() -> Image {
+        let mut img = Image::new(8, 4);
+        for y in 0..4 {
+            for x in 0..8 {
+                let p = if x < 4 { Pixel::new(20, 40, 160) } else { Pixel::new(240, 200, 40) };
+                img.set_pixel(x, y, p);
+            }
+        }
+        img
+    }
+
+    #[test]
+    fn test_snapping_keeps_the_palette() {
+        let img = two_tone();
+        let palette = extract_palette(&img, MAX_PALETTE).unwrap();
+        assert_eq!(palette.len(), 2);
+        assert!(extract_palette(&img, 1).is_none());
+
+        let blended = crate::algorithms::fast::Bicubic.upscale(&img, 3.0);
+        let snapped = PaletteMapper::new(palette.clone()).unwrap().map(&blended).to_image();
+        assert!(snapped.pixels.iter().all(|p| palette.contains(p)));
+        assert_eq!(snapped.get_pixel(0, 0), Some(Pixel::new(20, 40, 160)));
+        assert_eq!(snapped.get_pixel(23, 11), Some(Pixel::new(240, 200, 40)));
+
+        // A half-way blend dithers into a mix of both entries
+        let grey = Image::from_pixels(4, 4, vec![Pixel::new(130, 120, 100); 16]).unwrap();
+        let dithered = PaletteMapper::new(palette).unwrap().with_dither(1.0).map(&grey);
+        assert!(dithered.indices.contains(&0) && dithered.indices.contains(&1));
+
+        // 8-bit indices can't address a 257th colour
+        let too_many: Vec<Pixel> = (0..=MAX_PALETTE).map(|i| Pixel::new(i as u8, (i / 256) as u8, 0)).collect();
+        assert!(PaletteMapper::new(too_many).is_err());
+    }
+
+    #[test]
+    fn test_indexed_png_round_trip() {
+        let img = two_tone();
+        let indexed = IndexedImage::from_image(&img).unwrap();
+        let path = std::env::temp_dir().join(format!("palette_test_{}.png", std::process::id()));
+        indexed.save(&path).unwrap();
+        let loaded = crate::algorithms::label::LabelMap::load(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert!(matches!(loaded.format, crate::algorithms::label::LabelFormat::Indexed(ref p) if p.len() == 2));
+        assert_eq!(loaded.to_image().pixels, img.pixels);
+    }
+}
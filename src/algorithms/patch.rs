@@ -0,0 +1,256 @@
+use crate::algorithms::image::FloatImage;
+
+/// Single-channel f32 plane, used for patch matching on luminance
+#[derive(Debug, Clone)]
+pub struct Plane {
+    pub width: usize,
+    pub height: usize,
+    pub data: Vec<f32>,
+}
+
+impl Plane {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            data: vec![0.0; width * height],
+        }
+    }
+
+    /// Rec. 601 luma of an RGB image
+    pub fn luma(image: &FloatImage) -> Self {
+        Self {
+            width: image.width,
+            height: image.height,
+            data: image.data.iter().map(|c| 0.299 * c[0] + 0.587 * c[1] + 0.114 * c[2]).collect(),
+        }
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> f32 {
+        self.data[y * self.width + x]
+    }
+
+    pub fn get_clamped(&self, x: i32, y: i32) -> f32 {
+        let x = x.clamp(0, self.width as i32 - 1) as usize;
+        let y = y.clamp(0, self.height as i32 - 1) as usize;
+        self.data[y * self.width + x]
+    }
+
+    /// Copy the `size` x `size` patch with top-left corner (x, y), row-major
+    pub fn patch(&self, x: i32, y: i32, size: usize) -> Vec<f32> {
+        let mut out = Vec::with_capacity(size * size);
+        for dy in 0..size as i32 {
+            for dx in 0..size as i32 {
+                out.push(self.get_clamped(x + dx, y + dy));
+            }
+        }
+        out
+    }
+
+    /// Sum of squared differences between `patch` and the patch at (x, y)
+    ///
+    /// Stops accumulating once `limit` is exceeded, so callers searching for a
+    /// minimum can pass their best distance so far.
+    pub fn ssd(&self, patch: &[f32], x: i32, y: i32, size: usize, limit: f32) -> f32 {
+        let mut sum = 0.0;
+        for dy in 0..size {
+            let sy = (y + dy as i32).clamp(0, self.height as i32 - 1) as usize;
+            for dx in 0..size {
+                let sx = (x + dx as i32).clamp(0, self.width as i32 - 1) as usize;
+                let d = self.data[sy * self.width + sx] - patch[dy * size + dx];
+                sum += d * d;
+            }
+            if sum > limit {
+                return sum;
+            }
+        }
+        sum
+    }
+}
+
+/// Top-left corners of patches of `size` placed every `stride` pixels,
+/// with a final patch flush against the far edge
+pub fn patch_origins(len: usize, size: usize, stride: usize) -> Vec<usize> {
+    if len <= size {
+        return vec![0];
+    }
+    let last = len - size;
+    let mut origins: Vec<usize> = (0..=last).step_by(stride.max(1)).collect();
+    if origins.last() != Some(&last) {
+        origins.push(last);
+    }
+    origins
+}
+
+/// k-d tree over fixed-length vectors with best-bin-first approximate search
+///
+/// Points are stored flat; `nearest` visits leaves in order of their distance
+/// bound and gives up after `max_checks` candidate points, trading exactness
+/// for speed on large patch databases.
+pub struct KdTree {
+    dim: usize,
+    points: Vec<f32>,
+    nodes: Vec<KdNode>,
+    order: Vec<usize>,
+}
+
+enum KdNode {
+    Leaf { start: usize, end: usize },
+    Split { axis: usize, value: f32, left: usize, right: usize },
+}
+
+const KD_LEAF_SIZE: usize = 8;
+
+impl KdTree {
+    /// Build from `points.len() / dim` vectors stored back to back
+    pub fn new(points: Vec<f32>, dim: usize) -> Self {
+        let count = points.len().checked_div(dim).unwrap_or(0);
+        let mut tree = Self {
+            dim,
+            points,
+            nodes: Vec::new(),
+            order: (0..count).collect(),
+        };
+        if count > 0 {
+            tree.build(0, count);
+        }
+        tree
+    }
+
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+
+    /// Vector stored at `index`
+    pub fn point(&self, index: usize) -> &[f32] {
+        &self.points[index * self.dim..(index + 1) * self.dim]
+    }
+
+    fn build(&mut self, start: usize, end: usize) -> usize {
+        let id = self.nodes.len();
+        if end - start <= KD_LEAF_SIZE {
+            self.nodes.push(KdNode::Leaf { start, end });
+            return id;
+        }
+
+        // Split on the axis of greatest spread, at the median
+        let axis = (0..self.dim)
+            .map(|axis| {
+                let (lo, hi) = self.order[start..end].iter().fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), &i| {
+                    let v = self.points[i * self.dim + axis];
+                    (lo.min(v), hi.max(v))
+                });
+                (axis, hi - lo)
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map_or(0, |(axis, _)| axis);
+
+        let mid = (start + end) / 2;
+        let (dim, points) = (self.dim, &self.points);
+        self.order[start..end].select_nth_unstable_by(mid - start, |&a, &b| {
+            points[a * dim + axis].total_cmp(&points[b * dim + axis])
+        });
+        let value = self.points[self.order[mid] * self.dim + axis];
+
+        self.nodes.push(KdNode::Leaf { start: 0, end: 0 });
+        let left = self.build(start, mid);
+        let right = self.build(mid, end);
+        self.nodes[id] = KdNode::Split { axis, value, left, right };
+        id
+    }
+
+    /// Approximate nearest neighbour: `(index, squared distance)`
+    pub fn nearest(&self, query: &[f32], max_checks: usize) -> Option<(usize, f32)> {
+        use std::cmp::Reverse;
+        use std::collections::BinaryHeap;
+
+        if self.is_empty() {
+            return None;
+        }
+
+        // Min-heap of (distance bound, node); bounds are non-negative so their
+        // bit patterns order like the floats themselves
+        let mut queue = BinaryHeap::new();
+        queue.push(Reverse((0u32, 0usize)));
+        let mut best = (0, f32::INFINITY);
+        let mut checked = 0;
+
+        while let Some(Reverse((bound, node))) = queue.pop() {
+            if f32::from_bits(bound) >= best.1 || (checked >= max_checks && best.1.is_finite()) {
+                break;
+            }
+            let mut node = node;
+            let bound = f32::from_bits(bound);
+            loop {
+                match self.nodes[node] {
+                    KdNode::Leaf { start, end } => {
+                        for &i in &self.order[start..end] {
+                            let d: f32 = self.point(i).iter().zip(query).map(|(a, b)| (a - b) * (a - b)).sum();
+                            if d < best.1 {
+                                best = (i, d);
+                            }
+                        }
+                        checked += end - start;
+                        break;
+                    }
+                    KdNode::Split { axis, value, left, right } => {
+                        let diff = query[axis] - value;
+                        let (near, far) = if diff < 0.0 { (left, right) } else { (right, left) };
+                        let far_bound = bound.max(diff * diff);
+                        if far_bound < best.1 {
+                            queue.push(Reverse((far_bound.to_bits(), far)));
+                        }
+                        node = near;
+                    }
+                }
+            }
+        }
+
+        Some(best)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_patch_origins_cover_the_edges() {
+        assert_eq!(patch_origins(10, 4, 3), vec![0, 3, 6]);
+        assert_eq!(patch_origins(11, 4, 3), vec![0, 3, 6, 7]);
+        assert_eq!(patch_origins(3, 4, 2), vec![0]);
+    }
+
+    #[test]
+    fn test_kd_tree_finds_exact_match() {
+        let points: Vec<f32> = (0..200).flat_map(|i| [(i % 17) as f32, (i / 17) as f32, (i * 7 % 13) as f32]).collect();
+        let tree = KdTree::new(points, 3);
+        assert_eq!(tree.len(), 200);
+
+        let query = tree.point(123).to_vec();
+        assert_eq!(tree.nearest(&query, usize::MAX), Some((123, 0.0)));
+
+        // With an unlimited budget the search is exact
+        let query = [5.2, 3.9, 1.1];
+        let brute = (0..tree.len())
+            .map(|i| tree.point(i).iter().zip(&query).map(|(a, b)| (a - b) * (a - b)).sum::<f32>())
+            .fold(f32::INFINITY, f32::min);
+        assert_eq!(tree.nearest(&query, usize::MAX).unwrap().1, brute);
+    }
+
+    #[test]
+    fn test_ssd_matches_patch() {
+        let mut plane = Plane::new(4, 4);
+        for (i, v) in plane.data.iter_mut().enumerate() {
+            *v = i as f32;
+        }
+        let patch = plane.patch(1, 1, 2);
+        assert_eq!(patch, vec![5.0, 6.0, 9.0, 10.0]);
+        assert_eq!(plane.ssd(&patch, 1, 1, 2, f32::INFINITY), 0.0);
+        assert_eq!(plane.ssd(&patch, 2, 1, 2, f32::INFINITY), 4.0);
+    }
+}
@@ -0,0 +1,591 @@
+use std::path::Path;
+
+use crate::algorithms::degradation::Psf;
+use crate::algorithms::image::{FloatImage, Image};
+use crate::algorithms::linalg::ridge_solve;
+use crate::algorithms::patch::Plane;
+use crate::algorithms::upscaler::{Upscaler, UpscaleTier};
+
+/// File signature of a serialised filter bank
+const MAGIC: &[u8; 4] = b"RSFB";
+const VERSION: u16 = 1;
+
+/// Per-bucket linear filters learned by `RaisrTrainer`
+///
+/// Buckets are indexed by pixel type (output position modulo the scale) and
+/// by the angle, strength and coherence of the local gradient. Filters apply
+/// to the luma of a bicubic base, taps in row-major patch order.
+///
+/// The file format is little-endian: the 4-byte magic `RSFB`, a u16 version,
+/// u8 scale, patch size, angle bins, strength bins and coherence bins, the
+/// f32 strength and coherence thresholds (bins - 1 each), then every filter
+/// as f32 taps ordered by pixel type, angle, strength, coherence. Every
+/// header field must fit its byte, which construction checks.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilterBank {
+    scale: usize,
+    patch_size: usize,
+    angle_bins: usize,
+    strength_thresholds: Vec<f32>,
+    coherence_thresholds: Vec<f32>,
+    filters: Vec<f32>,
+}
+
+/// Gradient features that select a bucket
+#[derive(Debug, Clone, Copy)]
+struct GradientHash {
+    angle: f32,
+    strength: f32,
+    coherence: f32,
+}
+
+impl FilterBank {
+    /// A bank whose filters all pass the bicubic base through unchanged
+    ///
+    /// Fails for scales below 2, which could never enlarge an image, and for
+    /// a scale or patch size the file header can't hold.
+    pub fn identity(scale: usize, patch_size: usize) -> Result<Self, String> {
+        if scale < 2 {
+            return Err(format!("Filter bank scale must be at least 2, got {}", scale));
+        }
+        check_header_limits(&[("scale", scale), ("patch size", patch_size)])?;
+        if patch_size == 0 {
+            return Err("Filter bank patch size must be at least 1".to_string());
+        }
+        let mut bank = Self {
+            scale,
+            patch_size,
+            angle_bins: 1,
+            strength_thresholds: Vec::new(),
+            coherence_thresholds: Vec::new(),
+            filters: Vec::new(),
+        };
+        let taps = patch_size * patch_size;
+        bank.filters = (0..bank.bucket_count()).flat_map(|_| identity_filter(taps)).collect();
+        Ok(bank)
+    }
+
+    /// Native enlargement of one pass
+    pub fn scale(&self) -> usize {
+        self.scale
+    }
+
+    /// Width and height of each filter in pixels
+    pub fn patch_size(&self) -> usize {
+        self.patch_size
+    }
+
+    pub fn angle_bins(&self) -> usize {
+        self.angle_bins
+    }
+
+    /// Gradient strengths separating the strength bins
+    pub fn strength_thresholds(&self) -> &[f32] {
+        &self.strength_thresholds
+    }
+
+    /// Gradient coherences separating the coherence bins
+    pub fn coherence_thresholds(&self) -> &[f32] {
+        &self.coherence_thresholds
+    }
+
+    fn taps(&self) -> usize {
+        self.patch_size * self.patch_size
+    }
+
+    fn buckets_per_type(&self) -> usize {
+        self.angle_bins * (self.strength_thresholds.len() + 1) * (self.coherence_thresholds.len() + 1)
+    }
+
+    fn bucket_count(&self) -> usize {
+        self.scale * self.scale * self.buckets_per_type()
+    }
+
+    /// Bucket index for a pixel type and gradient hash
+    fn bucket(&self, pixel_type: usize, hash: GradientHash) -> usize {
+        let angle = ((hash.angle / std::f32::consts::PI * self.angle_bins as f32) as usize).min(self.angle_bins - 1);
+        let strength = self.strength_thresholds.iter().filter(|&&t| hash.strength >= t).count();
+        let coherence = self.coherence_thresholds.iter().filter(|&&t| hash.coherence >= t).count();
+
+        let strength_bins = self.strength_thresholds.len() + 1;
+        let coherence_bins = self.coherence_thresholds.len() + 1;
+        ((pixel_type * self.angle_bins + angle) * strength_bins + strength) * coherence_bins + coherence
+    }
+
+    fn filter(&self, bucket: usize) -> &[f32] {
+        &self.filters[bucket * self.taps()..(bucket + 1) * self.taps()]
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&VERSION.to_le_bytes());
+        for v in [
+            self.scale,
+            self.patch_size,
+            self.angle_bins,
+            self.strength_thresholds.len() + 1,
+            self.coherence_thresholds.len() + 1,
+        ] {
+            bytes.push(v as u8);
+        }
+        for v in self.strength_thresholds.iter().chain(&self.coherence_thresholds).chain(&self.filters) {
+            bytes.extend_from_slice(&v.to_le_bytes());
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        if bytes.len() < 11 || &bytes[0..4] != MAGIC {
+            return Err("Not a RAISR filter bank".to_string());
+        }
+        let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+        if version != VERSION {
+            return Err(format!("Unsupported filter bank version {}", version));
+        }
+
+        let [scale, patch_size, angle_bins, strength_bins, coherence_bins] =
+            [bytes[6], bytes[7], bytes[8], bytes[9], bytes[10]].map(|b| b as usize);
+        if scale == 0 || patch_size == 0 || angle_bins == 0 || strength_bins == 0 || coherence_bins == 0 {
+            return Err("Filter bank header has a zero dimension".to_string());
+        }
+        if scale < 2 {
+            return Err(format!("Filter bank scale must be at least 2, got {}", scale));
+        }
+
+        let floats: Vec<f32> = bytes[11..]
+            .chunks_exact(4)
+            .map(|c| f32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+            .collect();
+
+        let mut bank = Self {
+            scale,
+            patch_size,
+            angle_bins,
+            strength_thresholds: floats.iter().take(strength_bins - 1).copied().collect(),
+            coherence_thresholds: floats.iter().skip(strength_bins - 1).take(coherence_bins - 1).copied().collect(),
+            filters: Vec::new(),
+        };
+        let header = strength_bins - 1 + coherence_bins - 1;
+        let expected = header + bank.bucket_count() * bank.taps();
+        if floats.len() != expected || !(bytes.len() - 11).is_multiple_of(4) {
+            return Err(format!("Filter bank is truncated: expected {} values, found {}", expected, floats.len()));
+        }
+        bank.filters = floats[header..].to_vec();
+        Ok(bank)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), String> {
+        std::fs::write(path, self.to_bytes()).map_err(|e| format!("Failed to save filter bank: {}", e))
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let bytes = std::fs::read(path).map_err(|e| format!("Failed to read filter bank: {}", e))?;
+        Self::from_bytes(&bytes)
+    }
+}
+
+/// Fail if a header field is too large for its byte in the file format
+fn check_header_limits(fields: &[(&str, usize)]) -> Result<(), String> {
+    match fields.iter().find(|(_, value)| *value > u8::MAX as usize) {
+        Some((name, value)) => Err(format!("Filter bank {} must be at most {}, got {}", name, u8::MAX, value)),
+        None => Ok(()),
+    }
+}
+
+fn identity_filter(taps: usize) -> Vec<f32> {
+    let mut filter = vec![0.0; taps];
+    filter[taps / 2] = 1.0;
+    filter
+}
+
+/// Structure-tensor hash of every pixel, over a Gaussian-weighted window
+fn gradient_hashes(luma: &Plane, window: usize) -> Vec<GradientHash> {
+    let (w, h) = (luma.width, luma.height);
+    let mut gx = Plane::new(w, h);
+    let mut gy = Plane::new(w, h);
+    for y in 0..h as i32 {
+        for x in 0..w as i32 {
+            let i = y as usize * w + x as usize;
+            gx.data[i] = (luma.get_clamped(x + 1, y) - luma.get_clamped(x - 1, y)) * 0.5;
+            gy.data[i] = (luma.get_clamped(x, y + 1) - luma.get_clamped(x, y - 1)) * 0.5;
+        }
+    }
+
+    let radius = (window / 2) as i32;
+    let sigma = (window as f32 / 3.0).max(0.5);
+    let weights: Vec<f32> = (-radius..=radius)
+        .flat_map(|dy| (-radius..=radius).map(move |dx| (dx, dy)))
+        .map(|(dx, dy)| (-((dx * dx + dy * dy) as f32) / (2.0 * sigma * sigma)).exp())
+        .collect();
+    let weight_sum: f32 = weights.iter().sum();
+
+    let mut hashes = Vec::with_capacity(w * h);
+    for y in 0..h as i32 {
+        for x in 0..w as i32 {
+            let (mut xx, mut xy, mut yy) = (0.0, 0.0, 0.0);
+            let mut k = 0;
+            for dy in -radius..=radius {
+                for dx in -radius..=radius {
+                    let (a, b) = (gx.get_clamped(x + dx, y + dy), gy.get_clamped(x + dx, y + dy));
+                    xx += weights[k] * a * a;
+                    xy += weights[k] * a * b;
+                    yy += weights[k] * b * b;
+                    k += 1;
+                }
+            }
+            let (xx, xy, yy) = (xx / weight_sum, xy / weight_sum, yy / weight_sum);
+
+            let trace = xx + yy;
+            let spread = ((xx - yy).powi(2) + 4.0 * xy * xy).sqrt();
+            let l1 = ((trace + spread) * 0.5).max(0.0).sqrt();
+            let l2 = ((trace - spread) * 0.5).max(0.0).sqrt();
+
+            // Dominant gradient orientation folded into [0, pi)
+            let angle = (0.5 * (2.0 * xy).atan2(xx - yy)).rem_euclid(std::f32::consts::PI);
+            hashes.push(GradientHash {
+                angle,
+                strength: l1,
+                coherence: if l1 + l2 > 1e-6 { (l1 - l2) / (l1 + l2) } else { 0.0 },
+            });
+        }
+    }
+    hashes
+}
+
+/// Pixel type of an output position for an integer scale
+fn pixel_type(x: usize, y: usize, scale: usize) -> usize {
+    (y % scale) * scale + (x % scale)
+}
+
+/// Fits a `FilterBank` from high-resolution examples by least squares
+///
+/// Each example is downsampled by the scale with a bicubic PSF, enlarged again
+/// with bicubic, and every pixel's luma patch is regressed onto the original
+/// luma within its bucket. Strength and coherence thresholds are the terciles
+/// of the training data, so buckets are evenly populated.
+pub struct RaisrTrainer {
+    scale: usize,
+    patch_size: usize,
+    angle_bins: usize,
+    strength_bins: usize,
+    coherence_bins: usize,
+    ridge: f64,
+    sample_stride: usize,
+}
+
+impl RaisrTrainer {
+    /// 7x7 filters over 24 angles, 3 strengths and 3 coherences
+    pub fn new(scale: usize) -> Self {
+        Self {
+            scale: scale.clamp(2, 4),
+            patch_size: 7,
+            angle_bins: 24,
+            strength_bins: 3,
+            coherence_bins: 3,
+            ridge: 1e-3,
+            sample_stride: 1,
+        }
+    }
+
+    pub fn with_patch_size(mut self, patch_size: usize) -> Self {
+        // Odd sizes keep the filter centred on the pixel
+        self.patch_size = (patch_size | 1).clamp(3, 15);
+        self
+    }
+
+    pub fn with_bins(mut self, angle: usize, strength: usize, coherence: usize) -> Self {
+        self.angle_bins = angle.clamp(1, 255);
+        self.strength_bins = strength.clamp(1, 255);
+        self.coherence_bins = coherence.clamp(1, 255);
+        self
+    }
+
+    /// Ridge regularisation, relative to the mean diagonal of each bucket's normal matrix
+    pub fn with_ridge(mut self, ridge: f64) -> Self {
+        self.ridge = ridge;
+        self
+    }
+
+    /// Use every n-th pixel in each direction as a training sample
+    pub fn with_sample_stride(mut self, stride: usize) -> Self {
+        self.sample_stride = stride.max(1);
+        self
+    }
+
+    /// Train from in-memory high-resolution images
+    pub fn train_images(&self, images: &[Image]) -> Result<FilterBank, String> {
+        let pairs: Vec<(Plane, Plane)> = images.iter().filter_map(|im| training_pair(im, self.scale, self.patch_size)).collect();
+        if pairs.is_empty() {
+            return Err(format!(
+                "No training image is at least {}x{} pixels",
+                self.patch_size * self.scale,
+                self.patch_size * self.scale
+            ));
+        }
+
+        let hashes: Vec<Vec<GradientHash>> = pairs.iter().map(|(base, _)| gradient_hashes(base, self.patch_size)).collect();
+
+        // Terciles (or other quantiles) of strength and coherence
+        let quantiles = |values: &mut Vec<f32>, bins: usize| -> Vec<f32> {
+            values.sort_by(|a, b| a.total_cmp(b));
+            (1..bins).map(|i| values[(values.len() * i / bins).min(values.len() - 1)]).collect()
+        };
+        let mut strengths: Vec<f32> = hashes.iter().flatten().map(|h| h.strength).collect();
+        let mut coherences: Vec<f32> = hashes.iter().flatten().map(|h| h.coherence).collect();
+
+        let mut bank = FilterBank {
+            scale: self.scale,
+            patch_size: self.patch_size,
+            angle_bins: self.angle_bins,
+            strength_thresholds: quantiles(&mut strengths, self.strength_bins),
+            coherence_thresholds: quantiles(&mut coherences, self.coherence_bins),
+            filters: Vec::new(),
+        };
+
+        // Accumulate the normal equations of every bucket
+        let taps = bank.taps();
+        let buckets = bank.bucket_count();
+        let mut ata = vec![0.0f64; buckets * taps * taps];
+        let mut atb = vec![0.0f64; buckets * taps];
+        let mut counts = vec![0usize; buckets];
+        let radius = (self.patch_size / 2) as i32;
+
+        for ((base, original), hashes) in pairs.iter().zip(&hashes) {
+            for y in (0..base.height).step_by(self.sample_stride) {
+                for x in (0..base.width).step_by(self.sample_stride) {
+                    let bucket = bank.bucket(pixel_type(x, y, self.scale), hashes[y * base.width + x]);
+                    let patch = base.patch(x as i32 - radius, y as i32 - radius, self.patch_size);
+                    let target = original.get(x, y) as f64;
+
+                    let a = &mut ata[bucket * taps * taps..(bucket + 1) * taps * taps];
+                    for i in 0..taps {
+                        let pi = patch[i] as f64;
+                        for j in i..taps {
+                            a[i * taps + j] += pi * patch[j] as f64;
+                        }
+                        atb[bucket * taps + i] += pi * target;
+                    }
+                    counts[bucket] += 1;
+                }
+            }
+        }
+
+        // Solve each bucket; sparse buckets keep the identity filter
+        for bucket in 0..buckets {
+            let a = &mut ata[bucket * taps * taps..(bucket + 1) * taps * taps];
+            for i in 0..taps {
+                for j in 0..i {
+                    a[i * taps + j] = a[j * taps + i];
+                }
+            }
+            let solution = if counts[bucket] >= taps {
+                ridge_solve(a, &atb[bucket * taps..(bucket + 1) * taps], taps, self.ridge)
+            } else {
+                None
+            };
+            match solution {
+                Some(filter) => bank.filters.extend(filter.iter().map(|&v| v as f32)),
+                None => bank.filters.extend(identity_filter(taps)),
+            }
+        }
+
+        Ok(bank)
+    }
+
+    /// Train from every image file in a local folder
+    pub fn train_folder<P: AsRef<Path>>(&self, folder: P) -> Result<FilterBank, String> {
+        let images = load_folder(folder.as_ref())?;
+        self.train_images(&images)
+    }
+}
+
+/// Bicubic base luma and original luma of one example, cropped to the scale
+///
+/// Examples smaller than `patch_size` low-resolution pixels are skipped.
+pub(crate) fn training_pair(image: &Image, scale: usize, patch_size: usize) -> Option<(Plane, Plane)> {
+    let (w, h) = (image.width / scale * scale, image.height / scale * scale);
+    if w < patch_size * scale || h < patch_size * scale {
+        return None;
+    }
+
+    let full = FloatImage::from_image(image);
+    let mut cropped = FloatImage::new(w, h);
+    for y in 0..h {
+        for x in 0..w {
+            cropped.set(x, y, full.get(x, y));
+        }
+    }
+
+    let low = Psf::Bicubic.resample(&cropped, w / scale, h / scale);
+    let base = Psf::Bicubic.resample(&low, w, h);
+    Some((Plane::luma(&base), Plane::luma(&cropped)))
+}
+
+/// Load every readable image in a folder, in name order
+pub fn load_folder(folder: &Path) -> Result<Vec<Image>, String> {
+    const EXTENSIONS: [&str; 8] = ["png", "jpg", "jpeg", "bmp", "gif", "tif", "tiff", "webp"];
+
+    let mut paths: Vec<_> = std::fs::read_dir(folder)
+        .map_err(|e| format!("Failed to read {}: {}", folder.display(), e))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|e| EXTENSIONS.contains(&e.to_lowercase().as_str()))
+        })
+        .collect();
+    paths.sort();
+
+    if paths.is_empty() {
+        return Err(format!("No images found in {}", folder.display()));
+    }
+    paths.iter().map(Image::load).collect()
+}
+
+/// RAISR: Rapid and Accurate Image Super-Resolution
+///
+/// Enlarges with bicubic, hashes every pixel's local gradient into a bucket,
+/// and sharpens the luma with that bucket's learned filter. Colour follows
+/// the luma correction. Scales other than the bank's are reached by repeated
+/// application and a final bicubic resize.
+///
+/// Time complexity: O(n * patch²)
+/// Space complexity: O(n) plus the filter bank
+pub struct Raisr {
+    bank: FilterBank,
+}
+
+impl Raisr {
+    pub fn new(bank: FilterBank) -> Self {
+        Self { bank }
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        FilterBank::load(path).map(Self::new)
+    }
+
+    /// One pass at the bank's native scale
+    fn upscale_step(&self, image: &FloatImage) -> FloatImage {
+        let s = self.bank.scale;
+        let (w, h) = (image.width * s, image.height * s);
+        let mut result = Psf::Bicubic.resample(image, w, h);
+
+        let luma = Plane::luma(&result);
+        let hashes = gradient_hashes(&luma, self.bank.patch_size);
+        let radius = (self.bank.patch_size / 2) as i32;
+
+        for y in 0..h {
+            for x in 0..w {
+                let filter = self.bank.filter(self.bank.bucket(pixel_type(x, y, s), hashes[y * w + x]));
+                let patch = luma.patch(x as i32 - radius, y as i32 - radius, self.bank.patch_size);
+                let filtered: f32 = filter.iter().zip(&patch).map(|(f, p)| f * p).sum();
+
+                let delta = filtered - luma.get(x, y);
+                let pixel = &mut result.data[y * w + x];
+                for v in pixel.iter_mut() {
+                    *v += delta;
+                }
+            }
+        }
+
+        result.clamp_to_range();
+        result
+    }
+}
+
+impl Upscaler for Raisr {
+    fn upscale(&self, image: &Image, scale_factor: f32) -> Image {
+        let target_w = (image.width as f32 * scale_factor).round() as usize;
+        let target_h = (image.height as f32 * scale_factor).round() as usize;
+
+        let mut current = FloatImage::from_image(image);
+        while current.width < target_w {
+            let grown = self.upscale_step(&current);
+            // A bank that can't enlarge would otherwise loop forever
+            if grown.width <= current.width {
+                break;
+            }
+            current = grown;
+        }
+        if current.width != target_w || current.height != target_h {
+            current = Psf::Bicubic.resample(&current, target_w, target_h);
+        }
+        current.to_image()
+    }
+
+    fn name(&self) -> &str {
+        "RAISR (learned filters)"
+    }
+
+    fn tier(&self) -> UpscaleTier {
+        UpscaleTier::Medium
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::image::Pixel;
+    use crate::algorithms::test_support::rms_error;
+
+    fn create_training_image() -> Image {
+        let mut img = Image::new(32, 32);
+        for y in 0..32 {
+            for x in 0..32 {
+                let val = if (x + 2 * y) % 11 < 5 { 210 } else { 30 };
+                img.set_pixel(x, y, Pixel::new(val, val, (x * 7) as u8));
+            }
+        }
+        img
+    }
+
+    #[test]
+    fn test_filter_bank_round_trip() {
+        let bank = RaisrTrainer::new(2)
+            .with_patch_size(3)
+            .with_bins(4, 2, 2)
+            .train_images(&[create_training_image()])
+            .unwrap();
+
+        let bytes = bank.to_bytes();
+        assert_eq!(&bytes[0..4], MAGIC);
+        assert_eq!(FilterBank::from_bytes(&bytes).unwrap(), bank);
+        assert!(FilterBank::from_bytes(&bytes[..bytes.len() - 2]).is_err());
+
+        // A 1x bank can't enlarge anything
+        let mut one = bytes.clone();
+        one[6] = 1;
+        assert!(FilterBank::from_bytes(&one).is_err());
+        assert!(FilterBank::identity(1, 3).is_err());
+    }
+
+    #[test]
+    fn test_bank_limits_are_checked_at_construction() {
+        // Every header field is a single byte in the file
+        assert!(FilterBank::identity(256, 3).is_err());
+        assert!(FilterBank::identity(2, 256).is_err());
+        assert!(FilterBank::identity(2, 0).is_err());
+
+        let bank = FilterBank::identity(2, 3).unwrap();
+        assert_eq!((bank.scale(), bank.patch_size(), bank.angle_bins()), (2, 3, 1));
+        let result = Raisr::new(bank).upscale(&create_training_image(), 2.0);
+        assert_eq!((result.width, result.height), (64, 64));
+    }
+
+    #[test]
+    fn test_trained_filters_beat_bicubic_on_training_content() {
+        let truth = create_training_image();
+        let bank = RaisrTrainer::new(2).with_patch_size(5).with_bins(8, 2, 2).train_images(std::slice::from_ref(&truth)).unwrap();
+
+        let low = Psf::Bicubic.resample(&FloatImage::from_image(&truth), 16, 16).to_image();
+        let error = |im: &Image| rms_error(im, &truth);
+
+        let raisr = Raisr::new(bank).upscale(&low, 2.0);
+        assert_eq!((raisr.width, raisr.height), (32, 32));
+        assert!(error(&raisr) < error(&crate::fast::Bicubic.upscale(&low, 2.0)));
+
+        let identity = Raisr::new(FilterBank::identity(2, 3).unwrap()).upscale(&low, 3.0);
+        assert_eq!((identity.width, identity.height), (48, 48));
+    }
+}
@@ -0,0 +1,175 @@
+use crate::algorithms::image::Pixel;
+
+/// How output pixels are positioned on the source pixel grid
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GridAlignment {
+    /// Pixel centres line up: `src = (x + 0.5) / scale - 0.5`
+    Center,
+    /// Top-left corners line up: `src = x / scale`
+    Corner,
+    /// Custom sub-pixel phase `p`: `src = (x + p) / scale - p`
+    Phase(f32),
+}
+
+impl GridAlignment {
+    /// Sub-pixel phase of this alignment (0.5 for centre, 0.0 for corner)
+    pub fn phase(&self) -> f32 {
+        match self {
+            GridAlignment::Center => 0.5,
+            GridAlignment::Corner => 0.0,
+            GridAlignment::Phase(p) => *p,
+        }
+    }
+
+    /// Map an output coordinate to a continuous source coordinate
+    pub fn map(&self, x: usize, scale_factor: f32) -> f32 {
+        let p = self.phase();
+        (x as f32 + p) / scale_factor - p
+    }
+
+    /// Map an output coordinate to the index of the source pixel nearest to `map`
+    pub fn map_nearest(&self, x: usize, scale_factor: f32) -> i32 {
+        (self.map(x, scale_factor) + 0.5).floor() as i32
+    }
+
+    /// Parse `center`, `corner` or a numeric phase such as `0.25`
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "center" | "centre" => Some(GridAlignment::Center),
+            "corner" => Some(GridAlignment::Corner),
+            other => other.parse::<f32>().ok().map(GridAlignment::Phase),
+        }
+    }
+}
+
+/// How samples outside the source image are resolved
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EdgeMode {
+    /// Repeat the border pixel
+    Clamp,
+    /// Reflect at the border (`-1` reads `0`, `-2` reads `1`)
+    Mirror,
+    /// Tile the image, for seamless textures
+    Wrap,
+    /// Use a fixed colour outside the image
+    Constant(Pixel),
+}
+
+impl EdgeMode {
+    /// Resolve a possibly out-of-range index, or `None` for a constant border
+    /// or an empty axis
+    pub fn resolve(&self, i: i32, len: usize) -> Option<usize> {
+        if len == 0 {
+            return None;
+        }
+        let len = len as i32;
+        if (0..len).contains(&i) {
+            return Some(i as usize);
+        }
+
+        match self {
+            EdgeMode::Clamp => Some(i.clamp(0, len - 1) as usize),
+            EdgeMode::Mirror => {
+                let period = 2 * len;
+                let m = i.rem_euclid(period);
+                Some(if m < len { m } else { period - 1 - m } as usize)
+            }
+            EdgeMode::Wrap => Some(i.rem_euclid(len) as usize),
+            EdgeMode::Constant(_) => None,
+        }
+    }
+
+    /// Parse `clamp`, `mirror`, `wrap` or `constant:R,G,B`
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.to_lowercase();
+        match s.as_str() {
+            "clamp" => Some(EdgeMode::Clamp),
+            "mirror" | "reflect" => Some(EdgeMode::Mirror),
+            "wrap" | "tile" => Some(EdgeMode::Wrap),
+            "constant" => Some(EdgeMode::Constant(Pixel::black())),
+            other => {
+                let rgb = other.strip_prefix("constant:")?;
+                let parts: Vec<u8> = rgb.split(',').filter_map(|v| v.trim().parse().ok()).collect();
+                match parts.as_slice() {
+                    [r, g, b] => Some(EdgeMode::Constant(Pixel::new(*r, *g, *b))),
+                    _ => None,
+                }
+            }
+        }
+    }
+}
+
+/// Pixel-grid alignment and boundary handling shared by all upscalers
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SamplingConfig {
+    pub alignment: GridAlignment,
+    pub edge_mode: EdgeMode,
+}
+
+impl SamplingConfig {
+    /// Centre-aligned grid with clamped borders
+    pub fn new() -> Self {
+        Self {
+            alignment: GridAlignment::Center,
+            edge_mode: EdgeMode::Clamp,
+        }
+    }
+
+    pub fn with_alignment(mut self, alignment: GridAlignment) -> Self {
+        self.alignment = alignment;
+        self
+    }
+
+    pub fn with_edge_mode(mut self, edge_mode: EdgeMode) -> Self {
+        self.edge_mode = edge_mode;
+        self
+    }
+}
+
+impl Default for SamplingConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::image::Image;
+
+    #[test]
+    fn test_edge_modes() {
+        assert_eq!(EdgeMode::Clamp.resolve(-2, 4), Some(0));
+        assert_eq!(EdgeMode::Clamp.resolve(5, 4), Some(3));
+        assert_eq!(EdgeMode::Mirror.resolve(-1, 4), Some(0));
+        assert_eq!(EdgeMode::Mirror.resolve(-2, 4), Some(1));
+        assert_eq!(EdgeMode::Mirror.resolve(4, 4), Some(3));
+        assert_eq!(EdgeMode::Wrap.resolve(-1, 4), Some(3));
+        assert_eq!(EdgeMode::Wrap.resolve(5, 4), Some(1));
+        assert_eq!(EdgeMode::Constant(Pixel::white()).resolve(-1, 4), None);
+        assert_eq!(EdgeMode::Constant(Pixel::white()).resolve(2, 4), Some(2));
+        assert_eq!(EdgeMode::Wrap.resolve(0, 0), None);
+        assert_eq!(EdgeMode::Clamp.resolve(-1, 0), None);
+
+        // An empty image has nothing to clamp, mirror or wrap to
+        let empty = Image::new(0, 0);
+        for mode in [EdgeMode::Clamp, EdgeMode::Mirror, EdgeMode::Wrap] {
+            assert_eq!(empty.get_pixel_edge(1, -1, mode), Pixel::black());
+        }
+        assert_eq!(empty.get_pixel_edge(0, 0, EdgeMode::Constant(Pixel::white())), Pixel::white());
+    }
+
+    #[test]
+    fn test_alignment_mapping() {
+        assert_eq!(GridAlignment::Corner.map(2, 2.0), 1.0);
+        assert_eq!(GridAlignment::Center.map(1, 2.0), 0.25);
+        // Nearest rounds `map`, so it picks the pixels bilinear weights most
+        let nearest: Vec<i32> = (0..7).map(|x| GridAlignment::Phase(0.25).map_nearest(x, 3.0)).collect();
+        assert_eq!(nearest, vec![0, 0, 1, 1, 1, 2, 2]);
+        assert_eq!(GridAlignment::Phase(0.5), GridAlignment::parse("0.5").unwrap());
+        assert_eq!(
+            EdgeMode::parse("constant:1,2,3"),
+            Some(EdgeMode::Constant(Pixel::new(1, 2, 3)))
+        );
+    }
+}
@@ -1,17 +1,134 @@
-use crate::algorithms::image::{Image, Pixel};
+use crate::algorithms::degradation::Psf;
+use crate::algorithms::image::{FloatImage, Image};
 use crate::algorithms::upscaler::{Upscaler, UpscaleTier};
 
+/// Coarse-to-fine schedule for the iterative reconstruction algorithms
+///
+/// Each stage upscales the previous stage's estimate to a larger intermediate
+/// factor and refines it against the original input, so the full-resolution
+/// iterations start from an already consistent guess.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MultiScaleSchedule {
+    /// 2x, 4x, 8x, ... up to the final factor, with the same iterations per stage
+    Doubling { iterations: usize },
+    /// Explicit `(cumulative scale, iterations)` stages; the final factor is always the last
+    Stages(Vec<(f32, usize)>),
+}
+
+impl MultiScaleSchedule {
+    /// Resolve the stages for a final `scale_factor`
+    pub fn plan(&self, scale_factor: f32) -> Vec<(f32, usize)> {
+        const EPSILON: f32 = 1e-3;
+        match self {
+            MultiScaleSchedule::Doubling { iterations } => {
+                let mut stages = Vec::new();
+                let mut scale = 2.0;
+                while scale < scale_factor - EPSILON {
+                    stages.push((scale, *iterations));
+                    scale *= 2.0;
+                }
+                stages.push((scale_factor, *iterations));
+                stages
+            }
+            MultiScaleSchedule::Stages(given) => {
+                let mut given = given.clone();
+                given.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+                // The final stage takes the iterations of the first stage at or past it
+                let final_iterations = given.iter()
+                    .find(|(scale, _)| *scale >= scale_factor - EPSILON)
+                    .or(given.last())
+                    .map_or(10, |&(_, iterations)| iterations);
+
+                let mut stages: Vec<(f32, usize)> = given.into_iter()
+                    .filter(|(scale, _)| *scale > 1.0 && *scale < scale_factor - EPSILON)
+                    .collect();
+                stages.push((scale_factor, final_iterations));
+                stages
+            }
+        }
+    }
+
+    /// Parse `auto`, `auto:ITERS` (doubling) or `SCALE:ITERS,SCALE:ITERS,...`
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.to_lowercase();
+        if s == "auto" {
+            return Some(MultiScaleSchedule::Doubling { iterations: 10 });
+        }
+        if let Some(iterations) = s.strip_prefix("auto:") {
+            return Some(MultiScaleSchedule::Doubling { iterations: iterations.parse().ok()? });
+        }
+
+        let stages = s.split(',')
+            .map(|stage| {
+                let (scale, iterations) = stage.split_once(':')?;
+                Some((scale.trim().parse::<f32>().ok()?, iterations.trim().parse::<usize>().ok()?))
+            })
+            .collect::<Option<Vec<_>>>()?;
+        (!stages.is_empty()).then_some(MultiScaleSchedule::Stages(stages))
+    }
+}
+
+/// Stage sizes and iterations, or a single full-resolution stage without a schedule
+fn plan_stages(
+    schedule: &Option<MultiScaleSchedule>,
+    image: &Image,
+    scale_factor: f32,
+    iterations: usize,
+) -> Vec<(f32, usize, usize, usize)> {
+    let stages = match schedule {
+        Some(schedule) => schedule.plan(scale_factor),
+        None => vec![(scale_factor, iterations)],
+    };
+    stages.into_iter()
+        .map(|(scale, iterations)| {
+            let width = (image.width as f32 * scale).round() as usize;
+            let height = (image.height as f32 * scale).round() as usize;
+            (scale, width, height, iterations)
+        })
+        .collect()
+}
+
+/// Initial estimate for a stage: bicubic from the input for the first stage,
+/// otherwise the previous stage's f32 estimate enlarged without quantising
+fn seed_stage(image: &Image, previous: Option<&FloatImage>, scale: f32, width: usize, height: usize) -> FloatImage {
+    match previous {
+        Some(previous) => Psf::Bicubic.resample(previous, width, height),
+        None => FloatImage::from_image(&crate::fast::Bicubic.upscale(image, scale)),
+    }
+}
+
 /// Iterative Back-Projection (IBP)
 ///
 /// Iteratively refines the upscaled image by minimizing reconstruction error.
-/// Simulates the downscaling process and adjusts the upscaled image to minimize
-/// the difference between the simulated downscale and the original.
+/// Each iteration degrades the current estimate with the imaging model (PSF
+/// blur plus decimation), compares it with the original, and interpolates the
+/// residual back onto the high-resolution grid with the back-projection kernel.
+/// The estimate and residuals stay in f32, so large errors are never clipped.
 ///
-/// Time complexity: O(n² * iterations) due to iterative refinement
-/// Space complexity: O(n) for temporary buffers
+/// Time complexity: O(n * k * iterations) for kernel support k
+/// Space complexity: O(n) for the f32 estimate and residual buffers
 pub struct IterativeBackProjection {
     iterations: usize,
     learning_rate: f32,
+    psf: Psf,
+    back_projection: Psf,
+    tolerance: f32,
+    schedule: Option<MultiScaleSchedule>,
+}
+
+/// Output of a back-projection run, with the residual of every iteration
+pub struct BackProjectionResult {
+    pub image: Image,
+    /// RMSE between the degraded estimate and the input, in 8-bit levels
+    pub residuals: Vec<f32>,
+}
+
+impl BackProjectionResult {
+    /// Residuals formatted as `12.34 -> 3.21 -> ...`
+    pub fn residual_report(&self) -> String {
+        self.residuals.iter().map(|r| format!("{:.2}", r)).collect::<Vec<_>>().join(" -> ")
+    }
 }
 
 impl IterativeBackProjection {
@@ -20,6 +137,10 @@ impl IterativeBackProjection {
         Self {
             iterations: 10,
             learning_rate: 0.5,
+            psf: Psf::Bicubic,
+            back_projection: Psf::Bicubic,
+            tolerance: 0.5,
+            schedule: None,
         }
     }
 
@@ -27,7 +148,7 @@ impl IterativeBackProjection {
     pub fn fast() -> Self {
         Self {
             iterations: 5,
-            learning_rate: 0.5,
+            ..Self::new()
         }
     }
 
@@ -36,133 +157,95 @@ impl IterativeBackProjection {
         Self {
             iterations: 20,
             learning_rate: 0.3,
+            tolerance: 0.25,
+            ..Self::new()
         }
     }
 
-    /// Simulate downsampling (simple averaging)
-    fn simulate_downsample(image: &Image, target_width: usize, target_height: usize) -> Image {
-        let mut result = Image::new(target_width, target_height);
-        let scale_x = image.width as f32 / target_width as f32;
-        let scale_y = image.height as f32 / target_height as f32;
-
-        for y in 0..target_height {
-            for x in 0..target_width {
-                // Average pixels in the source region
-                let src_x_start = (x as f32 * scale_x) as usize;
-                let src_y_start = (y as f32 * scale_y) as usize;
-                let src_x_end = ((x + 1) as f32 * scale_x).min(image.width as f32) as usize;
-                let src_y_end = ((y + 1) as f32 * scale_y).min(image.height as f32) as usize;
-
-                let mut r_sum = 0.0;
-                let mut g_sum = 0.0;
-                let mut b_sum = 0.0;
-                let mut count = 0;
-
-                for sy in src_y_start..src_y_end {
-                    for sx in src_x_start..src_x_end {
-                        if let Some(px) = image.get_pixel(sx, sy) {
-                            r_sum += px.r as f32;
-                            g_sum += px.g as f32;
-                            b_sum += px.b as f32;
-                            count += 1;
-                        }
-                    }
-                }
+    /// Look up a preset by its CLI name (`ibp-fast`, `ibp`, `ibp-quality`)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "ibp-fast" => Some(Self::fast()),
+            "ibp" | "ibp-standard" | "back_projection" => Some(Self::new()),
+            "ibp-quality" => Some(Self::quality()),
+            _ => None,
+        }
+    }
+
+    pub fn with_iterations(mut self, iterations: usize) -> Self {
+        self.iterations = iterations;
+        self
+    }
+
+    pub fn with_learning_rate(mut self, learning_rate: f32) -> Self {
+        self.learning_rate = learning_rate;
+        self
+    }
+
+    /// PSF used to simulate the low-resolution capture
+    pub fn with_psf(mut self, psf: Psf) -> Self {
+        self.psf = psf;
+        self
+    }
+
+    /// Kernel used to spread the residual back onto the high-resolution grid
+    pub fn with_back_projection(mut self, kernel: Psf) -> Self {
+        self.back_projection = kernel;
+        self
+    }
 
-                if count > 0 {
-                    let avg_pixel = Pixel::new(
-                        (r_sum / count as f32) as u8,
-                        (g_sum / count as f32) as u8,
-                        (b_sum / count as f32) as u8,
-                    );
-                    result.set_pixel(x, y, avg_pixel);
+    /// Stop once the residual RMSE drops to this level (0 runs every iteration)
+    pub fn with_tolerance(mut self, tolerance: f32) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Refine coarse-to-fine; the schedule's per-stage iterations replace `iterations`
+    pub fn with_schedule(mut self, schedule: MultiScaleSchedule) -> Self {
+        self.schedule = Some(schedule);
+        self
+    }
+
+    /// Upscale and report the residual RMSE of each iteration
+    ///
+    /// With a multi-scale schedule the residuals of all stages are reported in
+    /// order; each is measured against the original input.
+    pub fn reconstruct(&self, image: &Image, scale_factor: f32) -> BackProjectionResult {
+        let target = FloatImage::from_image(image);
+        let mut residuals = Vec::new();
+        let mut estimate: Option<FloatImage> = None;
+
+        for (scale, width, height, iterations) in plan_stages(&self.schedule, image, scale_factor, self.iterations) {
+            let mut current = seed_stage(image, estimate.as_ref(), scale, width, height);
+
+            for _iter in 0..iterations {
+                let simulated = self.psf.resample(&current, image.width, image.height);
+                let residual = target.difference(&simulated);
+
+                let rmse = residual.rms();
+                residuals.push(rmse);
+                if rmse <= self.tolerance {
+                    break;
                 }
+
+                let correction = self.back_projection.resample(&residual, width, height);
+                current.add_scaled(&correction, self.learning_rate);
+                current.clamp_to_range();
             }
+
+            estimate = Some(current);
         }
 
-        result
-    }
-
-    /// Calculate error between two images
-    fn calculate_error(a: &Pixel, b: &Pixel) -> (f32, f32, f32) {
-        (
-            a.r as f32 - b.r as f32,
-            a.g as f32 - b.g as f32,
-            a.b as f32 - b.b as f32,
-        )
-    }
-
-    /// Back-project error to high-resolution image
-    fn back_project(
-        high_res: &mut Image,
-        low_res_error: &Image,
-        scale_factor: f32,
-        learning_rate: f32,
-    ) {
-        for y in 0..high_res.height {
-            for x in 0..high_res.width {
-                let src_x = (x as f32 / scale_factor) as usize;
-                let src_y = (y as f32 / scale_factor) as usize;
-
-                if let Some(error_pixel) = low_res_error.get_pixel(src_x, src_y) {
-                    if let Some(current) = high_res.get_pixel(x, y) {
-                        let new_r = (current.r as f32
-                            + error_pixel.r as f32 * learning_rate)
-                            .clamp(0.0, 255.0) as u8;
-                        let new_g = (current.g as f32
-                            + error_pixel.g as f32 * learning_rate)
-                            .clamp(0.0, 255.0) as u8;
-                        let new_b = (current.b as f32
-                            + error_pixel.b as f32 * learning_rate)
-                            .clamp(0.0, 255.0) as u8;
-
-                        high_res.set_pixel(x, y, Pixel::new(new_r, new_g, new_b));
-                    }
-                }
-            }
+        BackProjectionResult {
+            image: estimate.map_or_else(|| image.clone(), |e| e.to_image()),
+            residuals,
         }
     }
 }
 
 impl Upscaler for IterativeBackProjection {
     fn upscale(&self, image: &Image, scale_factor: f32) -> Image {
-        let _new_width = (image.width as f32 * scale_factor).round() as usize;
-        let _new_height = (image.height as f32 * scale_factor).round() as usize;
-
-        // Start with bilinear upscale as initial estimate
-        let mut result = crate::instant::Bilinear.upscale(image, scale_factor);
-
-        // Iterative refinement
-        for _iter in 0..self.iterations {
-            // Simulate downsampling the current high-res image
-            let simulated_low = Self::simulate_downsample(&result, image.width, image.height);
-
-            // Calculate error between simulated and original
-            let mut error_image = Image::new(image.width, image.height);
-            for y in 0..image.height {
-                for x in 0..image.width {
-                    let original = image.get_pixel(x, y).unwrap();
-                    let simulated = simulated_low.get_pixel(x, y).unwrap();
-
-                    let (er, eg, eb) = Self::calculate_error(&original, &simulated);
-
-                    error_image.set_pixel(
-                        x,
-                        y,
-                        Pixel::new(
-                            (er + 128.0).clamp(0.0, 255.0) as u8,
-                            (eg + 128.0).clamp(0.0, 255.0) as u8,
-                            (eb + 128.0).clamp(0.0, 255.0) as u8,
-                        ),
-                    );
-                }
-            }
-
-            // Back-project error to high-resolution image
-            Self::back_project(&mut result, &error_image, scale_factor, self.learning_rate);
-        }
-
-        result
+        self.reconstruct(image, scale_factor).image
     }
 
     fn name(&self) -> &str {
@@ -182,108 +265,211 @@ impl Default for IterativeBackProjection {
 
 /// Total Variation (TV) Regularization Upscaling
 ///
-/// Uses edge-preserving smoothing to reduce artifacts while maintaining sharpness.
-/// Minimizes total variation (sum of gradient magnitudes) while fitting the data.
+/// Solves `min_u  lambda * TV(u) + 1/2 * ||A u - f||^2` with the Chambolle-Pock
+/// primal-dual algorithm, where `A` is the PSF blur-and-decimate operator and
+/// `f` the input. The data term ties the result to the low-resolution image,
+/// while the (colour-coupled) TV term keeps edges sharp and flat areas clean.
+/// Pixel values are scaled to 0..1 and kept in f32 throughout.
 ///
-/// Time complexity: O(n * iterations)
-/// Space complexity: O(n) for gradient buffers
+/// Time complexity: O(n * k * iterations) for PSF support k
+/// Space complexity: O(n) for the primal and dual buffers
 pub struct TotalVariation {
     iterations: usize,
     lambda: f32, // Regularization strength
+    step: f32,
+    tolerance: f32,
+    psf: Psf,
+    schedule: Option<MultiScaleSchedule>,
 }
 
+/// Squared norm bound of the stacked operator [gradient; A]: 8 for the
+/// forward-difference gradient plus 1 for the normalised degradation
+const TV_OPERATOR_NORM_SQ: f32 = 9.0;
+
 impl TotalVariation {
     pub fn new() -> Self {
         Self {
-            iterations: 15,
-            lambda: 0.1,
+            iterations: 200,
+            lambda: 0.003,
+            step: 0.25,
+            tolerance: 1e-4,
+            psf: Psf::Bicubic,
+            schedule: None,
         }
     }
-    
-    /// Calculate total variation at a pixel
-    fn calculate_tv_gradient(image: &Image, x: usize, y: usize) -> (f32, f32, f32) {
-        let center = image.get_pixel(x, y).unwrap();
 
-        let right = image.get_pixel_clamped(x as i32 + 1, y as i32);
-        let bottom = image.get_pixel_clamped(x as i32, y as i32 + 1);
+    /// Look up by registry name (`tv`, `total_variation`)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "tv" | "total_variation" => Some(Self::new()),
+            _ => None,
+        }
+    }
 
-        let grad_x_r = right.r as f32 - center.r as f32;
-        let grad_x_g = right.g as f32 - center.g as f32;
-        let grad_x_b = right.b as f32 - center.b as f32;
+    /// Maximum number of primal-dual iterations
+    pub fn with_iterations(mut self, iterations: usize) -> Self {
+        self.iterations = iterations;
+        self
+    }
 
-        let grad_y_r = bottom.r as f32 - center.r as f32;
-        let grad_y_g = bottom.g as f32 - center.g as f32;
-        let grad_y_b = bottom.b as f32 - center.b as f32;
+    /// Weight of the TV term relative to data consistency
+    pub fn with_lambda(mut self, lambda: f32) -> Self {
+        self.lambda = lambda;
+        self
+    }
 
-        let tv_r = (grad_x_r.powi(2) + grad_y_r.powi(2)).sqrt();
-        let tv_g = (grad_x_g.powi(2) + grad_y_g.powi(2)).sqrt();
-        let tv_b = (grad_x_b.powi(2) + grad_y_b.powi(2)).sqrt();
+    /// Primal step size `tau`; the dual step is chosen so `tau * sigma * L^2 = 1`
+    pub fn with_step(mut self, step: f32) -> Self {
+        self.step = step;
+        self
+    }
 
-        (tv_r, tv_g, tv_b)
+    /// Stop once the relative change of the estimate per iteration drops below this
+    pub fn with_tolerance(mut self, tolerance: f32) -> Self {
+        self.tolerance = tolerance;
+        self
     }
 
-    /// Apply one iteration of TV regularization
-    fn tv_iteration(image: &mut Image, lambda: f32) {
-        let mut updates = Vec::new();
+    /// PSF of the degradation operator `A`
+    pub fn with_psf(mut self, psf: Psf) -> Self {
+        self.psf = psf;
+        self
+    }
 
-        for y in 0..image.height {
-            for x in 0..image.width {
-                let (tv_r, tv_g, tv_b) = Self::calculate_tv_gradient(image, x, y);
+    /// Solve coarse-to-fine; the schedule's per-stage iterations replace `iterations`
+    pub fn with_schedule(mut self, schedule: MultiScaleSchedule) -> Self {
+        self.schedule = Some(schedule);
+        self
+    }
 
-                // Get neighboring pixels for smoothing
-                let neighbors = [
-                    image.get_pixel_clamped(x as i32 - 1, y as i32),
-                    image.get_pixel_clamped(x as i32 + 1, y as i32),
-                    image.get_pixel_clamped(x as i32, y as i32 - 1),
-                    image.get_pixel_clamped(x as i32, y as i32 + 1),
-                ];
+    /// Forward-difference gradient with Neumann boundaries
+    fn gradient(u: &FloatImage, x: usize, y: usize) -> ([f32; 3], [f32; 3]) {
+        let center = u.get(x, y);
+        let right = if x + 1 < u.width { u.get(x + 1, y) } else { center };
+        let bottom = if y + 1 < u.height { u.get(x, y + 1) } else { center };
+
+        let mut gx = [0.0; 3];
+        let mut gy = [0.0; 3];
+        for c in 0..3 {
+            gx[c] = right[c] - center[c];
+            gy[c] = bottom[c] - center[c];
+        }
+        (gx, gy)
+    }
 
-                let center = image.get_pixel(x, y).unwrap();
+    /// Divergence, the negative adjoint of `gradient`
+    fn divergence(px: &FloatImage, py: &FloatImage, x: usize, y: usize) -> [f32; 3] {
+        let mut div = [0.0; 3];
+        for (c, d) in div.iter_mut().enumerate() {
+            if x + 1 < px.width {
+                *d += px.get(x, y)[c];
+            }
+            if x > 0 {
+                *d -= px.get(x - 1, y)[c];
+            }
+            if y + 1 < py.height {
+                *d += py.get(x, y)[c];
+            }
+            if y > 0 {
+                *d -= py.get(x, y - 1)[c];
+            }
+        }
+        div
+    }
 
-                // Weighted average with TV-based weights
-                let total_tv = tv_r + tv_g + tv_b + 1e-6;
-                let weight = lambda / total_tv;
+    /// Run the primal-dual solver from an initial high-resolution estimate
+    fn solve(&self, target: &FloatImage, mut u: FloatImage, iterations: usize) -> FloatImage {
+        let (width, height) = (u.width, u.height);
+        let tau = self.step;
+        let sigma = 1.0 / (tau * TV_OPERATOR_NORM_SQ);
+
+        let mut u_bar = u.clone();
+        let mut px = FloatImage::new(width, height);
+        let mut py = FloatImage::new(width, height);
+        let mut q = FloatImage::new(target.width, target.height);
+
+        for _ in 0..iterations {
+            // Dual ascent on the TV term: project onto the colour-coupled lambda-ball
+            for y in 0..height {
+                for x in 0..width {
+                    let (gx, gy) = Self::gradient(&u_bar, x, y);
+                    let mut nx = px.get(x, y);
+                    let mut ny = py.get(x, y);
+                    for c in 0..3 {
+                        nx[c] += sigma * gx[c];
+                        ny[c] += sigma * gy[c];
+                    }
+                    let norm = (0..3).map(|c| nx[c] * nx[c] + ny[c] * ny[c]).sum::<f32>().sqrt();
+                    let shrink = (norm / self.lambda).max(1.0);
+                    for c in 0..3 {
+                        nx[c] /= shrink;
+                        ny[c] /= shrink;
+                    }
+                    px.set(x, y, nx);
+                    py.set(x, y, ny);
+                }
+            }
 
-                let mut new_r = center.r as f32;
-                let mut new_g = center.g as f32;
-                let mut new_b = center.b as f32;
+            // Dual ascent on the data term: prox of 1/2 ||. - f||^2
+            let residual = self.psf.resample(&u_bar, target.width, target.height).difference(target);
+            q.add_scaled(&residual, sigma);
+            for v in q.data.iter_mut().flatten() {
+                *v /= 1.0 + sigma;
+            }
 
-                for neighbor in &neighbors {
-                    new_r += weight * (neighbor.r as f32 - center.r as f32);
-                    new_g += weight * (neighbor.g as f32 - center.g as f32);
-                    new_b += weight * (neighbor.b as f32 - center.b as f32);
+            // Primal descent, projected onto the valid intensity range
+            let data_grad = self.psf.resample_adjoint(&q, width, height);
+            let previous = u.clone();
+            for y in 0..height {
+                for x in 0..width {
+                    let div = Self::divergence(&px, &py, x, y);
+                    let dg = data_grad.get(x, y);
+                    let mut value = u.get(x, y);
+                    for c in 0..3 {
+                        value[c] = (value[c] + tau * (div[c] - dg[c])).clamp(0.0, 1.0);
+                    }
+                    u.set(x, y, value);
                 }
+            }
+
+            // Over-relaxation
+            let change = u.difference(&previous);
+            u_bar = u.clone();
+            u_bar.add_scaled(&change, 1.0);
 
-                updates.push((
-                    x,
-                    y,
-                    Pixel::new(
-                        new_r.clamp(0.0, 255.0) as u8,
-                        new_g.clamp(0.0, 255.0) as u8,
-                        new_b.clamp(0.0, 255.0) as u8,
-                    ),
-                ));
+            let relative = change.rms() / u.rms().max(1e-6);
+            if relative < self.tolerance {
+                break;
             }
         }
 
-        // Apply updates
-        for (x, y, pixel) in updates {
-            image.set_pixel(x, y, pixel);
-        }
+        u
     }
 }
 
 impl Upscaler for TotalVariation {
     fn upscale(&self, image: &Image, scale_factor: f32) -> Image {
-        // Start with bicubic as initial estimate
-        let mut result = crate::fast::Bicubic.upscale(image, scale_factor);
+        let mut target = FloatImage::from_image(image);
+        for v in target.data.iter_mut().flatten() {
+            *v /= 255.0;
+        }
+
+        // Stages are seeded in 0..255 and solved in 0..1
+        let mut estimate: Option<FloatImage> = None;
+        for (scale, width, height, iterations) in plan_stages(&self.schedule, image, scale_factor, self.iterations) {
+            let mut u = seed_stage(image, estimate.as_ref(), scale, width, height);
+            for v in u.data.iter_mut().flatten() {
+                *v = (*v / 255.0).clamp(0.0, 1.0);
+            }
 
-        // Apply TV regularization
-        for _ in 0..self.iterations {
-            Self::tv_iteration(&mut result, self.lambda);
+            let mut solved = self.solve(&target, u, iterations);
+            for v in solved.data.iter_mut().flatten() {
+                *v *= 255.0;
+            }
+            estimate = Some(solved);
         }
 
-        result
+        estimate.map_or_else(|| image.clone(), |e| e.to_image())
     }
 
     fn name(&self) -> &str {
@@ -304,6 +490,7 @@ impl Default for TotalVariation {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::algorithms::image::Pixel;
 
     fn create_test_image() -> Image {
         let mut img = Image::new(4, 4);
@@ -326,6 +513,47 @@ mod tests {
         assert_eq!(result.height, 8);
     }
 
+    #[test]
+    fn test_ibp_converges_to_the_degradation_model() {
+        // Capture a hard black/white edge through a Gaussian PSF
+        let mut high = FloatImage::new(12, 12);
+        for y in 0..12 {
+            for x in 5..12 {
+                high.set(x, y, [255.0; 3]);
+            }
+        }
+        let psf = Psf::Gaussian(0.6);
+        let img = psf.resample(&high, 6, 6).to_image();
+
+        let result = IterativeBackProjection::new()
+            .with_psf(psf)
+            .with_learning_rate(1.0)
+            .with_tolerance(0.0)
+            .reconstruct(&img, 2.0);
+
+        assert_eq!(result.residuals.len(), 10);
+        assert!(result.residuals.last().unwrap() < &(result.residuals[0] * 0.5));
+
+        let early = IterativeBackProjection::new().with_tolerance(1000.0).reconstruct(&img, 2.0);
+        assert_eq!(early.residuals.len(), 1);
+    }
+
+    #[test]
+    fn test_multiscale_schedule() {
+        let doubling = MultiScaleSchedule::Doubling { iterations: 3 };
+        assert_eq!(doubling.plan(15.0), vec![(2.0, 3), (4.0, 3), (8.0, 3), (15.0, 3)]);
+        assert_eq!(doubling.plan(2.0), vec![(2.0, 3)]);
+
+        let stages = MultiScaleSchedule::parse("4:5,2:8,8:2").unwrap();
+        assert_eq!(stages.plan(6.0), vec![(2.0, 8), (4.0, 5), (6.0, 2)]);
+
+        let img = create_test_image();
+        let ibp = IterativeBackProjection::new().with_schedule(doubling.clone()).reconstruct(&img, 6.0);
+        assert_eq!((ibp.image.width, ibp.image.height), (24, 24));
+        let tv = TotalVariation::new().with_schedule(doubling).upscale(&img, 6.0);
+        assert_eq!((tv.width, tv.height), (24, 24));
+    }
+
     #[test]
     fn test_tv() {
         let img = create_test_image();
@@ -335,4 +563,24 @@ mod tests {
         assert_eq!(result.width, 8);
         assert_eq!(result.height, 8);
     }
+
+    #[test]
+    fn test_tv_is_consistent_with_the_input() {
+        let mut img = Image::new(8, 8);
+        for y in 0..8 {
+            for x in 0..8 {
+                let val = if x + y > 7 { 220 } else { 30 };
+                img.set_pixel(x, y, Pixel::new(val, val / 2, 90));
+            }
+        }
+
+        let residual = |result: &Image| {
+            let degraded = Psf::Bicubic.resample(&FloatImage::from_image(result), 8, 8);
+            FloatImage::from_image(&img).difference(&degraded).rms()
+        };
+
+        let tv = TotalVariation::new().upscale(&img, 3.0);
+        let bicubic = crate::fast::Bicubic.upscale(&img, 3.0);
+        assert!(residual(&tv) < residual(&bicubic) * 0.75);
+    }
 }
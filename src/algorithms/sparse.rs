@@ -0,0 +1,572 @@
+use std::path::Path;
+
+use crate::algorithms::degradation::Psf;
+use crate::algorithms::image::{FloatImage, Image};
+use crate::algorithms::linalg::{cholesky_solve, ridge_solve};
+use crate::algorithms::patch::{patch_origins, Plane};
+use crate::algorithms::raisr::{load_folder, training_pair};
+use crate::algorithms::upscaler::{Upscaler, UpscaleTier};
+
+/// File signature of a serialised dictionary
+const MAGIC: &[u8; 4] = b"SCDC";
+const VERSION: u16 = 1;
+
+/// Patches with a smaller feature norm are flat and keep the bicubic base
+const MIN_FEATURE_NORM: f32 = 1.0;
+
+/// Coupled low/high-resolution patch dictionary
+///
+/// Low-resolution atoms describe a `patch_size` x `patch_size` window of a
+/// bicubic base by its first and second derivatives in x and y (four planes,
+/// unit norm). High-resolution atoms are the matching detail patch: the
+/// original minus the mean of the base window, in the same norm units.
+///
+/// The file format is little-endian: the 4-byte magic `SCDC`, a u16 version,
+/// u8 scale and patch size, a u32 atom count, then all low-resolution atoms
+/// followed by all high-resolution atoms as f32 values, atom by atom.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dictionary {
+    pub scale: usize,
+    pub patch_size: usize,
+    atoms: usize,
+    low: Vec<f32>,
+    high: Vec<f32>,
+}
+
+impl Dictionary {
+    pub fn atoms(&self) -> usize {
+        self.atoms
+    }
+
+    fn high_dim(&self) -> usize {
+        self.patch_size * self.patch_size
+    }
+
+    fn high_atom(&self, atom: usize) -> &[f32] {
+        let dim = self.high_dim();
+        &self.high[atom * dim..(atom + 1) * dim]
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&VERSION.to_le_bytes());
+        bytes.push(self.scale as u8);
+        bytes.push(self.patch_size as u8);
+        bytes.extend_from_slice(&(self.atoms as u32).to_le_bytes());
+        for v in self.low.iter().chain(&self.high) {
+            bytes.extend_from_slice(&v.to_le_bytes());
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        if bytes.len() < 12 || &bytes[0..4] != MAGIC {
+            return Err("Not a sparse-coding dictionary".to_string());
+        }
+        let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+        if version != VERSION {
+            return Err(format!("Unsupported dictionary version {}", version));
+        }
+
+        let (scale, patch_size) = (bytes[6] as usize, bytes[7] as usize);
+        let atoms = u32::from_le_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]) as usize;
+        if scale == 0 || patch_size == 0 || atoms == 0 {
+            return Err("Dictionary header has a zero dimension".to_string());
+        }
+
+        let floats: Vec<f32> = bytes[12..]
+            .chunks_exact(4)
+            .map(|c| f32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+            .collect();
+        let low_len = atoms * 4 * patch_size * patch_size;
+        let expected = low_len + atoms * patch_size * patch_size;
+        if floats.len() != expected || !(bytes.len() - 12).is_multiple_of(4) {
+            return Err(format!("Dictionary is truncated: expected {} values, found {}", expected, floats.len()));
+        }
+
+        Ok(Self {
+            scale,
+            patch_size,
+            atoms,
+            low: floats[..low_len].to_vec(),
+            high: floats[low_len..].to_vec(),
+        })
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), String> {
+        std::fs::write(path, self.to_bytes()).map_err(|e| format!("Failed to save dictionary: {}", e))
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let bytes = std::fs::read(path).map_err(|e| format!("Failed to read dictionary: {}", e))?;
+        Self::from_bytes(&bytes)
+    }
+}
+
+/// Orthogonal matching pursuit
+///
+/// Greedily picks up to `sparsity` atoms (unit-norm rows of `dictionary`,
+/// each `signal.len()` long) and least-squares fits their coefficients,
+/// stopping early once the residual norm drops below `tolerance`. Returns
+/// `(atom, coefficient)` pairs.
+pub fn orthogonal_matching_pursuit(dictionary: &[f32], signal: &[f32], sparsity: usize, tolerance: f32) -> Vec<(usize, f32)> {
+    let dim = signal.len();
+    let atoms = dictionary.len() / dim.max(1);
+    let atom = |i: usize| &dictionary[i * dim..(i + 1) * dim];
+    let dot = |a: &[f32], b: &[f32]| a.iter().zip(b).map(|(x, y)| x * y).sum::<f32>();
+
+    let mut selected: Vec<usize> = Vec::new();
+    let mut coefficients: Vec<f32> = Vec::new();
+    let mut residual = signal.to_vec();
+
+    while selected.len() < sparsity.min(atoms) && dot(&residual, &residual).sqrt() > tolerance {
+        let best = (0..atoms)
+            .filter(|i| !selected.contains(i))
+            .map(|i| (i, dot(atom(i), &residual).abs()))
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        let Some((best, correlation)) = best else { break };
+        if correlation <= f32::EPSILON {
+            break;
+        }
+        selected.push(best);
+
+        // Refit every selected coefficient against the signal
+        let k = selected.len();
+        let mut gram = vec![0.0f64; k * k];
+        for (i, &a) in selected.iter().enumerate() {
+            for (j, &b) in selected.iter().enumerate() {
+                gram[i * k + j] = dot(atom(a), atom(b)) as f64;
+            }
+            gram[i * k + i] += 1e-9;
+        }
+        let rhs: Vec<f64> = selected.iter().map(|&a| dot(atom(a), signal) as f64).collect();
+        let Some(solution) = cholesky_solve(&gram, &rhs, k) else {
+            selected.pop();
+            break;
+        };
+        coefficients = solution.iter().map(|&c| c as f32).collect();
+
+        residual.copy_from_slice(signal);
+        for (&a, &c) in selected.iter().zip(&coefficients) {
+            for (r, v) in residual.iter_mut().zip(atom(a)) {
+                *r -= c * v;
+            }
+        }
+    }
+
+    selected.into_iter().zip(coefficients).collect()
+}
+
+/// First and second derivatives of the base luma, the low-resolution features
+fn feature_planes(luma: &Plane) -> [Plane; 4] {
+    let mut planes = [(); 4].map(|_| Plane::new(luma.width, luma.height));
+    for y in 0..luma.height as i32 {
+        for x in 0..luma.width as i32 {
+            let i = y as usize * luma.width + x as usize;
+            let c = luma.get_clamped(x, y);
+            planes[0].data[i] = luma.get_clamped(x + 1, y) - luma.get_clamped(x - 1, y);
+            planes[1].data[i] = luma.get_clamped(x, y + 1) - luma.get_clamped(x, y - 1);
+            planes[2].data[i] = luma.get_clamped(x + 2, y) - 2.0 * c + luma.get_clamped(x - 2, y);
+            planes[3].data[i] = luma.get_clamped(x, y + 2) - 2.0 * c + luma.get_clamped(x, y - 2);
+        }
+    }
+    planes
+}
+
+fn feature(planes: &[Plane; 4], x: usize, y: usize, size: usize) -> Vec<f32> {
+    planes.iter().flat_map(|p| p.patch(x as i32, y as i32, size)).collect()
+}
+
+fn norm(v: &[f32]) -> f32 {
+    v.iter().map(|x| x * x).sum::<f32>().sqrt()
+}
+
+/// Least-squares dictionary for fixed sparse codes: argmin ‖Y - D A‖
+///
+/// `targets` holds one `dim`-vector per sample; the result is atom-major.
+fn fit_atoms(codes: &[Vec<(usize, f32)>], targets: &[f32], dim: usize, atoms: usize) -> Vec<f32> {
+    let mut gram = vec![0.0f64; atoms * atoms];
+    let mut cross = vec![0.0f64; dim * atoms];
+    for (code, target) in codes.iter().zip(targets.chunks_exact(dim)) {
+        for &(a, ca) in code {
+            for &(b, cb) in code {
+                gram[a * atoms + b] += (ca * cb) as f64;
+            }
+            for (d, &t) in target.iter().enumerate() {
+                cross[d * atoms + a] += (t * ca) as f64;
+            }
+        }
+    }
+
+    // Unused atoms get an identity row so the system stays definite
+    for a in 0..atoms {
+        if gram[a * atoms + a] == 0.0 {
+            gram[a * atoms + a] = 1.0;
+        }
+    }
+
+    let mut dictionary = vec![0.0f32; atoms * dim];
+    for d in 0..dim {
+        if let Some(row) = ridge_solve(&gram, &cross[d * atoms..(d + 1) * atoms], atoms, 1e-6) {
+            for (a, v) in row.iter().enumerate() {
+                dictionary[a * dim + d] = *v as f32;
+            }
+        }
+    }
+    dictionary
+}
+
+/// Learns a coupled `Dictionary` from high-resolution examples
+///
+/// Follows Zeyde et al.'s take on Yang et al.: the low-resolution dictionary
+/// is learned on normalised derivative features by alternating OMP coding
+/// with a method-of-optimal-directions update, then the high-resolution
+/// dictionary is the least-squares fit to the final codes, so each pair of
+/// atoms shares one sparse representation.
+pub struct DictionaryTrainer {
+    scale: usize,
+    patch_size: usize,
+    atoms: usize,
+    sparsity: usize,
+    iterations: usize,
+    max_samples: usize,
+    sample_stride: usize,
+}
+
+impl DictionaryTrainer {
+    /// 256 atoms of 5x5 patches, 3 non-zeros per code, 10 training rounds
+    pub fn new(scale: usize) -> Self {
+        Self {
+            scale: scale.clamp(2, 4),
+            patch_size: 5,
+            atoms: 256,
+            sparsity: 3,
+            iterations: 10,
+            max_samples: 20_000,
+            sample_stride: 2,
+        }
+    }
+
+    pub fn with_patch_size(mut self, patch_size: usize) -> Self {
+        self.patch_size = patch_size.clamp(3, 15);
+        self
+    }
+
+    pub fn with_atoms(mut self, atoms: usize) -> Self {
+        self.atoms = atoms.max(1);
+        self
+    }
+
+    /// Non-zero coefficients per patch code
+    pub fn with_sparsity(mut self, sparsity: usize) -> Self {
+        self.sparsity = sparsity.max(1);
+        self
+    }
+
+    pub fn with_iterations(mut self, iterations: usize) -> Self {
+        self.iterations = iterations;
+        self
+    }
+
+    /// Cap on training patches; larger sets are evenly subsampled
+    pub fn with_max_samples(mut self, max_samples: usize) -> Self {
+        self.max_samples = max_samples.max(1);
+        self
+    }
+
+    /// Place a training patch every n pixels in each direction
+    pub fn with_sample_stride(mut self, stride: usize) -> Self {
+        self.sample_stride = stride.max(1);
+        self
+    }
+
+    /// Train from in-memory high-resolution images
+    pub fn train_images(&self, images: &[Image]) -> Result<Dictionary, String> {
+        let p = self.patch_size;
+        let (low_dim, high_dim) = (4 * p * p, p * p);
+
+        // Normalised feature / detail pairs from every textured patch
+        let mut features = Vec::new();
+        let mut details = Vec::new();
+        for (base, original) in images.iter().filter_map(|im| training_pair(im, self.scale, p)) {
+            let planes = feature_planes(&base);
+            for &y in &patch_origins(base.height, p, self.sample_stride) {
+                for &x in &patch_origins(base.width, p, self.sample_stride) {
+                    let f = feature(&planes, x, y, p);
+                    let n = norm(&f);
+                    if n < MIN_FEATURE_NORM {
+                        continue;
+                    }
+                    let base_patch = base.patch(x as i32, y as i32, p);
+                    let mean = base_patch.iter().sum::<f32>() / high_dim as f32;
+                    features.extend(f.iter().map(|v| v / n));
+                    details.extend(original.patch(x as i32, y as i32, p).iter().map(|v| (v - mean) / n));
+                }
+            }
+        }
+
+        let available = features.len() / low_dim;
+        if available == 0 {
+            return Err(format!(
+                "No textured {}x{} patches in the training images (each needs at least {} pixels per side)",
+                p, p, p * self.scale
+            ));
+        }
+        if available > self.max_samples {
+            let keep: Vec<usize> = (0..self.max_samples).map(|i| i * available / self.max_samples).collect();
+            features = keep.iter().flat_map(|&i| features[i * low_dim..(i + 1) * low_dim].to_vec()).collect();
+            details = keep.iter().flat_map(|&i| details[i * high_dim..(i + 1) * high_dim].to_vec()).collect();
+        }
+        let samples = features.len() / low_dim;
+        let atoms = self.atoms.min(samples);
+
+        // Seed atoms with evenly spaced samples
+        let mut low: Vec<f32> = (0..atoms)
+            .flat_map(|a| features[a * samples / atoms * low_dim..(a * samples / atoms + 1) * low_dim].to_vec())
+            .collect();
+
+        let code_all = |low: &[f32]| -> Vec<Vec<(usize, f32)>> {
+            features.chunks_exact(low_dim).map(|f| orthogonal_matching_pursuit(low, f, self.sparsity, 1e-3)).collect()
+        };
+
+        for round in 0..self.iterations {
+            let codes = code_all(&low);
+            low = fit_atoms(&codes, &features, low_dim, atoms);
+
+            // Renormalise; re-seed dead atoms with samples
+            for a in 0..atoms {
+                let atom = &mut low[a * low_dim..(a + 1) * low_dim];
+                let n = norm(atom);
+                if n > 1e-6 {
+                    atom.iter_mut().for_each(|v| *v /= n);
+                } else {
+                    let s = (a * 7919 + round * 104_729) % samples;
+                    atom.copy_from_slice(&features[s * low_dim..(s + 1) * low_dim]);
+                }
+            }
+        }
+
+        let codes = code_all(&low);
+        let high = fit_atoms(&codes, &details, high_dim, atoms);
+
+        Ok(Dictionary {
+            scale: self.scale,
+            patch_size: p,
+            atoms,
+            low,
+            high,
+        })
+    }
+
+    /// Train from every image file in a local folder
+    pub fn train_folder<P: AsRef<Path>>(&self, folder: P) -> Result<Dictionary, String> {
+        let images = load_folder(folder.as_ref())?;
+        self.train_images(&images)
+    }
+}
+
+/// Sparse-coding super-resolution (Yang et al. 2010)
+///
+/// Enlarges with bicubic, codes each overlapping window's derivative features
+/// against the low-resolution atoms with OMP, and rebuilds the window from
+/// the coupled high-resolution atoms plus the window mean. Overlapping
+/// windows are averaged, and colour follows the luma correction. Works best
+/// at the dictionary's training scale.
+///
+/// Time complexity: O(n * atoms * patch² * sparsity / step²)
+/// Space complexity: O(n) plus the dictionary
+pub struct SparseCoding {
+    dictionary: Dictionary,
+    sparsity: usize,
+    overlap: usize,
+}
+
+impl SparseCoding {
+    /// 3 non-zeros per code, windows overlapping by all but one pixel
+    pub fn new(dictionary: Dictionary) -> Self {
+        let overlap = dictionary.patch_size - 1;
+        Self {
+            dictionary,
+            sparsity: 3,
+            overlap,
+        }
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        Dictionary::load(path).map(Self::new)
+    }
+
+    pub fn with_sparsity(mut self, sparsity: usize) -> Self {
+        self.sparsity = sparsity.max(1);
+        self
+    }
+
+    /// Pixels shared by neighbouring windows; less overlap is faster
+    pub fn with_overlap(mut self, overlap: usize) -> Self {
+        self.overlap = overlap.min(self.dictionary.patch_size - 1);
+        self
+    }
+}
+
+impl Upscaler for SparseCoding {
+    fn upscale(&self, image: &Image, scale_factor: f32) -> Image {
+        let w = (image.width as f32 * scale_factor).round() as usize;
+        let h = (image.height as f32 * scale_factor).round() as usize;
+        let p = self.dictionary.patch_size;
+        let step = p - self.overlap;
+
+        let mut result = Psf::Bicubic.resample(&FloatImage::from_image(image), w, h);
+        let luma = Plane::luma(&result);
+        let planes = feature_planes(&luma);
+
+        let mut sum = Plane::new(w, h);
+        let mut weight = Plane::new(w, h);
+        for &y in &patch_origins(h, p, step) {
+            for &x in &patch_origins(w, p, step) {
+                let base_patch = luma.patch(x as i32, y as i32, p);
+                let f = feature(&planes, x, y, p);
+                let n = norm(&f);
+
+                let mut patch = base_patch.clone();
+                if n >= MIN_FEATURE_NORM {
+                    let normalised: Vec<f32> = f.iter().map(|v| v / n).collect();
+                    let mean = base_patch.iter().sum::<f32>() / (p * p) as f32;
+                    patch.iter_mut().for_each(|v| *v = mean);
+                    for (atom, c) in orthogonal_matching_pursuit(&self.dictionary.low, &normalised, self.sparsity, 1e-3) {
+                        for (v, a) in patch.iter_mut().zip(self.dictionary.high_atom(atom)) {
+                            *v += c * a * n;
+                        }
+                    }
+                }
+
+                for dy in 0..p.min(h - y) {
+                    for dx in 0..p.min(w - x) {
+                        let i = (y + dy) * w + x + dx;
+                        sum.data[i] += patch[dy * p + dx];
+                        weight.data[i] += 1.0;
+                    }
+                }
+            }
+        }
+
+        for (i, px) in result.data.iter_mut().enumerate() {
+            if weight.data[i] > 0.0 {
+                let delta = sum.data[i] / weight.data[i] - luma.data[i];
+                px.iter_mut().for_each(|v| *v += delta);
+            }
+        }
+        result.clamp_to_range();
+        result.to_image()
+    }
+
+    fn name(&self) -> &str {
+        "Sparse-Coding Dictionary SR"
+    }
+
+    fn tier(&self) -> UpscaleTier {
+        UpscaleTier::Slow
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::image::Pixel;
+
+    fn create_training_image() -> Image {
+        let mut img = Image::new(32, 32);
+        for y in 0..32 {
+            for x in 0..32 {
+                let val = if (x + 2 * y) % 11 < 5 { 210 } else { 30 };
+                img.set_pixel(x, y, Pixel::new(val, val, val));
+            }
+        }
+        img
+    }
+
+    #[test]
+    fn test_omp_recovers_sparse_code() {
+        // Three orthonormal atoms in 4-D, signal = 2·e0 - 0.5·e2
+        let dictionary = [1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0];
+        let mut code = orthogonal_matching_pursuit(&dictionary, &[2.0, 0.0, -0.5, 0.0], 3, 1e-6);
+        code.sort_by_key(|&(a, _)| a);
+        assert_eq!(code.len(), 2);
+        assert_eq!(code[0].0, 0);
+        assert!((code[0].1 - 2.0).abs() < 1e-5);
+        assert_eq!(code[1].0, 2);
+        assert!((code[1].1 + 0.5).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_dictionary_round_trip() {
+        let dictionary = DictionaryTrainer::new(2)
+            .with_patch_size(3)
+            .with_atoms(16)
+            .with_iterations(2)
+            .train_images(&[create_training_image()])
+            .unwrap();
+        assert_eq!(dictionary.atoms(), 16);
+
+        let bytes = dictionary.to_bytes();
+        assert_eq!(&bytes[0..4], MAGIC);
+        assert_eq!(Dictionary::from_bytes(&bytes).unwrap(), dictionary);
+        assert!(Dictionary::from_bytes(&bytes[..bytes.len() - 4]).is_err());
+    }
+
+    #[test]
+    fn test_coupled_atoms_rebuild_a_training_patch() {
+        // One atom pair learned from a single 8x8 window: its features code
+        // to exactly that atom, so the window comes back as the original
+        let training = create_training_image();
+        let mut truth = Image::new(8, 8);
+        for y in 0..8 {
+            for x in 0..8 {
+                truth.set_pixel(x, y, training.get_pixel(x, y).unwrap());
+            }
+        }
+        let low = Psf::Bicubic.resample(&FloatImage::from_image(&truth), 4, 4).to_image();
+
+        let base = Plane::luma(&Psf::Bicubic.resample(&FloatImage::from_image(&low), 8, 8));
+        let f = feature(&feature_planes(&base), 0, 0, 8);
+        let n = norm(&f);
+        assert!(n >= MIN_FEATURE_NORM);
+        let mean = base.data.iter().sum::<f32>() / 64.0;
+        let dictionary = Dictionary {
+            scale: 2,
+            patch_size: 8,
+            atoms: 1,
+            low: f.iter().map(|v| v / n).collect(),
+            high: Plane::luma(&FloatImage::from_image(&truth)).data.iter().map(|v| (v - mean) / n).collect(),
+        };
+
+        let result = SparseCoding::new(dictionary).with_overlap(0).upscale(&low, 2.0);
+        assert_eq!((result.width, result.height), (8, 8));
+        let drift = result.pixels.iter().zip(&truth.pixels).map(|(a, b)| a.r.abs_diff(b.r)).max();
+        assert!(drift <= Some(1), "drift {:?}", drift);
+    }
+
+    #[test]
+    fn test_overlapping_windows_are_averaged() {
+        // Empty high atoms rebuild every window of a steep ramp as its flat mean
+        let mut low = Image::new(8, 8);
+        for (i, p) in low.pixels.iter_mut().enumerate() {
+            let v = (20 + i % 8 * 30) as u8;
+            *p = Pixel::new(v, v, v);
+        }
+        let mut atom = vec![0.0; 64];
+        atom[0] = 1.0;
+        let dictionary = Dictionary { scale: 2, patch_size: 4, atoms: 1, low: atom, high: vec![0.0; 16] };
+        let row = |image: &Image| -> Vec<u8> { (0..16).map(|x| image.get_pixel(x, 8).unwrap().r).collect() };
+
+        // Side-by-side windows give flat 4-pixel blocks
+        let blocks = row(&SparseCoding::new(dictionary.clone()).with_overlap(0).upscale(&low, 2.0));
+        assert!(blocks.chunks(4).all(|b| b.iter().all(|&v| v == b[0])), "{:?}", blocks);
+
+        // Windows sliding one pixel at a time blend those means into a ramp
+        let blended = row(&SparseCoding::new(dictionary).upscale(&low, 2.0));
+        assert!(blended.windows(2).all(|w| w[0] <= w[1]), "{:?}", blended);
+        let distinct = |values: &[u8]| values.windows(2).filter(|w| w[0] != w[1]).count();
+        assert!(distinct(&blended) > distinct(&blocks), "{:?} vs {:?}", blended, blocks);
+    }
+}
@@ -0,0 +1,149 @@
+use crate::algorithms::degradation::Psf;
+use crate::algorithms::image::{FloatImage, Image};
+use crate::algorithms::sampling::SamplingConfig;
+use crate::algorithms::upscaler::{Upscaler, UpscaleTier};
+
+/// Factor a supersampling wrapper renders at, for a requested target factor
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InternalScale {
+    /// Round the target up to a whole factor (2.5x renders at 3x)
+    NextInteger,
+    /// Round the target up to a power of two (3x renders at 4x)
+    NextPowerOfTwo,
+    /// Always render at this factor, or at the target if that is larger
+    Fixed(f32),
+}
+
+impl InternalScale {
+    /// Internal factor for a target `scale_factor`, never below it
+    pub fn resolve(&self, scale_factor: f32) -> f32 {
+        const EPSILON: f32 = 1e-3;
+        let internal = match *self {
+            InternalScale::NextInteger => (scale_factor - EPSILON).ceil(),
+            InternalScale::NextPowerOfTwo => 2f32.powf((scale_factor - EPSILON).log2().ceil()),
+            InternalScale::Fixed(factor) => factor,
+        };
+        internal.max(scale_factor)
+    }
+
+    /// Parse `integer`, `pow2` or a fixed factor such as `4`
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "integer" | "int" | "ceil" => Some(InternalScale::NextInteger),
+            "pow2" | "power_of_two" => Some(InternalScale::NextPowerOfTwo),
+            other => other.parse::<f32>().ok().filter(|f| *f > 0.0).map(InternalScale::Fixed),
+        }
+    }
+}
+
+/// Render with any upscaler at a higher internal factor, then downsample
+///
+/// Pixel-art and text algorithms are often only at their best at whole or
+/// power-of-two factors. This wrapper runs `inner` at the `InternalScale`
+/// factor and shrinks the result to the requested size with `filter`, whose
+/// kernel is stretched over each output pixel so the downscale antialiases
+/// rather than aliases. `Psf::Box` is an exact area average.
+///
+/// Time complexity: O(inner at the internal factor) plus O(n * k²) to downsample
+/// Space complexity: O(n * (internal / target)²) for the oversized render
+pub struct Supersample {
+    inner: Box<dyn Upscaler>,
+    name: String,
+    internal: InternalScale,
+    filter: Psf,
+}
+
+impl Supersample {
+    /// Render at the next power of two and area-average down
+    pub fn new(inner: Box<dyn Upscaler>) -> Self {
+        Self {
+            name: format!("Supersampled {}", inner.name()),
+            inner,
+            internal: InternalScale::NextPowerOfTwo,
+            filter: Psf::Box,
+        }
+    }
+
+    pub fn with_internal_scale(mut self, internal: InternalScale) -> Self {
+        self.internal = internal;
+        self
+    }
+
+    /// Antialiasing filter for the final downscale
+    pub fn with_filter(mut self, filter: Psf) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    fn downsample(&self, rendered: Image, width: usize, height: usize) -> Image {
+        if rendered.width == width && rendered.height == height {
+            return rendered;
+        }
+        self.filter.resample(&FloatImage::from_image(&rendered), width, height).to_image()
+    }
+}
+
+impl Upscaler for Supersample {
+    fn upscale(&self, image: &Image, scale_factor: f32) -> Image {
+        let rendered = self.inner.upscale(image, self.internal.resolve(scale_factor));
+        let width = (image.width as f32 * scale_factor).round() as usize;
+        let height = (image.height as f32 * scale_factor).round() as usize;
+        self.downsample(rendered, width, height)
+    }
+
+    fn upscale_with_sampling(&self, image: &Image, scale_factor: f32, sampling: &SamplingConfig) -> Image {
+        let rendered = self.inner.upscale_with_sampling(image, self.internal.resolve(scale_factor), sampling);
+        let width = (image.width as f32 * scale_factor).round() as usize;
+        let height = (image.height as f32 * scale_factor).round() as usize;
+        self.downsample(rendered, width, height)
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn supports_sampling(&self) -> bool {
+        self.inner.supports_sampling()
+    }
+
+    fn tier(&self) -> UpscaleTier {
+        self.inner.tier()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::image::Pixel;
+    use crate::algorithms::instant::NearestNeighbor;
+
+    #[test]
+    fn test_internal_scale() {
+        assert_eq!(InternalScale::NextPowerOfTwo.resolve(3.0), 4.0);
+        assert_eq!(InternalScale::NextPowerOfTwo.resolve(4.0), 4.0);
+        assert_eq!(InternalScale::NextInteger.resolve(2.5), 3.0);
+        assert_eq!(InternalScale::NextInteger.resolve(3.0), 3.0);
+        assert_eq!(InternalScale::Fixed(4.0).resolve(6.0), 6.0);
+        assert_eq!(InternalScale::parse("pow2"), Some(InternalScale::NextPowerOfTwo));
+        assert_eq!(InternalScale::parse("8"), Some(InternalScale::Fixed(8.0)));
+    }
+
+    #[test]
+    fn test_supersampled_nearest_is_antialiased() {
+        // Black and white columns: at 1.5x plain nearest neighbour can only repeat them,
+        // rendering at 2x and area-averaging puts grey where a column straddles a pixel
+        let mut img = Image::new(4, 2);
+        for y in 0..2 {
+            for x in 0..4 {
+                let v = if x % 2 == 0 { 0 } else { 255 };
+                img.set_pixel(x, y, Pixel::new(v, v, v));
+            }
+        }
+
+        let result = Supersample::new(Box::new(NearestNeighbor)).upscale(&img, 1.5);
+        assert_eq!((result.width, result.height), (6, 3));
+        assert_eq!(result.get_pixel(0, 0), Some(Pixel::black()));
+        assert!(result.pixels.iter().any(|p| p.r > 64 && p.r < 192));
+        assert!(NearestNeighbor.upscale(&img, 1.5).pixels.iter().all(|p| p.r == 0 || p.r == 255));
+    }
+}
@@ -0,0 +1,28 @@
+// Fixtures and measures shared by the algorithm tests
+use crate::algorithms::image::{FloatImage, Image, Pixel};
+
+/// An `ink` disc centred on `paper`, antialiased by 8x8 supersampling
+pub fn render_disc(size: usize, radius: f32, paper: Pixel, ink: Pixel) -> Image {
+    let centre = size as f32 / 2.0;
+    let mut img = Image::new(size, size);
+    for y in 0..size {
+        for x in 0..size {
+            let mut inside = 0;
+            for sy in 0..8 {
+                for sx in 0..8 {
+                    let (u, v) = (x as f32 + (sx as f32 + 0.5) / 8.0, y as f32 + (sy as f32 + 0.5) / 8.0);
+                    if (u - centre).powi(2) + (v - centre).powi(2) < radius * radius {
+                        inside += 1;
+                    }
+                }
+            }
+            img.set_pixel(x, y, Pixel::lerp(paper, ink, inside as f32 / 64.0));
+        }
+    }
+    img
+}
+
+/// Root-mean-square difference from `truth` over every channel
+pub fn rms_error(image: &Image, truth: &Image) -> f32 {
+    FloatImage::from_image(image).difference(&FloatImage::from_image(truth)).rms()
+}
@@ -0,0 +1,445 @@
+use crate::algorithms::image::{Image, Pixel};
+use crate::algorithms::upscaler::{Upscaler, UpscaleTier};
+use crate::algorithms::vector::{
+    assemble_shapes, colour_key, outline, trace_boundaries, Outline, PathSegment, Point, VectorImage, Vectorizer, OUTSIDE,
+};
+
+/// Palette entries closer than this (RGB distance) are one colour
+const MIN_DISTANCE: f32 = 40.0;
+
+/// Corners stay sharp between straight runs of this many pixels
+const CORNER_RUN: f32 = 2.0;
+
+fn distance_sq(a: [f32; 3], b: [f32; 3]) -> f32 {
+    (0..3).map(|c| (a[c] - b[c]).powi(2)).sum()
+}
+
+fn channels(p: Pixel) -> [f32; 3] {
+    [p.r as f32, p.g as f32, p.b as f32]
+}
+
+fn nearest(palette: &[[f32; 3]], colour: [f32; 3]) -> (usize, f32) {
+    palette
+        .iter()
+        .enumerate()
+        .map(|(i, &p)| (i, distance_sq(p, colour)))
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .unwrap_or((0, 0.0))
+}
+
+/// Dominant flat colours of an image, most common first
+///
+/// Flat pixels, those matching their 4-neighbours, are histogrammed in
+/// 4-bit-per-channel buckets, so antialiased edges don't add entries. The
+/// fullest buckets become entries unless close to one already chosen, down
+/// to 0.25% of the flat pixels. A few k-means passes then refine the entries
+/// using only pixels near one.
+pub fn detect_palette(image: &Image, max_colors: usize) -> Vec<Pixel> {
+    let (w, h) = (image.width, image.height);
+    let flat: Vec<Pixel> = (0..w * h)
+        .filter(|&i| {
+            let (x, y) = ((i % w) as i32, (i / w) as i32);
+            let centre = channels(image.pixels[i]);
+            [(-1, 0), (1, 0), (0, -1), (0, 1)]
+                .iter()
+                .all(|&(dx, dy)| distance_sq(channels(image.get_pixel_clamped(x + dx, y + dy)), centre) < 24.0 * 24.0)
+        })
+        .map(|i| image.pixels[i])
+        .collect();
+    let samples = if flat.is_empty() { &image.pixels } else { &flat };
+
+    let mut buckets = vec![(0usize, [0.0f64; 3]); 4096];
+    for &p in samples {
+        let bucket = &mut buckets[(p.r as usize >> 4) << 8 | (p.g as usize >> 4) << 4 | p.b as usize >> 4];
+        bucket.0 += 1;
+        for (sum, v) in bucket.1.iter_mut().zip(channels(p)) {
+            *sum += v as f64;
+        }
+    }
+    buckets.sort_by_key(|b| std::cmp::Reverse(b.0));
+
+    let min_count = (samples.len() / 400).max(1);
+    let mut palette: Vec<[f32; 3]> = Vec::new();
+    for &(count, sum) in buckets.iter().take_while(|b| b.0 >= min_count) {
+        let mean = sum.map(|s| (s / count as f64) as f32);
+        if palette.len() < max_colors.max(1) && palette.iter().all(|&p| distance_sq(p, mean) > MIN_DISTANCE * MIN_DISTANCE) {
+            palette.push(mean);
+        }
+    }
+    if palette.is_empty() {
+        return Vec::new();
+    }
+
+    for _ in 0..5 {
+        let mut sums = vec![(0usize, [0.0f64; 3]); palette.len()];
+        for &p in &image.pixels {
+            let (i, d) = nearest(&palette, channels(p));
+            if d < MIN_DISTANCE * MIN_DISTANCE {
+                sums[i].0 += 1;
+                for (sum, v) in sums[i].1.iter_mut().zip(channels(p)) {
+                    *sum += v as f64;
+                }
+            }
+        }
+        for (entry, (count, sum)) in palette.iter_mut().zip(sums) {
+            if count > 0 {
+                *entry = sum.map(|s| (s / count as f64) as f32);
+            }
+        }
+    }
+
+    palette.iter().map(|p| Pixel::new(p[0].round() as u8, p[1].round() as u8, p[2].round() as u8)).collect()
+}
+
+/// Raster-to-vector tracing for flat-colour artwork, logos and line art
+///
+/// Quantises the image to its detected palette (at most `max_colors`),
+/// merges regions smaller than `min_area` pixels into their commonest
+/// neighbour, traces the boundaries between regions along pixel edges, and
+/// fits cubic Béziers to the midpoints of each boundary's straight runs
+/// (Schneider's algorithm) to within `tolerance` pixels. Corners between
+/// runs of two pixels or more stay sharp. The result is a `VectorImage` for
+/// SVG export, rasterised at any scale as an `Upscaler`.
+///
+/// Time complexity: O(n * max_colors) to quantise, O(output pixels) to rasterise
+/// Space complexity: O(n)
+pub struct Tracer {
+    max_colors: usize,
+    tolerance: f32,
+    min_area: usize,
+}
+
+impl Tracer {
+    /// Up to 16 colours, half-pixel fitting tolerance, speckles under 4 pixels removed
+    pub fn new() -> Self {
+        Self {
+            max_colors: 16,
+            tolerance: 0.5,
+            min_area: 4,
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "trace" | "vector_trace" => Some(Self::new()),
+            _ => None,
+        }
+    }
+
+    pub fn with_max_colors(mut self, colors: usize) -> Self {
+        self.max_colors = colors.clamp(2, 256);
+        self
+    }
+
+    /// Largest distance, in source pixels, between a fitted curve and the traced boundary
+    pub fn with_tolerance(mut self, pixels: f32) -> Self {
+        self.tolerance = pixels.max(0.05);
+        self
+    }
+
+    /// Regions smaller than this many pixels join a neighbour; 0 keeps every region
+    pub fn with_min_area(mut self, pixels: usize) -> Self {
+        self.min_area = pixels;
+        self
+    }
+
+    /// Palette index of every pixel, with speckles merged away
+    pub fn quantize(&self, image: &Image) -> (Vec<Pixel>, Vec<usize>) {
+        let palette = detect_palette(image, self.max_colors);
+        if palette.is_empty() {
+            return (palette, Vec::new());
+        }
+        let entries: Vec<[f32; 3]> = palette.iter().map(|&p| channels(p)).collect();
+        let mut labels: Vec<usize> = image.pixels.iter().map(|&p| nearest(&entries, channels(p)).0).collect();
+        remove_speckles(&mut labels, image.width, image.height, self.min_area);
+        (palette, labels)
+    }
+
+    /// Vectorise the image into flat colour regions in source pixel units
+    pub fn vectorize(&self, image: &Image) -> VectorImage {
+        let (w, h) = (image.width, image.height);
+        let mut vector = VectorImage { width: w as f32, height: h as f32, shapes: Vec::new() };
+        let (palette, labels) = self.quantize(image);
+        if labels.is_empty() {
+            return vector;
+        }
+
+        let cells: Vec<u32> = labels.iter().map(|&i| colour_key(palette[i])).collect();
+        let boundaries = trace_boundaries(&cells, w, h);
+        let paths: Vec<(Point, Vec<PathSegment>)> = boundaries
+            .iter()
+            .map(|boundary| {
+                let points: Vec<Point> = boundary.points.iter().map(|&(x, y)| (x as f32, y as f32)).collect();
+                let corner_run = if boundary.sides.contains(&OUTSIDE) { 0.0 } else { CORNER_RUN };
+                self.fit(outline(&points, boundary.closed, corner_run))
+            })
+            .collect();
+        vector.shapes = assemble_shapes(&boundaries, &paths);
+        vector
+    }
+
+    fn fit(&self, outline: Outline) -> (Point, Vec<PathSegment>) {
+        let mut segments = Vec::new();
+        match outline {
+            Outline::Loop(mut points) => {
+                // Close the loop smoothly through its first point
+                let n = points.len();
+                let tangent = normalize(sub(points[1], points[n - 1]));
+                points.push(points[0]);
+                fit_cubics(&points, tangent, scale(tangent, -1.0), self.tolerance, &mut segments);
+                (points[0], segments)
+            }
+            Outline::Pieces(pieces) => {
+                for piece in &pieces {
+                    let k = piece.len() - 1;
+                    if k == 1 {
+                        segments.push(PathSegment::Line(piece[1]));
+                    } else {
+                        let (t1, t2) = (normalize(sub(piece[1], piece[0])), normalize(sub(piece[k - 1], piece[k])));
+                        fit_cubics(piece, t1, t2, self.tolerance, &mut segments);
+                    }
+                }
+                (pieces[0][0], segments)
+            }
+        }
+    }
+}
+
+/// Merge 4-connected regions under `min_area` pixels into their commonest neighbour
+fn remove_speckles(labels: &mut [usize], width: usize, height: usize, min_area: usize) {
+    let mut seen = vec![false; labels.len()];
+    for start in 0..labels.len() {
+        if seen[start] {
+            continue;
+        }
+        seen[start] = true;
+        let label = labels[start];
+        let mut members = vec![start];
+        let mut i = 0;
+        let mut neighbours: Vec<usize> = Vec::new();
+        while i < members.len() {
+            let p = members[i];
+            i += 1;
+            let (x, y) = (p % width, p / width);
+            let adjacent = [
+                (x > 0).then(|| p - 1),
+                (x + 1 < width).then_some(p + 1),
+                (y > 0).then(|| p - width),
+                (y + 1 < height).then_some(p + width),
+            ];
+            for q in adjacent.into_iter().flatten() {
+                if labels[q] != label {
+                    neighbours.push(labels[q]);
+                } else if !seen[q] {
+                    seen[q] = true;
+                    members.push(q);
+                }
+            }
+        }
+
+        if members.len() < min_area && !neighbours.is_empty() {
+            neighbours.sort_unstable();
+            let commonest = neighbours
+                .chunk_by(|a, b| a == b)
+                .max_by_key(|run| run.len())
+                .map(|run| run[0])
+                .unwrap_or(label);
+            for p in members {
+                labels[p] = commonest;
+            }
+        }
+    }
+}
+
+fn sub(a: Point, b: Point) -> Point {
+    (a.0 - b.0, a.1 - b.1)
+}
+
+fn add(a: Point, b: Point) -> Point {
+    (a.0 + b.0, a.1 + b.1)
+}
+
+fn scale(a: Point, s: f32) -> Point {
+    (a.0 * s, a.1 * s)
+}
+
+fn dot(a: Point, b: Point) -> f32 {
+    a.0 * b.0 + a.1 * b.1
+}
+
+fn normalize(a: Point) -> Point {
+    let length = dot(a, a).sqrt();
+    if length > 1e-9 { scale(a, 1.0 / length) } else { (0.0, 0.0) }
+}
+
+fn bezier(curve: &[Point; 4], t: f32) -> Point {
+    let u = 1.0 - t;
+    let weights = [u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t];
+    curve.iter().zip(weights).fold((0.0, 0.0), |sum, (&p, w)| add(sum, scale(p, w)))
+}
+
+/// Cubic Béziers through `points` to within `tolerance`, leaving the ends
+/// along `tan1` and `tan2` (Schneider, Graphics Gems 1990)
+fn fit_cubics(points: &[Point], tan1: Point, tan2: Point, tolerance: f32, out: &mut Vec<PathSegment>) {
+    let n = points.len();
+    if n == 2 {
+        out.push(PathSegment::Line(points[1]));
+        return;
+    }
+
+    // Chord-length parameters, refined by Newton steps unless hopeless
+    let mut lengths = vec![0.0f32; n];
+    for i in 1..n {
+        lengths[i] = lengths[i - 1] + dot(sub(points[i], points[i - 1]), sub(points[i], points[i - 1])).sqrt();
+    }
+    let total = lengths[n - 1].max(1e-9);
+    let mut u: Vec<f32> = lengths.iter().map(|l| l / total).collect();
+
+    let limit = tolerance * tolerance;
+    let mut curve = generate_bezier(points, &u, tan1, tan2);
+    let (mut error, mut split) = max_error(points, &curve, &u);
+    for _ in 0..4 {
+        if error > 16.0 * limit {
+            break;
+        }
+        for (t, &p) in u.iter_mut().zip(points) {
+            *t = newton_step(&curve, p, *t);
+        }
+        curve = generate_bezier(points, &u, tan1, tan2);
+        (error, split) = max_error(points, &curve, &u);
+    }
+    if error <= limit {
+        out.push(PathSegment::Cubic(curve[1], curve[2], curve[3]));
+        return;
+    }
+
+    let centre = normalize(sub(points[split - 1], points[split + 1]));
+    fit_cubics(&points[..=split], tan1, centre, tolerance, out);
+    fit_cubics(&points[split..], scale(centre, -1.0), tan2, tolerance, out);
+}
+
+/// Least-squares handle lengths along the end tangents
+fn generate_bezier(points: &[Point], u: &[f32], tan1: Point, tan2: Point) -> [Point; 4] {
+    let (first, last) = (points[0], points[points.len() - 1]);
+    let (mut c, mut x) = ([[0.0f32; 2]; 2], [0.0f32; 2]);
+    for (&p, &t) in points.iter().zip(u) {
+        let s = 1.0 - t;
+        let (b0, b1, b2, b3) = (s * s * s, 3.0 * s * s * t, 3.0 * s * t * t, t * t * t);
+        let (a1, a2) = (scale(tan1, b1), scale(tan2, b2));
+        c[0][0] += dot(a1, a1);
+        c[0][1] += dot(a1, a2);
+        c[1][1] += dot(a2, a2);
+        let rest = sub(p, add(scale(first, b0 + b1), scale(last, b2 + b3)));
+        x[0] += dot(a1, rest);
+        x[1] += dot(a2, rest);
+    }
+
+    let det = c[0][0] * c[1][1] - c[0][1] * c[0][1];
+    let chord = dot(sub(last, first), sub(last, first)).sqrt();
+    let (mut left, mut right) = (0.0, 0.0);
+    if det.abs() > 1e-12 {
+        left = (x[0] * c[1][1] - x[1] * c[0][1]) / det;
+        right = (c[0][0] * x[1] - c[0][1] * x[0]) / det;
+    }
+    // Degenerate or backwards handles fall back to a third of the chord
+    if left < 1e-6 * chord || right < 1e-6 * chord {
+        left = chord / 3.0;
+        right = chord / 3.0;
+    }
+    [first, add(first, scale(tan1, left)), add(last, scale(tan2, right)), last]
+}
+
+/// Largest squared distance between points and their curve positions, and where
+fn max_error(points: &[Point], curve: &[Point; 4], u: &[f32]) -> (f32, usize) {
+    let mut worst = (0.0, points.len() / 2);
+    for i in 1..points.len() - 1 {
+        let d = sub(bezier(curve, u[i]), points[i]);
+        if dot(d, d) > worst.0 {
+            worst = (dot(d, d), i);
+        }
+    }
+    worst
+}
+
+/// One Newton-Raphson step towards the curve parameter closest to `p`
+fn newton_step(curve: &[Point; 4], p: Point, t: f32) -> f32 {
+    let d1: Vec<Point> = (0..3).map(|i| scale(sub(curve[i + 1], curve[i]), 3.0)).collect();
+    let d2: Vec<Point> = (0..2).map(|i| scale(sub(d1[i + 1], d1[i]), 2.0)).collect();
+    let s = 1.0 - t;
+    let q1 = add(add(scale(d1[0], s * s), scale(d1[1], 2.0 * s * t)), scale(d1[2], t * t));
+    let q2 = add(scale(d2[0], s), scale(d2[1], t));
+    let diff = sub(bezier(curve, t), p);
+    let denominator = dot(q1, q1) + dot(diff, q2);
+    if denominator.abs() < 1e-12 {
+        t
+    } else {
+        (t - dot(diff, q1) / denominator).clamp(0.0, 1.0)
+    }
+}
+
+impl Vectorizer for Tracer {
+    fn vectorize(&self, image: &Image) -> VectorImage {
+        Tracer::vectorize(self, image)
+    }
+}
+
+impl Upscaler for Tracer {
+    fn upscale(&self, image: &Image, scale_factor: f32) -> Image {
+        let width = (image.width as f32 * scale_factor).round() as usize;
+        let height = (image.height as f32 * scale_factor).round() as usize;
+        if image.width == 0 || image.height == 0 {
+            return Image::new(width, height);
+        }
+        Tracer::vectorize(self, image).rasterize(width, height)
+    }
+
+    fn name(&self) -> &str {
+        "Vector Tracing"
+    }
+
+    fn tier(&self) -> UpscaleTier {
+        UpscaleTier::Medium
+    }
+}
+
+impl Default for Tracer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::test_support::{self, rms_error};
+
+    /// Orange disc on teal
+    fn render_disc(size: usize, radius: f32) -> Image {
+        test_support::render_disc(size, radius, Pixel::new(20, 140, 150), Pixel::new(250, 150, 30))
+    }
+
+    #[test]
+    fn test_palette_ignores_edge_pixels() {
+        let palette = detect_palette(&render_disc(24, 8.0), 16);
+        assert_eq!(palette.len(), 2);
+        assert!(palette.contains(&Pixel::new(20, 140, 150)));
+        assert!(palette.contains(&Pixel::new(250, 150, 30)));
+    }
+
+    #[test]
+    fn test_trace_upscales_flat_art() {
+        let small = render_disc(24, 8.0);
+        let truth = render_disc(96, 32.0);
+        let error = |im: &Image| rms_error(im, &truth);
+
+        let tracer = Tracer::new();
+        let vector = tracer.vectorize(&small);
+        assert_eq!(vector.shapes.len(), 2);
+        assert!(vector.to_svg(4.0).contains('C'));
+
+        let traced = tracer.upscale(&small, 4.0);
+        assert_eq!(traced.get_pixel(48, 48), Some(Pixel::new(250, 150, 30)));
+        assert_eq!(traced.get_pixel(2, 2), Some(Pixel::new(20, 140, 150)));
+        assert!(error(&traced) < 0.7 * error(&crate::fast::Bicubic.upscale(&small, 4.0)));
+    }
+}
@@ -0,0 +1,420 @@
+use crate::algorithms::fast::{Bicubic, EwaLanczos, Lanczos};
+use crate::algorithms::image::{Image, Pixel};
+use crate::algorithms::instant::Bilinear;
+use crate::algorithms::sampling::EdgeMode;
+
+/// 2D affine transform from source to output coordinates
+///
+/// Coordinates are continuous with the origin at the top-left image corner,
+/// so pixel (i, j) covers `[i, i + 1) x [j, j + 1)`.
+///
+/// ```text
+/// x' = a * x + b * y + tx
+/// y' = c * x + d * y + ty
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Affine {
+    pub a: f32,
+    pub b: f32,
+    pub c: f32,
+    pub d: f32,
+    pub tx: f32,
+    pub ty: f32,
+}
+
+impl Affine {
+    pub fn identity() -> Self {
+        Self { a: 1.0, b: 0.0, c: 0.0, d: 1.0, tx: 0.0, ty: 0.0 }
+    }
+
+    pub fn translate(tx: f32, ty: f32) -> Self {
+        Self { tx, ty, ..Self::identity() }
+    }
+
+    pub fn scale(sx: f32, sy: f32) -> Self {
+        Self { a: sx, d: sy, ..Self::identity() }
+    }
+
+    /// Clockwise rotation on screen (y points down) by `radians`
+    pub fn rotate(radians: f32) -> Self {
+        let (sin, cos) = radians.sin_cos();
+        Self { a: cos, b: -sin, c: sin, d: cos, tx: 0.0, ty: 0.0 }
+    }
+
+    /// Rotate clockwise by `degrees`; quarter turns are exact
+    pub fn rotate_degrees(degrees: f32) -> Self {
+        let mut rotation = Self::rotate(degrees.to_radians());
+        if degrees.rem_euclid(90.0) == 0.0 {
+            for v in [&mut rotation.a, &mut rotation.b, &mut rotation.c, &mut rotation.d] {
+                *v = v.round();
+            }
+        }
+        rotation
+    }
+
+    /// Shear: `x' = x + kx * y`, `y' = y + ky * x`
+    pub fn shear(kx: f32, ky: f32) -> Self {
+        Self { b: kx, c: ky, ..Self::identity() }
+    }
+
+    pub fn flip_horizontal() -> Self {
+        Self::scale(-1.0, 1.0)
+    }
+
+    pub fn flip_vertical() -> Self {
+        Self::scale(1.0, -1.0)
+    }
+
+    /// Compose: apply `self` first, then `next`
+    pub fn then(&self, next: &Affine) -> Affine {
+        Affine {
+            a: next.a * self.a + next.b * self.c,
+            b: next.a * self.b + next.b * self.d,
+            c: next.c * self.a + next.d * self.c,
+            d: next.c * self.b + next.d * self.d,
+            tx: next.a * self.tx + next.b * self.ty + next.tx,
+            ty: next.c * self.tx + next.d * self.ty + next.ty,
+        }
+    }
+
+    pub fn determinant(&self) -> f32 {
+        self.a * self.d - self.b * self.c
+    }
+
+    /// Inverse transform, or `None` if the matrix is singular
+    pub fn inverse(&self) -> Option<Affine> {
+        let det = self.determinant();
+        if det.abs() < 1e-12 {
+            return None;
+        }
+
+        let a = self.d / det;
+        let b = -self.b / det;
+        let c = -self.c / det;
+        let d = self.a / det;
+        Some(Affine {
+            a,
+            b,
+            c,
+            d,
+            tx: -(a * self.tx + b * self.ty),
+            ty: -(c * self.tx + d * self.ty),
+        })
+    }
+
+    pub fn apply(&self, x: f32, y: f32) -> (f32, f32) {
+        (
+            self.a * x + self.b * y + self.tx,
+            self.c * x + self.d * y + self.ty,
+        )
+    }
+}
+
+impl Default for Affine {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+/// Interpolation kernel used to resample through a transform
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Interpolation {
+    Nearest,
+    Bilinear,
+    Bicubic,
+    /// Lanczos with the given number of lobes
+    Lanczos(i32),
+    /// Jinc-windowed-Jinc EWA
+    EwaLanczos,
+    /// Jinc-windowed-Jinc EWA with the sharpening blur
+    EwaLanczosSharp,
+}
+
+/// Sampler at continuous pixel-centre coordinates; the jacobian is the source
+/// displacement of one output pixel step, `[dx/du, dx/dv, dy/du, dy/dv]`
+type Sampler = Box<dyn Fn(&Image, f32, f32, [f32; 4], EdgeMode) -> Pixel>;
+
+/// Jacobian of an output-to-source mapping that does not change scale
+const UNIT_JACOBIAN: [f32; 4] = [1.0, 0.0, 0.0, 1.0];
+
+impl Interpolation {
+    /// Parse a kernel from an algorithm name (`nearest`, `bicubic`, `lanczos3`, ...)
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "nearest" | "nearest_neighbor" => Some(Interpolation::Nearest),
+            "bilinear" => Some(Interpolation::Bilinear),
+            "bicubic" => Some(Interpolation::Bicubic),
+            "lanczos2" => Some(Interpolation::Lanczos(2)),
+            "lanczos" | "lanczos3" => Some(Interpolation::Lanczos(3)),
+            "lanczos4" => Some(Interpolation::Lanczos(4)),
+            "ewa" | "ewa_lanczos" => Some(Interpolation::EwaLanczos),
+            "ewa_sharp" | "ewa_lanczossharp" => Some(Interpolation::EwaLanczosSharp),
+            _ => None,
+        }
+    }
+
+    /// Build a sampler over pixel-centre coordinates (pixel i sits at x = i)
+    ///
+    /// Separable kernels are point samplers and ignore the jacobian; EWA
+    /// filters over the elliptical footprint it describes.
+    fn sampler(&self) -> Sampler {
+        match *self {
+            Interpolation::Nearest => Box::new(|image: &Image, x: f32, y: f32, _, edge_mode| {
+                image.get_pixel_edge(x.round() as i32, y.round() as i32, edge_mode)
+            }),
+            Interpolation::Bilinear => Box::new(|image: &Image, x, y, _, edge_mode| Bilinear::sample_bilinear(image, x, y, edge_mode)),
+            Interpolation::Bicubic => Box::new(|image: &Image, x, y, _, edge_mode| Bicubic::sample_bicubic(image, x, y, edge_mode)),
+            Interpolation::Lanczos(lobes) => {
+                let lanczos = Lanczos::with_lobes(lobes);
+                Box::new(move |image: &Image, x, y, _, edge_mode| lanczos.sample_lanczos(image, x, y, edge_mode))
+            }
+            Interpolation::EwaLanczos | Interpolation::EwaLanczosSharp => {
+                let ewa = if *self == Interpolation::EwaLanczos { EwaLanczos::new() } else { EwaLanczos::sharp() };
+                Box::new(move |image: &Image, x, y, jacobian, edge_mode| {
+                    ewa.sample_ewa_footprint(image, x, y, jacobian, edge_mode)
+                })
+            }
+        }
+    }
+}
+
+/// Resample an image through an affine transform or arbitrary coordinate mapping
+///
+/// Source positions that fall outside the input are filled with the background
+/// colour; the border itself is blended over roughly one output pixel.
+pub struct Warp {
+    interpolation: Interpolation,
+    background: Pixel,
+    output_size: Option<(usize, usize)>,
+}
+
+impl Warp {
+    /// Create a warp with a black background that fits the transformed bounds
+    pub fn new(interpolation: Interpolation) -> Self {
+        Self {
+            interpolation,
+            background: Pixel::black(),
+            output_size: None,
+        }
+    }
+
+    pub fn with_background(mut self, background: Pixel) -> Self {
+        self.background = background;
+        self
+    }
+
+    /// Use a fixed output canvas instead of fitting the transformed bounds
+    pub fn with_output_size(mut self, width: usize, height: usize) -> Self {
+        self.output_size = Some((width, height));
+        self
+    }
+
+    /// Apply an affine transform in a single resampling step
+    ///
+    /// Without a fixed output size, the canvas is the bounding box of the
+    /// transformed image and is shifted so that it starts at (0, 0).
+    pub fn affine(&self, image: &Image, transform: &Affine) -> Image {
+        let (transform, width, height) = match self.output_size {
+            Some((width, height)) => (*transform, width, height),
+            None => {
+                let corners = [
+                    transform.apply(0.0, 0.0),
+                    transform.apply(image.width as f32, 0.0),
+                    transform.apply(0.0, image.height as f32),
+                    transform.apply(image.width as f32, image.height as f32),
+                ];
+                let min_x = corners.iter().map(|c| c.0).fold(f32::INFINITY, f32::min);
+                let max_x = corners.iter().map(|c| c.0).fold(f32::NEG_INFINITY, f32::max);
+                let min_y = corners.iter().map(|c| c.1).fold(f32::INFINITY, f32::min);
+                let max_y = corners.iter().map(|c| c.1).fold(f32::NEG_INFINITY, f32::max);
+
+                // Snap to whole pixels so axis-aligned results are not shifted
+                let (min_x, min_y) = ((min_x + 1e-3).floor(), (min_y + 1e-3).floor());
+                let width = ((max_x - 1e-3).ceil() - min_x).max(0.0) as usize;
+                let height = ((max_y - 1e-3).ceil() - min_y).max(0.0) as usize;
+
+                (transform.then(&Affine::translate(-min_x, -min_y)), width, height)
+            }
+        };
+
+        let inverse = match transform.inverse() {
+            Some(inverse) => inverse,
+            None => return Image::from_pixels(
+                width,
+                height,
+                vec![self.background; width * height],
+            ).unwrap(),
+        };
+
+        let jacobian = [inverse.a, inverse.b, inverse.c, inverse.d];
+        self.resample(image, width, height, |x, y| Some(inverse.apply(x, y)), |_, _| jacobian)
+    }
+
+    /// Rotate clockwise by `degrees` and scale by `scale_factor` in one step
+    pub fn rotate_and_scale(&self, image: &Image, degrees: f32, scale_factor: f32) -> Image {
+        let transform = Affine::rotate_degrees(degrees).then(&Affine::scale(scale_factor, scale_factor));
+        self.affine(image, &transform)
+    }
+
+    /// Apply a user-supplied mapping from output to source coordinates
+    ///
+    /// `mapping` receives continuous output coordinates (pixel centres at
+    /// `i + 0.5`) and returns continuous source coordinates, or `None` to use
+    /// the background colour. The local scale, which sets EWA footprints and
+    /// the width of border blending, comes from the mapping's finite
+    /// differences one output pixel along each axis.
+    pub fn map<F>(&self, image: &Image, width: usize, height: usize, mapping: F) -> Image
+    where
+        F: Fn(f32, f32) -> Option<(f32, f32)>,
+    {
+        let jacobian = |x: f32, y: f32| match (mapping(x, y), mapping(x + 1.0, y), mapping(x, y + 1.0)) {
+            (Some((u, v)), Some((ux, vx)), Some((uy, vy))) => [ux - u, uy - u, vx - v, vy - v],
+            _ => UNIT_JACOBIAN,
+        };
+        self.resample(image, width, height, &mapping, jacobian)
+    }
+
+    fn resample<F, J>(&self, image: &Image, width: usize, height: usize, mapping: F, jacobian: J) -> Image
+    where
+        F: Fn(f32, f32) -> Option<(f32, f32)>,
+        J: Fn(f32, f32) -> [f32; 4],
+    {
+        let sampler = self.interpolation.sampler();
+        let (src_w, src_h) = (image.width as f32, image.height as f32);
+
+        let mut result = Image::new(width, height);
+
+        for y in 0..height {
+            for x in 0..width {
+                let pixel = match mapping(x as f32 + 0.5, y as f32 + 0.5) {
+                    Some((u, v)) => {
+                        let footprint = jacobian(x as f32 + 0.5, y as f32 + 0.5);
+                        let [a, b, c, d] = footprint;
+                        let pixels_per_source = 1.0 / (a * d - b * c).abs().sqrt().max(1e-6);
+
+                        // Distance inside the source rectangle, in output pixels
+                        let inside = u.min(src_w - u).min(v).min(src_h - v) * pixels_per_source;
+                        let coverage = (inside + 0.5).clamp(0.0, 1.0);
+
+                        if coverage <= 0.0 {
+                            self.background
+                        } else {
+                            let sample = sampler(image, u - 0.5, v - 0.5, footprint, EdgeMode::Clamp);
+                            if coverage >= 1.0 {
+                                sample
+                            } else {
+                                Pixel::lerp(self.background, sample, coverage)
+                            }
+                        }
+                    }
+                    None => self.background,
+                };
+                result.set_pixel(x, y, pixel);
+            }
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_image() -> Image {
+        let mut img = Image::new(4, 2);
+        for y in 0..2 {
+            for x in 0..4 {
+                img.set_pixel(x, y, Pixel::new((x * 60) as u8, (y * 120) as u8, 10));
+            }
+        }
+        img
+    }
+
+    #[test]
+    fn test_affine_inverse_and_compose() {
+        let t = Affine::rotate_degrees(30.0)
+            .then(&Affine::scale(2.0, 3.0))
+            .then(&Affine::translate(5.0, -1.0));
+        let round_trip = t.then(&t.inverse().unwrap());
+
+        let (x, y) = round_trip.apply(1.5, -2.0);
+        assert!((x - 1.5).abs() < 1e-4);
+        assert!((y + 2.0).abs() < 1e-4);
+        assert!(Affine::scale(0.0, 1.0).inverse().is_none());
+    }
+
+    #[test]
+    fn test_identity_warp_is_lossless() {
+        let img = create_test_image();
+        for interpolation in [
+            Interpolation::Nearest,
+            Interpolation::Bilinear,
+            Interpolation::Bicubic,
+        ] {
+            let result = Warp::new(interpolation).affine(&img, &Affine::identity());
+            assert_eq!(result.width, 4);
+            assert_eq!(result.height, 2);
+            assert_eq!(result.pixels, img.pixels);
+        }
+    }
+
+    #[test]
+    fn test_ewa_filters_shrinking_warps() {
+        // Stripes two pixels wide, shrunk 5x and rotated, average to an even grey
+        let mut img = Image::new(64, 64);
+        for (i, p) in img.pixels.iter_mut().enumerate() {
+            *p = if (i % 64) / 2 % 2 == 0 { Pixel::black() } else { Pixel::white() };
+        }
+        let transform = Affine::rotate_degrees(30.0).then(&Affine::scale(0.2, 0.2));
+        let result = Warp::new(Interpolation::EwaLanczos).affine(&img, &transform);
+        let (cx, cy) = (result.width / 2, result.height / 2);
+        let centre: Vec<u8> = (cy - 2..=cy + 2)
+            .flat_map(|y| (cx - 2..=cx + 2).map(move |x| (x, y)))
+            .map(|(x, y)| result.get_pixel(x, y).unwrap().r)
+            .collect();
+        let spread = centre.iter().max().unwrap() - centre.iter().min().unwrap();
+        assert!(spread < 24, "aliasing spread {}", spread);
+
+        assert_eq!(Interpolation::parse("ewa_sharp"), Some(Interpolation::EwaLanczosSharp));
+    }
+
+    #[test]
+    fn test_map_blends_borders_at_the_local_scale() {
+        // A map that matches an affine warp must blend its borders the same way
+        let img = Image::from_pixels(8, 8, vec![Pixel::white(); 64]).unwrap();
+        let warp = Warp::new(Interpolation::Bilinear).with_background(Pixel::black());
+        for scale in [0.25, 3.0] {
+            let expected = warp.affine(&img, &Affine::scale(scale, scale));
+            let result = warp.map(&img, expected.width, expected.height, |x, y| Some((x / scale, y / scale)));
+            let drift = result.pixels.iter().zip(&expected.pixels).map(|(a, b)| a.r.abs_diff(b.r)).max();
+            assert!(drift <= Some(1), "scale {} drift {:?}", scale, drift);
+        }
+    }
+
+    #[test]
+    fn test_rotate_90_fits_bounds() {
+        let img = create_test_image();
+        let result = Warp::new(Interpolation::Nearest).affine(&img, &Affine::rotate_degrees(90.0));
+
+        assert_eq!(result.width, 2);
+        assert_eq!(result.height, 4);
+        // Clockwise: the bottom-left source pixel becomes the top-left output pixel
+        assert_eq!(result.get_pixel(0, 0), img.get_pixel(0, 1));
+        assert_eq!(result.get_pixel(1, 3), img.get_pixel(3, 0));
+    }
+
+    #[test]
+    fn test_rotate_and_upscale_in_one_step() {
+        let img = create_test_image();
+        let transform = Affine::rotate_degrees(45.0).then(&Affine::scale(2.0, 2.0));
+        let result = Warp::new(Interpolation::Bicubic)
+            .with_background(Pixel::white())
+            .affine(&img, &transform);
+
+        // 45 degree bounding box of a 4x2 image is ~4.24 wide, doubled
+        assert_eq!(result.width, 9);
+        assert_eq!(result.height, 9);
+        assert_eq!(result.get_pixel(0, 0).unwrap(), Pixel::white());
+    }
+}
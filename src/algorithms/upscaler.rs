@@ -1,4 +1,5 @@
 use crate::algorithms::image::Image;
+use crate::algorithms::sampling::SamplingConfig;
 pub(crate) use crate::algorithms::upscale_tier::UpscaleTier;
 
 /// Trait for all upscaling algorithms
@@ -6,6 +7,20 @@ pub trait Upscaler: Send + Sync {
     /// Upscale an image by the given factor
     fn upscale(&self, image: &Image, scale_factor: f32) -> Image;
 
+    /// Upscale with explicit pixel-grid alignment and boundary handling
+    ///
+    /// Algorithms that don't report `supports_sampling` ignore `sampling` and
+    /// behave like `upscale`.
+    fn upscale_with_sampling(&self, image: &Image, scale_factor: f32, sampling: &SamplingConfig) -> Image {
+        let _ = sampling;
+        self.upscale(image, scale_factor)
+    }
+
+    /// Whether `upscale_with_sampling` honours the sampling config
+    fn supports_sampling(&self) -> bool {
+        false
+    }
+
     /// Get the name of this upscaler
     fn name(&self) -> &str;
 
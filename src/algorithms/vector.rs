@@ -0,0 +1,488 @@
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::path::Path;
+
+use crate::algorithms::image::{Image, Pixel};
+
+pub type Point = (f32, f32);
+
+/// One piece of a contour, continuing from the previous end point
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PathSegment {
+    Line(Point),
+    /// Quadratic Bézier: control point, end point
+    Quad(Point, Point),
+    /// Cubic Bézier: two control points, end point
+    Cubic(Point, Point, Point),
+}
+
+impl PathSegment {
+    pub fn end(&self) -> Point {
+        match *self {
+            PathSegment::Line(p) | PathSegment::Quad(_, p) | PathSegment::Cubic(_, _, p) => p,
+        }
+    }
+}
+
+/// A closed outline
+#[derive(Debug, Clone, PartialEq)]
+pub struct Contour {
+    pub start: Point,
+    pub segments: Vec<PathSegment>,
+}
+
+impl Contour {
+    /// Points along the contour, curves split into `steps` lines each
+    fn flatten(&self, steps: usize) -> Vec<Point> {
+        let mut points = vec![self.start];
+        let mut from = self.start;
+        for segment in &self.segments {
+            match *segment {
+                PathSegment::Line(p) => points.push(p),
+                PathSegment::Quad(c, p) => {
+                    for i in 1..=steps {
+                        let t = i as f32 / steps as f32;
+                        let u = 1.0 - t;
+                        points.push((
+                            u * u * from.0 + 2.0 * u * t * c.0 + t * t * p.0,
+                            u * u * from.1 + 2.0 * u * t * c.1 + t * t * p.1,
+                        ));
+                    }
+                }
+                PathSegment::Cubic(c1, c2, p) => {
+                    for i in 1..=steps {
+                        let t = i as f32 / steps as f32;
+                        let u = 1.0 - t;
+                        let (a, b, c, d) = (u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t);
+                        points.push((
+                            a * from.0 + b * c1.0 + c * c2.0 + d * p.0,
+                            a * from.1 + b * c1.1 + c * c2.1 + d * p.1,
+                        ));
+                    }
+                }
+            }
+            from = segment.end();
+        }
+        points
+    }
+}
+
+/// A filled region: all contours together, filled with the even-odd rule
+#[derive(Debug, Clone, PartialEq)]
+pub struct Shape {
+    pub fill: Pixel,
+    pub contours: Vec<Contour>,
+}
+
+/// Resolution-independent image made of filled shapes, in source pixel units
+///
+/// Shapes are expected to partition the canvas, as the vectorisers in this
+/// crate produce; rasterising normalises by total coverage so shared edges
+/// leave no seams.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VectorImage {
+    pub width: f32,
+    pub height: f32,
+    pub shapes: Vec<Shape>,
+}
+
+/// Converts a raster image into filled vector shapes
+pub trait Vectorizer {
+    fn vectorize(&self, image: &Image) -> VectorImage;
+}
+
+/// Whether a path asks for SVG output
+pub fn is_svg_path<P: AsRef<Path>>(path: P) -> bool {
+    path.as_ref().extension().is_some_and(|e| e.eq_ignore_ascii_case("svg"))
+}
+
+/// Vertical sub-scanlines per output pixel when rasterising
+const SUBSCANLINES: usize = 4;
+
+impl VectorImage {
+    /// Render at `width` x `height` pixels with antialiasing
+    pub fn rasterize(&self, width: usize, height: usize) -> Image {
+        let (sx, sy) = (width as f32 / self.width, height as f32 / self.height);
+        let steps = ((sx.max(sy) * 2.0).ceil() as usize).clamp(2, 32);
+
+        // Crossings of every shape edge with every sub-scanline
+        let rows = height * SUBSCANLINES;
+        let mut crossings: Vec<Vec<(usize, f32)>> = vec![Vec::new(); rows];
+        for (index, shape) in self.shapes.iter().enumerate() {
+            for contour in &shape.contours {
+                let points: Vec<Point> = contour.flatten(steps).iter().map(|p| (p.0 * sx, p.1 * sy)).collect();
+                for i in 0..points.len() {
+                    let (a, b) = (points[i], points[(i + 1) % points.len()]);
+                    if a.1 == b.1 {
+                        continue;
+                    }
+                    let (lo, hi) = if a.1 < b.1 { (a, b) } else { (b, a) };
+                    let first = ((lo.1 * SUBSCANLINES as f32 - 0.5).ceil().max(0.0)) as usize;
+                    let last = ((hi.1 * SUBSCANLINES as f32 - 0.5).ceil().max(0.0) as usize).min(rows);
+                    for (row, list) in crossings.iter_mut().enumerate().take(last).skip(first) {
+                        let y = (row as f32 + 0.5) / SUBSCANLINES as f32;
+                        let x = lo.0 + (y - lo.1) / (hi.1 - lo.1) * (hi.0 - lo.0);
+                        list.push((index, x));
+                    }
+                }
+            }
+        }
+
+        let mut colour = vec![[0.0f32; 3]; width * height];
+        let mut coverage = vec![0.0f32; width * height];
+        let weight = 1.0 / SUBSCANLINES as f32;
+        for (row, list) in crossings.iter_mut().enumerate() {
+            let y = row / SUBSCANLINES;
+            list.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.total_cmp(&b.1)));
+            for pair in list.chunks_exact(2) {
+                let (shape, x0, x1) = (pair[0].0, pair[0].1.clamp(0.0, width as f32), pair[1].1.clamp(0.0, width as f32));
+                if pair[1].0 != shape || x1 <= x0 {
+                    continue;
+                }
+                let fill = self.shapes[shape].fill;
+                for x in (x0.floor() as usize)..(x1.ceil() as usize).min(width) {
+                    let overlap = (x1.min(x as f32 + 1.0) - x0.max(x as f32)).max(0.0) * weight;
+                    let i = y * width + x;
+                    colour[i][0] += fill.r as f32 * overlap;
+                    colour[i][1] += fill.g as f32 * overlap;
+                    colour[i][2] += fill.b as f32 * overlap;
+                    coverage[i] += overlap;
+                }
+            }
+        }
+
+        let pixels = colour.iter().zip(&coverage).map(|(c, &w)| {
+            if w > 1e-6 {
+                Pixel::new((c[0] / w).round() as u8, (c[1] / w).round() as u8, (c[2] / w).round() as u8)
+            } else {
+                Pixel::black()
+            }
+        });
+        Image { width, height, pixels: pixels.collect() }
+    }
+
+    /// SVG document displayed at `scale` times the source size
+    pub fn to_svg(&self, scale: f32) -> String {
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">",
+            (self.width * scale).round(),
+            (self.height * scale).round(),
+            self.width,
+            self.height
+        );
+        for shape in &self.shapes {
+            let mut d = String::new();
+            for contour in &shape.contours {
+                let _ = write!(d, "M{} {}", fmt(contour.start.0), fmt(contour.start.1));
+                for segment in &contour.segments {
+                    let _ = match *segment {
+                        PathSegment::Line(p) => write!(d, "L{} {}", fmt(p.0), fmt(p.1)),
+                        PathSegment::Quad(c, p) => write!(d, "Q{} {} {} {}", fmt(c.0), fmt(c.1), fmt(p.0), fmt(p.1)),
+                        PathSegment::Cubic(c1, c2, p) => write!(
+                            d,
+                            "C{} {} {} {} {} {}",
+                            fmt(c1.0), fmt(c1.1), fmt(c2.0), fmt(c2.1), fmt(p.0), fmt(p.1)
+                        ),
+                    };
+                }
+                d.push('Z');
+            }
+            // A hairline stroke in the fill colour hides antialiasing seams
+            let hex = format!("#{:02x}{:02x}{:02x}", shape.fill.r, shape.fill.g, shape.fill.b);
+            let _ = writeln!(
+                svg,
+                "<path fill=\"{hex}\" fill-rule=\"evenodd\" stroke=\"{hex}\" stroke-width=\"0.05\" d=\"{d}\"/>"
+            );
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    /// Render at `scale` times the source size, rounded like `Upscaler` output
+    pub fn rasterize_scaled(&self, scale: f32) -> Image {
+        self.rasterize((self.width * scale).round() as usize, (self.height * scale).round() as usize)
+    }
+
+    pub fn save_svg<P: AsRef<Path>>(&self, path: P, scale: f32) -> Result<(), String> {
+        std::fs::write(path, self.to_svg(scale)).map_err(|e| format!("Failed to save SVG: {}", e))
+    }
+}
+
+/// Colour key for the area outside the image in a cell grid
+pub(crate) const OUTSIDE: u32 = u32::MAX;
+
+pub(crate) fn colour_key(p: Pixel) -> u32 {
+    (p.r as u32) << 16 | (p.g as u32) << 8 | p.b as u32
+}
+
+pub(crate) fn key_colour(key: u32) -> Pixel {
+    Pixel::new((key >> 16) as u8, (key >> 8) as u8, key as u8)
+}
+
+/// A boundary between two colours of a cell grid, along cell edges from one
+/// junction (a lattice vertex where other than two boundary edges meet) to
+/// the next, or a closed loop without junctions
+pub(crate) struct Boundary {
+    /// Lattice points; cell (x, y) spans (x, y) to (x + 1, y + 1)
+    pub points: Vec<(i32, i32)>,
+    pub closed: bool,
+    /// Colour keys on either side, `OUTSIDE` beyond the frame
+    pub sides: [u32; 2],
+    /// Lattice vertex ids of the two ends
+    pub ends: (usize, usize),
+}
+
+/// All boundaries between differently keyed cells, the frame included
+pub(crate) fn trace_boundaries(cells: &[u32], width: usize, height: usize) -> Vec<Boundary> {
+    let cell = |x: i32, y: i32| {
+        if x < 0 || y < 0 || x >= width as i32 || y >= height as i32 {
+            OUTSIDE
+        } else {
+            cells[y as usize * width + x as usize]
+        }
+    };
+    let vertex = |x: i32, y: i32| y as usize * (width + 1) + x as usize;
+    let horizontal_edges = width * (height + 1);
+
+    // Boundary edges at a lattice vertex: (neighbour, edge id, sides)
+    let edges_at = |x: i32, y: i32| {
+        let mut edges = Vec::with_capacity(4);
+        let mut add = |to: (i32, i32), id: usize, sides: [u32; 2]| {
+            if sides[0] != sides[1] {
+                edges.push((to, id, sides));
+            }
+        };
+        if x < width as i32 {
+            add((x + 1, y), y as usize * width + x as usize, [cell(x, y - 1), cell(x, y)]);
+        }
+        if x > 0 {
+            add((x - 1, y), y as usize * width + x as usize - 1, [cell(x - 1, y - 1), cell(x - 1, y)]);
+        }
+        if y < height as i32 {
+            add((x, y + 1), horizontal_edges + vertex(x, y), [cell(x - 1, y), cell(x, y)]);
+        }
+        if y > 0 {
+            add((x, y - 1), horizontal_edges + vertex(x, y - 1), [cell(x - 1, y - 1), cell(x, y - 1)]);
+        }
+        edges
+    };
+    let junction = |x: i32, y: i32| edges_at(x, y).len() != 2;
+
+    let mut visited = vec![false; horizontal_edges + (width + 1) * height];
+    let mut boundaries = Vec::new();
+    let walk = |start: (i32, i32), visited: &mut Vec<bool>, boundaries: &mut Vec<Boundary>| {
+        for (to, id, sides) in edges_at(start.0, start.1) {
+            if visited[id] {
+                continue;
+            }
+            visited[id] = true;
+            let mut points = vec![start, to];
+            let mut cur = to;
+            while cur != start && !junction(cur.0, cur.1) {
+                let Some((next, id, _)) = edges_at(cur.0, cur.1).into_iter().find(|e| !visited[e.1]) else {
+                    break;
+                };
+                visited[id] = true;
+                points.push(next);
+                cur = next;
+            }
+            boundaries.push(Boundary {
+                points,
+                closed: cur == start && !junction(start.0, start.1),
+                sides,
+                ends: (vertex(start.0, start.1), vertex(cur.0, cur.1)),
+            });
+        }
+    };
+
+    for y in 0..=height as i32 {
+        for x in 0..=width as i32 {
+            if junction(x, y) {
+                walk((x, y), &mut visited, &mut boundaries);
+            }
+        }
+    }
+    // What remains are closed loops without junctions
+    for y in 0..=height as i32 {
+        for x in 0..=width as i32 {
+            walk((x, y), &mut visited, &mut boundaries);
+        }
+    }
+    boundaries
+}
+
+/// One shape per colour key, chaining each boundary's fitted path
+/// (`start`, `segments`) end to end into closed contours
+pub(crate) fn assemble_shapes(boundaries: &[Boundary], paths: &[(Point, Vec<PathSegment>)]) -> Vec<Shape> {
+    let mut by_colour: HashMap<u32, Vec<usize>> = HashMap::new();
+    for (i, boundary) in boundaries.iter().enumerate() {
+        for side in boundary.sides {
+            if side != OUTSIDE {
+                by_colour.entry(side).or_default().push(i);
+            }
+        }
+    }
+    let mut colours: Vec<u32> = by_colour.keys().copied().collect();
+    colours.sort_unstable();
+
+    let mut shapes = Vec::with_capacity(colours.len());
+    for colour in colours {
+        let ids = &by_colour[&colour];
+        let mut at_vertex: HashMap<usize, Vec<usize>> = HashMap::new();
+        for (k, &i) in ids.iter().enumerate() {
+            if !boundaries[i].closed {
+                at_vertex.entry(boundaries[i].ends.0).or_default().push(k);
+                at_vertex.entry(boundaries[i].ends.1).or_default().push(k);
+            }
+        }
+
+        let mut used = vec![false; ids.len()];
+        let mut contours = Vec::new();
+        for k in 0..ids.len() {
+            if used[k] {
+                continue;
+            }
+            used[k] = true;
+            let (first, (start, segments)) = (&boundaries[ids[k]], &paths[ids[k]]);
+            let mut contour = Contour { start: *start, segments: segments.clone() };
+            let (origin, mut end) = first.ends;
+            while !first.closed && end != origin {
+                let Some(j) = at_vertex.get(&end).and_then(|list| list.iter().copied().find(|&j| !used[j])) else {
+                    break;
+                };
+                used[j] = true;
+                let (next, (start, segments)) = (&boundaries[ids[j]], &paths[ids[j]]);
+                if next.ends.0 == end {
+                    contour.segments.extend_from_slice(segments);
+                    end = next.ends.1;
+                } else {
+                    contour.segments.extend(reversed(*start, segments));
+                    end = next.ends.0;
+                }
+            }
+            contours.push(contour);
+        }
+        shapes.push(Shape { fill: key_colour(colour), contours });
+    }
+    shapes
+}
+
+/// The same path traversed backwards, without its new start point
+fn reversed(start: Point, segments: &[PathSegment]) -> Vec<PathSegment> {
+    let mut points = vec![start];
+    points.extend(segments.iter().map(|s| s.end()));
+    segments
+        .iter()
+        .enumerate()
+        .rev()
+        .map(|(i, segment)| match *segment {
+            PathSegment::Line(_) => PathSegment::Line(points[i]),
+            PathSegment::Quad(c, _) => PathSegment::Quad(c, points[i]),
+            PathSegment::Cubic(c1, c2, _) => PathSegment::Cubic(c2, c1, points[i]),
+        })
+        .collect()
+}
+
+/// Where a curve should pass along a traced boundary
+pub(crate) enum Outline {
+    /// A loop without corners, as the midpoints of its straight runs
+    Loop(Vec<Point>),
+    /// Pieces between corners or ends: each starts and ends on one, with the
+    /// run midpoints between; a single straight run is just its two ends
+    Pieces(Vec<Vec<Point>>),
+}
+
+/// Reduce a lattice polyline to corners and run midpoints
+///
+/// The midpoints of a staircase's runs lie on the line it approximates.
+/// Turns of 90° or more between runs of at least `corner_run` units stay
+/// corners; a run of zero makes every turn a corner.
+pub(crate) fn outline(points: &[Point], closed: bool, corner_run: f32) -> Outline {
+    // Only the turning points matter on the lattice
+    let ring: Vec<Point> = if closed { points[..points.len() - 1].to_vec() } else { points.to_vec() };
+    let n = ring.len();
+    let turns = |i: usize| {
+        let (a, b, c) = (ring[(i + n - 1) % n], ring[i], ring[(i + 1) % n]);
+        (b.0 - a.0) * (c.1 - b.1) != (b.1 - a.1) * (c.0 - b.0)
+    };
+    let mut ring: Vec<Point> = (0..n).filter(|&i| (!closed && (i == 0 || i == n - 1)) || turns(i)).map(|i| ring[i]).collect();
+    let n = ring.len();
+    if closed {
+        ring.push(ring[0]);
+    }
+
+    let mid = |a: Point, b: Point| ((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0);
+    let sharp = |a: Point, b: Point, c: Point| {
+        let (d1, d2) = ((b.0 - a.0, b.1 - a.1), (c.0 - b.0, c.1 - b.1));
+        let run = |d: (f32, f32)| (d.0 * d.0 + d.1 * d.1).sqrt();
+        corner_run == 0.0 || d1.0 * d2.0 + d1.1 * d2.1 <= 0.0 && run(d1) >= corner_run && run(d2) >= corner_run
+    };
+
+    let open: Vec<Point> = if closed && n >= 3 {
+        match (0..n).find(|&i| sharp(ring[(i + n - 1) % n], ring[i], ring[i + 1])) {
+            Some(s) => ring[s..n].iter().chain(&ring[..=s]).copied().collect(),
+            None => return Outline::Loop((0..n).map(|i| mid(ring[i], ring[i + 1])).collect()),
+        }
+    } else {
+        ring
+    };
+
+    let m = open.len();
+    let mut pieces = Vec::new();
+    let mut piece = vec![open[0]];
+    for i in 0..m - 1 {
+        piece.push(mid(open[i], open[i + 1]));
+        if i + 2 == m || sharp(open[i], open[i + 1], open[i + 2]) {
+            if piece.len() == 2 {
+                piece.pop();
+            }
+            piece.push(open[i + 1]);
+            pieces.push(std::mem::replace(&mut piece, vec![open[i + 1]]));
+        }
+    }
+    Outline::Pieces(pieces)
+}
+
+/// Coordinates to three decimals without trailing zeros
+fn fmt(v: f32) -> String {
+    let s = format!("{:.3}", v);
+    let s = s.trim_end_matches('0').trim_end_matches('.');
+    if s == "-0" { "0".to_string() } else { s.to_string() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rasterize_and_svg() {
+        // A red square with a square hole, on a blue canvas with a matching hole
+        let square = |(x0, y0): Point, size: f32| Contour {
+            start: (x0, y0),
+            segments: vec![
+                PathSegment::Line((x0 + size, y0)),
+                PathSegment::Line((x0 + size, y0 + size)),
+                PathSegment::Line((x0, y0 + size)),
+            ],
+        };
+        let image = VectorImage {
+            width: 4.0,
+            height: 4.0,
+            shapes: vec![
+                Shape { fill: Pixel::new(0, 0, 255), contours: vec![square((0.0, 0.0), 4.0), square((1.0, 1.0), 2.0)] },
+                Shape { fill: Pixel::new(255, 0, 0), contours: vec![square((1.0, 1.0), 2.0)] },
+            ],
+        };
+
+        let raster = image.rasterize(8, 8);
+        assert_eq!(raster.get_pixel(0, 0), Some(Pixel::new(0, 0, 255)));
+        assert_eq!(raster.get_pixel(3, 4), Some(Pixel::new(255, 0, 0)));
+        assert_eq!(raster.get_pixel(6, 1), Some(Pixel::new(0, 0, 255)));
+
+        let svg = image.to_svg(2.0);
+        assert!(svg.starts_with("<svg") && svg.contains("width=\"8\""));
+        assert!(svg.contains("fill=\"#ff0000\"") && svg.contains("M1 1L3 1L3 3L1 3Z"));
+    }
+}
@@ -1,5 +1,6 @@
 /// Content type detection for smart algorithm selection
 use crate::algorithms::image::{Image, Pixel};
+use crate::algorithms::palette::{extract_palette, MAX_ANALYSIS_PALETTE};
 pub(crate) use crate::content_type::ContentType;
 
 #[derive(Debug, Clone)]
@@ -10,6 +11,8 @@ pub struct ContentAnalysis {
     pub gradient_smoothness: f32,
     pub text_likelihood: f32,
     pub noise_level: f32,
+    /// Exact colours, most common first, when the image has at most 255
+    pub palette: Option<Vec<Pixel>>,
 }
 
 impl ContentAnalysis {
@@ -19,6 +22,7 @@ impl ContentAnalysis {
         let gradient_smoothness = calculate_gradient_smoothness(image);
         let text_likelihood = detect_text_regions(image);
         let noise_level = calculate_noise_level(image);
+        let palette = extract_palette(image, MAX_ANALYSIS_PALETTE);
 
         let content_type = classify_content(
             color_count,
@@ -34,6 +38,7 @@ impl ContentAnalysis {
             gradient_smoothness,
             text_likelihood,
             noise_level,
+            palette,
         }
     }
 
@@ -45,6 +50,9 @@ impl ContentAnalysis {
         println!("     Gradient smooth:   {:.2}", self.gradient_smoothness);
         println!("     Text likelihood:   {:.2}", self.text_likelihood);
         println!("     Noise level:       {:.2}", self.noise_level);
+        if let Some(palette) = &self.palette {
+            println!("     Palette:           {} colors", palette.len());
+        }
         println!("     Recommended algo:  {}", self.content_type.recommended_algorithm());
     }
 }
@@ -25,7 +25,7 @@ impl ContentType {
         match self {
             ContentType::PixelArt => "nearest",
             ContentType::Photography => "lanczos3",
-            ContentType::Text => "nearest",
+            ContentType::Text => "sdf",
             ContentType::Screenshot => "bicubic",
             ContentType::Artwork => "lanczos3",
             ContentType::Mixed => "bicubic",
@@ -1,5 +1,7 @@
 use event_chains::{ChainableEvent, EventContext, EventResult};
 use crate::algorithms::image::Image;
+use crate::algorithms::instant::NearestNeighbor;
+use crate::algorithms::upscaler::Upscaler;
 use crate::event_chain_pipeline::upscale_config::UpscaleConfig;
 
 /// Nearest neighbor upscaling event
@@ -17,22 +19,7 @@ impl ChainableEvent for NearestNeighborEvent {
             None => return EventResult::Failure("No upscale config in context".to_string()),
         };
 
-        let new_width = (image.width as f32 * config.scale_factor).round() as usize;
-        let new_height = (image.height as f32 * config.scale_factor).round() as usize;
-
-        let mut result = Image::new(new_width, new_height);
-
-        for y in 0..new_height {
-            for x in 0..new_width {
-                let src_x = (x as f32 / config.scale_factor).floor() as usize;
-                let src_y = (y as f32 / config.scale_factor).floor() as usize;
-
-                let src_x = src_x.min(image.width - 1);
-                let src_y = src_y.min(image.height - 1);
-
-                result.set_pixel(x, y, image.get_pixel(src_x, src_y).unwrap());
-            }
-        }
+        let result = NearestNeighbor.upscale(&image, config.scale_factor);
 
         context.set("output_image", result);
         EventResult::Success(())
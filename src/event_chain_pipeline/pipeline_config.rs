@@ -1,3 +1,12 @@
+use crate::algorithms::chain::ChainSpec;
+use crate::algorithms::degradation::Psf;
+use crate::algorithms::display::DisplayEmulation;
+use crate::algorithms::instant::IntegerFit;
+use crate::algorithms::ensemble::Fusion;
+use crate::algorithms::sampling::SamplingConfig;
+use crate::algorithms::slow::MultiScaleSchedule;
+use crate::algorithms::supersample::InternalScale;
+
 /// Configuration for the upscaling pipeline
 #[derive(Clone)]
 pub struct PipelineConfig {
@@ -5,6 +14,49 @@ pub struct PipelineConfig {
     pub force_algorithm: Option<String>,
     pub enable_preprocessing: bool,
     pub enable_postprocessing: bool,
+    /// Grid alignment and edge handling; `None` is a centre-aligned grid with clamped borders
+    pub sampling: Option<SamplingConfig>,
+    /// Clockwise rotation in degrees, applied in the same resampling step as the upscale
+    pub rotation: Option<f32>,
+    /// PSF of the degradation model for reconstruction algorithms; `None` keeps their default
+    pub psf: Option<Psf>,
+    /// Coarse-to-fine stages for the iterative reconstruction algorithms
+    pub schedule: Option<MultiScaleSchedule>,
+    /// High-resolution reference images for reference-guided upscaling
+    pub references: Vec<String>,
+    /// Trained model file for learned algorithms (e.g. a RAISR filter bank)
+    pub model_path: Option<String>,
+    /// High-resolution guide image; the output takes its size and edges
+    pub guide_path: Option<String>,
+    /// Spatial (source pixels) and range (0-255) sigmas for guided upsampling
+    pub guide_sigma: Option<(f32, f32)>,
+    /// Bézier fitting tolerance in source pixels for vector tracing
+    pub trace_tolerance: Option<f32>,
+    /// Edge and flat-region algorithms for the edge-map hybrid
+    pub hybrid_algorithms: Option<(String, String)>,
+    /// Gradient thresholds between which the hybrid blends from flat to edge
+    pub hybrid_thresholds: Option<(f32, f32)>,
+    /// Ensemble members by name, each with its weight for mean fusion
+    pub ensemble_members: Option<Vec<(String, f32)>>,
+    /// How the ensemble combines its members' outputs
+    pub fusion: Option<Fusion>,
+    /// Stages of a progressive chain, an algorithm and factor each
+    pub chain: Option<ChainSpec>,
+    /// Render at a higher internal factor, then downsample to the requested size
+    pub supersample: Option<InternalScale>,
+    /// Antialiasing filter for the supersampling downscale; `None` is an area average
+    pub supersample_filter: Option<Psf>,
+    /// What integer-only scaling does with a fractional factor; `None` refuses it
+    pub integer_fit: Option<IntegerFit>,
+    /// Retro display filters (scanlines, phosphor masks, LCD grid) applied to pixel art after upscaling
+    pub display: Option<DisplayEmulation>,
+    /// Snap blending algorithms' output back to the source palette (at most 255 colours);
+    /// `None` snaps pixel art or dithered output only
+    pub preserve_palette: Option<bool>,
+    /// Ordered dithering strength (0-1) for palette snapping; `None` snaps without dithering
+    pub dither: Option<f32>,
+    /// Save as an indexed PNG (or GIF); fails if the output has more than 256 colours
+    pub indexed_output: bool,
 }
 
 impl PipelineConfig {
@@ -14,6 +66,27 @@ impl PipelineConfig {
             force_algorithm: None,
             enable_preprocessing: true,
             enable_postprocessing: true,
+            sampling: None,
+            rotation: None,
+            psf: None,
+            schedule: None,
+            references: Vec::new(),
+            model_path: None,
+            guide_path: None,
+            guide_sigma: None,
+            trace_tolerance: None,
+            hybrid_algorithms: None,
+            hybrid_thresholds: None,
+            ensemble_members: None,
+            fusion: None,
+            chain: None,
+            supersample: None,
+            supersample_filter: None,
+            integer_fit: None,
+            display: None,
+            preserve_palette: None,
+            dither: None,
+            indexed_output: false,
         }
     }
 
@@ -31,4 +104,114 @@ impl PipelineConfig {
         self.enable_postprocessing = enabled;
         self
     }
+
+    pub fn with_sampling(mut self, sampling: SamplingConfig) -> Self {
+        self.sampling = Some(sampling);
+        self
+    }
+
+    pub fn with_rotation(mut self, degrees: f32) -> Self {
+        self.rotation = Some(degrees);
+        self
+    }
+
+    pub fn with_psf(mut self, psf: Psf) -> Self {
+        self.psf = Some(psf);
+        self
+    }
+
+    pub fn with_schedule(mut self, schedule: MultiScaleSchedule) -> Self {
+        self.schedule = Some(schedule);
+        self
+    }
+
+    /// Reference images; without a forced algorithm they select reference-guided upscaling
+    pub fn with_references(mut self, paths: Vec<String>) -> Self {
+        self.references = paths;
+        self
+    }
+
+    pub fn with_model(mut self, path: String) -> Self {
+        self.model_path = Some(path);
+        self
+    }
+
+    /// Guide image; without a forced algorithm it selects joint bilateral upsampling
+    pub fn with_guide(mut self, path: String) -> Self {
+        self.guide_path = Some(path);
+        self
+    }
+
+    pub fn with_guide_sigma(mut self, spatial: f32, range: f32) -> Self {
+        self.guide_sigma = Some((spatial, range));
+        self
+    }
+
+    pub fn with_trace_tolerance(mut self, pixels: f32) -> Self {
+        self.trace_tolerance = Some(pixels);
+        self
+    }
+
+    pub fn with_hybrid_algorithms(mut self, edge: String, flat: String) -> Self {
+        self.hybrid_algorithms = Some((edge, flat));
+        self
+    }
+
+    pub fn with_hybrid_thresholds(mut self, low: f32, high: f32) -> Self {
+        self.hybrid_thresholds = Some((low, high));
+        self
+    }
+
+    pub fn with_ensemble_members(mut self, members: Vec<(String, f32)>) -> Self {
+        self.ensemble_members = Some(members);
+        self
+    }
+
+    pub fn with_fusion(mut self, fusion: Fusion) -> Self {
+        self.fusion = Some(fusion);
+        self
+    }
+
+    /// Chain stages; without a forced algorithm they select the progressive chain
+    pub fn with_chain(mut self, chain: ChainSpec) -> Self {
+        self.chain = Some(chain);
+        self
+    }
+
+    pub fn with_supersample(mut self, internal: InternalScale) -> Self {
+        self.supersample = Some(internal);
+        self
+    }
+
+    pub fn with_supersample_filter(mut self, filter: Psf) -> Self {
+        self.supersample_filter = Some(filter);
+        self
+    }
+
+    pub fn with_integer_fit(mut self, fit: IntegerFit) -> Self {
+        self.integer_fit = Some(fit);
+        self
+    }
+
+    /// Display filters; for pixel art without a forced algorithm they select integer scaling,
+    /// padding fractional factors unless `integer_fit` says otherwise
+    pub fn with_display(mut self, display: DisplayEmulation) -> Self {
+        self.display = Some(display);
+        self
+    }
+
+    pub fn with_palette_preservation(mut self, enabled: bool) -> Self {
+        self.preserve_palette = Some(enabled);
+        self
+    }
+
+    pub fn with_dither(mut self, strength: f32) -> Self {
+        self.dither = Some(strength);
+        self
+    }
+
+    pub fn with_indexed_output(mut self, enabled: bool) -> Self {
+        self.indexed_output = enabled;
+        self
+    }
 }
@@ -1,4 +1,6 @@
 use event_chains::{ChainableEvent, EventContext, EventResult};
+use crate::algorithms::image::Image;
+use crate::content_analysis::{ContentAnalysis, ContentType};
 use crate::event_chain_pipeline::pipeline_config::PipelineConfig;
 
 /// Apply post-processing effects if needed
@@ -22,8 +24,21 @@ impl ChainableEvent for PostProcessImageEvent {
             return EventResult::Success(());
         }
 
-        // For now, post-processing is minimal
-        // Could add: color correction, artifact reduction, etc.
+        // Retro display emulation only makes sense over the blocks of upscaled pixel art
+        if let Some(ref display) = config.display {
+            let pixel_art = context.get::<ContentAnalysis>("content_analysis").is_some_and(|a| a.content_type == ContentType::PixelArt);
+            if pixel_art {
+                let image: Image = match context.get("output_image") {
+                    Some(img) => img,
+                    None => return EventResult::Failure("No output image in context".to_string()),
+                };
+                println!("   Applying display emulation ({} filters)...", display.filters().len());
+                context.set("output_image", display.apply(&image, config.scale_factor));
+            } else {
+                println!("   Display emulation is for pixel art, skipping");
+            }
+        }
+
         println!("   Post-processing complete");
 
         EventResult::Success(())
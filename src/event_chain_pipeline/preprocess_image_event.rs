@@ -1,5 +1,6 @@
 use event_chains::{ChainableEvent, EventContext, EventResult};
 use crate::algorithms::image::Image;
+use crate::algorithms::label::is_label_algorithm;
 use crate::event_chain_pipeline::pipeline_config::PipelineConfig;
 
 /// Apply preprocessing if needed (denoise, sharpen, etc.)
@@ -23,6 +24,18 @@ impl ChainableEvent for PreprocessImageEvent {
             return EventResult::Success(());
         }
 
+        // Denoising and sharpening would blend labels
+        if config.force_algorithm.as_deref().is_some_and(is_label_algorithm) {
+            println!("   Label map, skipping preprocessing");
+            return EventResult::Success(());
+        }
+
+        // Vectorisers need the original flat colours; filtering adds in-between ones
+        if config.force_algorithm.as_deref().is_some_and(|a| crate::get_vectorizer(a).is_some()) {
+            println!("   Flat colours for vectorising, skipping preprocessing");
+            return EventResult::Success(());
+        }
+
         let needs_denoising = context.get::<bool>("needs_denoising").unwrap_or(false);
         let needs_sharpening = context.get::<bool>("needs_sharpening").unwrap_or(false);
 
@@ -83,16 +96,11 @@ fn apply_simple_denoise(image: &Image) -> Image {
                 }
             }
 
-            if count > 0 {
+            if let (Some(r), Some(g), Some(b)) =
+                (r_sum.checked_div(count), g_sum.checked_div(count), b_sum.checked_div(count))
+            {
                 use crate::algorithms::image::Pixel;
-                result.set_pixel(
-                    x, y,
-                    Pixel::new(
-                        (r_sum / count) as u8,
-                        (g_sum / count) as u8,
-                        (b_sum / count) as u8,
-                    )
-                );
+                result.set_pixel(x, y, Pixel::new(r as u8, g as u8, b as u8));
             }
         }
     }
@@ -1,6 +1,9 @@
 use std::path::PathBuf;
 use event_chains::{ChainableEvent, EventContext, EventResult};
 use crate::algorithms::image::Image;
+use crate::algorithms::palette::IndexedImage;
+use crate::algorithms::vector::{is_svg_path, VectorImage};
+use crate::event_chain_pipeline::pipeline_config::PipelineConfig;
 
 /// Save output image to file
 pub struct SaveImageEvent {
@@ -15,11 +18,45 @@ impl SaveImageEvent {
 
 impl ChainableEvent for SaveImageEvent {
     fn execute(&self, context: &mut EventContext) -> EventResult<()> {
+        if is_svg_path(&self.path) {
+            let vector: VectorImage = match context.get("vector_image") {
+                Some(vector) => vector,
+                None => return EventResult::Failure("SVG output needs a vectorising algorithm (depixelize, trace)".to_string()),
+            };
+            let scale = context.get::<PipelineConfig>("config").map_or(1.0, |config| config.scale_factor);
+            return match vector.save_svg(&self.path, scale) {
+                Ok(_) => {
+                    println!("   SVG saved successfully");
+                    EventResult::Success(())
+                }
+                Err(e) => EventResult::Failure(e),
+            };
+        }
+
         let image: Image = match context.get("output_image") {
             Some(img) => img,
             None => return EventResult::Failure("No output image in context".to_string()),
         };
 
+        let indexed_output = context.get::<PipelineConfig>("config").is_some_and(|config| config.indexed_output);
+        let is_gif = self.path.extension().is_some_and(|e| e.eq_ignore_ascii_case("gif"));
+        if indexed_output || is_gif {
+            match IndexedImage::from_image(&image) {
+                Ok(indexed) => {
+                    return match indexed.save(&self.path) {
+                        Ok(_) => {
+                            println!("   Indexed image saved successfully ({} colors)", indexed.palette.len());
+                            EventResult::Success(())
+                        }
+                        Err(e) => EventResult::Failure(e),
+                    };
+                }
+                // Without --indexed a GIF falls back to the encoder's own quantisation
+                Err(e) if indexed_output => return EventResult::Failure(e),
+                Err(_) => {}
+            }
+        }
+
         match image.save(&self.path) {
             Ok(_) => {
                 println!("   Image saved successfully");
@@ -1,10 +1,11 @@
 use event_chains::{ChainableEvent, EventContext, EventResult};
-use crate::algorithms::fast::{Bicubic, Lanczos};
-use crate::algorithms::image::Image;
-use crate::algorithms::instant::{Bilinear, NearestNeighbor};
-use crate::algorithms::slow::IterativeBackProjection;
-use crate::content_analysis::ContentAnalysis;
+use crate::algorithms::image::{Image, Pixel};
+use crate::algorithms::instant::IntegerFit;
+use crate::algorithms::palette::{is_palette_blending, PaletteMapper};
+use crate::algorithms::upscaler::Upscaler;
+use crate::content_analysis::{ContentAnalysis, ContentType};
 use crate::event_chain_pipeline::pipeline_config::PipelineConfig;
+use crate::UpscalePath;
 
 /// Select and apply the optimal upscaling algorithm
 pub struct UpscaleWithStrategyEvent;
@@ -15,6 +16,36 @@ impl UpscaleWithStrategyEvent {
     }
 }
 
+impl UpscaleWithStrategyEvent {
+    /// Run `algorithm_name` on the plain `Upscaler` path
+    fn upscale_generic(&self, algorithm_name: &str, upscaler: &dyn Upscaler, image: &Image, config: &PipelineConfig) -> Image {
+        match algorithm_name.to_lowercase().as_str() {
+            "chain" | "progressive" => {
+                if let Some(ref spec) = config.chain {
+                    let stages: Vec<String> = spec.plan(config.scale_factor).iter()
+                        .map(|(name, factor)| format!("{} {}x", name, factor))
+                        .collect();
+                    println!("   Stages: {}", stages.join(" -> "));
+                }
+            }
+            "reference" | "example_based" => println!("   Reference set: {}", config.references.join(", ")),
+            _ => {}
+        }
+        if let Some(internal) = config.supersample {
+            println!("   Supersampling at {}x", internal.resolve(config.scale_factor));
+        }
+
+        println!("   Upscaling with {} ({}x)...", upscaler.name(), config.scale_factor);
+        if config.sampling.is_some() && !upscaler.supports_sampling() {
+            println!("   {} ignores the sampling grid and edge mode", upscaler.name());
+        }
+        match config.sampling {
+            Some(ref sampling) => upscaler.upscale_with_sampling(image, config.scale_factor, sampling),
+            None => upscaler.upscale(image, config.scale_factor),
+        }
+    }
+}
+
 impl ChainableEvent for UpscaleWithStrategyEvent {
     fn execute(&self, context: &mut EventContext) -> EventResult<()> {
         let image: Image = match context.get("input_image") {
@@ -22,7 +53,7 @@ impl ChainableEvent for UpscaleWithStrategyEvent {
             None => return EventResult::Failure("No input image in context".to_string()),
         };
 
-        let config: PipelineConfig = match context.get("config") {
+        let mut config: PipelineConfig = match context.get("config") {
             Some(cfg) => cfg,
             None => return EventResult::Failure("No config in context".to_string()),
         };
@@ -44,28 +75,96 @@ impl ChainableEvent for UpscaleWithStrategyEvent {
                 println!("   Using {} (user choice, matches recommendation)", forced);
             }
             forced.clone()
+        } else if !config.references.is_empty() {
+            println!("   Using reference (reference images supplied)");
+            "reference".to_string()
+        } else if config.guide_path.is_some() {
+            println!("   Using joint_bilateral (guide image supplied)");
+            "joint_bilateral".to_string()
+        } else if config.chain.is_some() {
+            println!("   Using chain (stages supplied)");
+            "chain".to_string()
+        } else if config.display.is_some() && analysis.content_type == ContentType::PixelArt {
+            println!("   Using integer (display emulation needs evenly sized pixels)");
+            // Pad fractional factors rather than refuse them, unless told otherwise
+            if config.integer_fit.is_none() {
+                config.integer_fit = Some(IntegerFit::Pad(Pixel::black()));
+            }
+            "integer".to_string()
         } else {
             // Auto-select based on analysis
             println!("   Auto-selected: {} (based on {:?})", recommended, analysis.content_type);
             recommended.to_string()
         };
 
-        // Get the upscaler
-        let upscaler: Box<dyn crate::algorithms::upscaler::Upscaler> = match algorithm_name.as_str() {
-            "nearest" => Box::new(NearestNeighbor),
-            "bilinear" => Box::new(Bilinear),
-            "bicubic" => Box::new(Bicubic),
-            "lanczos2" => Box::new(Lanczos::fast()),
-            "lanczos3" => Box::new(Lanczos::new()),
-            "lanczos4" => Box::new(Lanczos::high_quality()),
-            "ibp-fast" => Box::new(IterativeBackProjection::fast()),
-            "ibp" | "ibp-standard" => Box::new(IterativeBackProjection::new()),
-            "ibp-quality" => Box::new(IterativeBackProjection::quality()),
-            _ => return EventResult::Failure(format!("Unknown algorithm: {}", algorithm_name)),
+        let path = match UpscalePath::select(&algorithm_name, &config) {
+            Ok(p) => p,
+            Err(e) => return EventResult::Failure(e),
         };
 
-        println!("   Upscaling with {} ({}x)...", upscaler.name(), config.scale_factor);
-        let result = upscaler.upscale(&image, config.scale_factor);
+        let result = match path {
+            // Rotation is folded into the upscale as a single affine resampling step
+            UpscalePath::Rotate { warp, degrees } => {
+                println!("   Rotating {} degrees and upscaling {}x in one step...", degrees, config.scale_factor);
+                warp.rotate_and_scale(&image, degrees, config.scale_factor)
+            }
+            // Guided upsampling takes its output size from the guide image
+            UpscalePath::Guided { upsampler, guide } => {
+                println!("   Upscaling with {} to the guide's {}x{}...", upsampler.name(), guide.width, guide.height);
+                match upsampler.upsample(&image, &guide) {
+                    Ok(r) => r,
+                    Err(e) => return EventResult::Failure(e),
+                }
+            }
+            // Back-projection reports its residual for every iteration
+            UpscalePath::BackProjection(ibp) => {
+                println!("   Upscaling with {} ({}x)...", ibp.name(), config.scale_factor);
+                let result = ibp.reconstruct(&image, config.scale_factor);
+                println!("   Residual RMSE: {}", result.residual_report());
+                result.image
+            }
+            // Integer-only scaling refuses fractional factors unless told to pad
+            UpscalePath::Integer(sharp) => {
+                println!("   Upscaling with {} ({}x)...", sharp.name(), config.scale_factor);
+                match sharp.try_upscale(&image, config.scale_factor, &config.sampling.unwrap_or_default()) {
+                    Ok(r) => r,
+                    Err(e) => return EventResult::Failure(e),
+                }
+            }
+            // Vectorisers keep their shapes for SVG output
+            UpscalePath::Vectorize(vectorizer) => {
+                println!("   Vectorising with {} ({}x)...", algorithm_name, config.scale_factor);
+                let vector = vectorizer.vectorize(&image);
+                let result = vector.rasterize_scaled(config.scale_factor);
+                println!("   Shapes: {}", vector.shapes.len());
+                context.set("vector_image", vector);
+                result
+            }
+            // The pipeline loads RGB and resamples colours, which would alter label values
+            UpscalePath::Labels(_) => return EventResult::Failure(format!(
+                "{} needs exact label values, which the pipeline's colour processing would change; use --mode=traditional",
+                algorithm_name
+            )),
+            UpscalePath::Upscaler(upscaler) => self.upscale_generic(&algorithm_name, upscaler.as_ref(), &image, &config),
+        };
+
+        // Snap pixel art, or anything when asked, back to the source palette
+        let snap = config.preserve_palette
+            .unwrap_or(analysis.content_type == ContentType::PixelArt || config.dither.is_some());
+        let result = match analysis.palette {
+            Some(palette) if snap && is_palette_blending(&algorithm_name.to_lowercase()) => {
+                let mapper = match PaletteMapper::new(palette) {
+                    Ok(m) => m,
+                    Err(e) => return EventResult::Failure(e),
+                };
+                println!("   Snapped to {}-colour palette", mapper.palette().len());
+                mapper
+                    .with_dither(config.dither.unwrap_or(0.0))
+                    .map(&result)
+                    .to_image()
+            }
+            _ => result,
+        };
 
         println!("   Output size: {}x{}", result.width, result.height);
         context.set("output_image", result);
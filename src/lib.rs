@@ -5,10 +5,10 @@
 //!
 //! ## Algorithm Tiers
 //!
-//! - **Instant**: Nearest neighbor, bilinear (O(n))
-//! - **Fast**: Bicubic, Lanczos (O(n) with higher constants)
-//! - **Medium**: Edge-directed, scale-by-rules (O(n log n))
-//! - **Slow**: Iterative back-projection, TV regularization (O(nÂ²) or iterative)
+//! - **Instant**: Nearest neighbor, bilinear, sharp bilinear, integer scaling (O(n))
+//! - **Fast**: Bicubic, Lanczos, EWA Jinc (O(n) with higher constants)
+//! - **Medium**: Edge-directed, scale-by-rules, DCCI, ICBI, fractal coding, vector tracing, edge-map hybrid, ensembles, progressive chains (O(n log n))
+//! - **Slow**: Iterative back-projection, TV regularization, self-similarity, sparse coding (with a trained dictionary), depixelization (O(nÂ²) or iterative)
 //!
 //! ## Quick Start
 //!
@@ -30,37 +30,277 @@ pub fn get_upscaler(name: &str) -> Option<Box<dyn Upscaler>> {
     match name.to_lowercase().as_str() {
         "nearest" | "nearest_neighbor" => Some(Box::new(instant::NearestNeighbor)),
         "bilinear" => Some(Box::new(instant::Bilinear)),
+        "sharp_bilinear" | "sharp-bilinear" => Some(Box::new(instant::SharpBilinear::new())),
+        "integer" | "integer_scale" => Some(Box::new(instant::SharpBilinear::integer_only(instant::IntegerFit::Refuse))),
+        "label" | "labels" | "mask" => Some(Box::new(LabelUpscaler::new())),
         "bicubic" => Some(Box::new(fast::Bicubic)),
         "lanczos" | "lanczos3" => Some(Box::new(fast::Lanczos::new())),
         "lanczos2" => Some(Box::new(fast::Lanczos::fast())),
         "lanczos4" => Some(Box::new(fast::Lanczos::high_quality())),
+        "ewa" | "ewa_lanczos" => Some(Box::new(fast::EwaLanczos::new())),
+        "ewa_sharp" | "ewa_lanczossharp" => Some(Box::new(fast::EwaLanczos::sharp())),
         "edge_directed" | "edi" => Some(Box::new(medium::EdgeDirected)),
         "scale_by_rules" | "xbr" => Some(Box::new(medium::ScaleByRules)),
+        "dcci" | "directional_cubic" => Some(Box::new(medium::DirectionalCubicConvolution)),
+        "icbi" | "iterative_curvature" => Some(Box::new(medium::IterativeCurvature::new())),
+        "fractal" | "ifs" => Some(Box::new(medium::FractalCoding::new())),
+        "sdf" | "distance_field" => Some(Box::new(SignedDistanceField::new())),
+        "hybrid" | "edge_hybrid" => Some(Box::new(EdgeHybrid::new())),
+        "ensemble" => Some(Box::new(Ensemble::new())),
+        "chain" | "progressive" => Some(Box::new(ProgressiveChain::new())),
         "ibp" | "back_projection" => Some(Box::new(slow::IterativeBackProjection::new())),
         "tv" | "total_variation" => Some(Box::new(slow::TotalVariation::new())),
+        "self_similarity" | "selfsim" => Some(Box::new(example::SelfSimilarity::new())),
+        "depixelize" | "kopf" => Some(Box::new(Depixelize::new())),
+        "trace" | "vector_trace" => Some(Box::new(Tracer::new())),
         _ => None,
     }
 }
 
+/// Get a vectorising algorithm by name, for SVG output
+pub fn get_vectorizer(name: &str) -> Option<Box<dyn Vectorizer>> {
+    match name.to_lowercase().as_str() {
+        "depixelize" | "kopf" => Some(Box::new(Depixelize::new())),
+        "trace" | "vector_trace" => Some(Box::new(Tracer::new())),
+        _ => None,
+    }
+}
+
+/// Build an upscaler by name with the options in `config` applied
+///
+/// Covers every algorithm that runs as a plain `Upscaler`, with the PSF and
+/// other reconstruction options taken from the config, and wraps the result
+/// for supersampling when that is set. Algorithms with their own output path
+/// are chosen by `UpscalePath::select`.
+pub fn build_upscaler(name: &str, config: &PipelineConfig) -> Result<Box<dyn Upscaler>, String> {
+    let name = name.to_lowercase();
+    let upscaler: Box<dyn Upscaler> = if let Some(ibp) = back_projection(&name, config) {
+        Box::new(ibp)
+    } else if let Some(sharp) = sharp_bilinear(&name, config) {
+        Box::new(sharp)
+    } else {
+        match name.as_str() {
+            "tv" | "total_variation" => {
+                let mut tv = slow::TotalVariation::new();
+                if let Some(psf) = config.psf {
+                    tv = tv.with_psf(psf);
+                }
+                if let Some(ref schedule) = config.schedule {
+                    tv = tv.with_schedule(schedule.clone());
+                }
+                Box::new(tv)
+            }
+            "hybrid" | "edge_hybrid" => {
+                let mut hybrid = match config.hybrid_algorithms {
+                    Some((ref edge, ref flat)) => EdgeHybrid::from_names(edge, flat)?,
+                    None => EdgeHybrid::new(),
+                };
+                if let Some((low, high)) = config.hybrid_thresholds {
+                    hybrid = hybrid.with_thresholds(low, high);
+                }
+                Box::new(hybrid)
+            }
+            "ensemble" => {
+                let mut ensemble = match config.ensemble_members {
+                    Some(ref members) => Ensemble::from_members(members)?,
+                    None => Ensemble::new(),
+                };
+                if let Some(fusion) = config.fusion {
+                    ensemble = ensemble.with_fusion(fusion);
+                }
+                if let Some(psf) = config.psf {
+                    ensemble = ensemble.with_psf(psf);
+                }
+                Box::new(ensemble)
+            }
+            "chain" | "progressive" => match config.chain {
+                Some(ref spec) => Box::new(ProgressiveChain::from_spec(spec.clone())?),
+                None => Box::new(ProgressiveChain::new()),
+            },
+            "reference" | "example_based" => Box::new(example::ReferenceGuided::from_files(&config.references)?),
+            "raisr" => {
+                let path = config.model_path.as_ref()
+                    .ok_or("raisr needs a trained filter bank (model path)")?;
+                Box::new(Raisr::from_file(path)?)
+            }
+            "cnn" | "srcnn" | "espcn" | "fsrcnn" => {
+                let path = config.model_path.as_ref()
+                    .ok_or("cnn needs a safetensors weights file (model path)")?;
+                Box::new(NeuralUpscaler::from_file(path)?)
+            }
+            "sparse" | "sparse_coding" => {
+                let path = config.model_path.as_ref()
+                    .ok_or("sparse needs a trained dictionary (model path)")?;
+                Box::new(SparseCoding::from_file(path)?)
+            }
+            other => get_upscaler(other).ok_or_else(|| format!("Unknown algorithm: {}", name))?,
+        }
+    };
+
+    // Optionally render oversized and antialias down to the requested size
+    Ok(match config.supersample {
+        // Downsampling would blend labels into values the input never had
+        Some(_) if is_label_algorithm(&name) => {
+            return Err(format!("{} can't be supersampled: downsampling would blend labels", name));
+        }
+        Some(internal) => {
+            let mut supersample = Supersample::new(upscaler).with_internal_scale(internal);
+            if let Some(filter) = config.supersample_filter {
+                supersample = supersample.with_filter(filter);
+            }
+            Box::new(supersample)
+        }
+        None => upscaler,
+    })
+}
+
+/// Back-projection by name with the configured PSF and schedule
+fn back_projection(name: &str, config: &PipelineConfig) -> Option<slow::IterativeBackProjection> {
+    let mut ibp = slow::IterativeBackProjection::from_name(name)?;
+    if let Some(psf) = config.psf {
+        ibp = ibp.with_psf(psf);
+    }
+    if let Some(ref schedule) = config.schedule {
+        ibp = ibp.with_schedule(schedule.clone());
+    }
+    Some(ibp)
+}
+
+/// Sharp bilinear or integer scaling by name with the configured fit
+fn sharp_bilinear(name: &str, config: &PipelineConfig) -> Option<instant::SharpBilinear> {
+    let sharp = instant::SharpBilinear::from_name(name)?;
+    Some(match config.integer_fit {
+        Some(fit) => sharp.with_fit(fit),
+        None => sharp,
+    })
+}
+
+/// Vectoriser by name with the configured tracing tolerance
+fn configured_vectorizer(name: &str, config: &PipelineConfig) -> Option<Box<dyn Vectorizer>> {
+    match (Tracer::from_name(name), config.trace_tolerance) {
+        (Some(tracer), Some(tolerance)) => Some(Box::new(tracer.with_tolerance(tolerance))),
+        _ => get_vectorizer(name),
+    }
+}
+
+/// How a named algorithm runs for a given configuration
+///
+/// Most algorithms are plain upscalers. The rest need a guide image, report
+/// more than pixels, resample in a different way, can refuse a factor, or
+/// must bypass colour conversion, and each front-end handles those paths
+/// explicitly.
+pub enum UpscalePath {
+    /// Rotate and scale in one affine resampling step
+    Rotate { warp: Warp, degrees: f32 },
+    /// Label maps, loaded and saved without colour conversion
+    Labels(LabelUpscaler),
+    /// Upsampling to the guide image's size
+    Guided { upsampler: GuidedUpsampler, guide: Image },
+    /// Back-projection, which reports its residual for every iteration
+    BackProjection(slow::IterativeBackProjection),
+    /// Sharp bilinear or integer scaling, which can refuse a fractional factor
+    Integer(instant::SharpBilinear),
+    /// Vectorisers, whose shapes can be saved as SVG
+    Vectorize(Box<dyn Vectorizer>),
+    /// Every other algorithm, built by `build_upscaler`
+    Upscaler(Box<dyn Upscaler>),
+}
+
+impl UpscalePath {
+    /// Choose the path for `name`, with every option in `config` applied
+    pub fn select(name: &str, config: &PipelineConfig) -> Result<Self, String> {
+        let name = name.to_lowercase();
+
+        let path = Self::select_path(&name, config)?;
+        match path {
+            UpscalePath::Upscaler(_) => Ok(path),
+            _ if config.supersample.is_some() => Err(format!(
+                "Supersampling only wraps plain upscalers; {} runs on its own path",
+                name
+            )),
+            _ => Ok(path),
+        }
+    }
+
+    /// The path `name` runs on, before checking it against the supersampling option
+    fn select_path(name: &str, config: &PipelineConfig) -> Result<Self, String> {
+        if let Some(degrees) = config.rotation {
+            let interpolation = Interpolation::parse(name).ok_or_else(|| format!(
+                "Rotation needs a resampling kernel (nearest, bilinear, bicubic, lanczos2-4, ewa_lanczos), got {}",
+                name
+            ))?;
+            let background = match config.sampling.map(|s| s.edge_mode) {
+                Some(EdgeMode::Constant(pixel)) => pixel,
+                _ => Pixel::black(),
+            };
+            let warp = Warp::new(interpolation).with_background(background);
+            return Ok(UpscalePath::Rotate { warp, degrees });
+        }
+
+        if is_label_algorithm(name) {
+            return Ok(UpscalePath::Labels(LabelUpscaler::new()));
+        }
+
+        if let Some(ref guide_path) = config.guide_path {
+            let mut upsampler = GuidedUpsampler::from_name(name)
+                .ok_or_else(|| format!("A guide image needs joint_bilateral or guided_filter, got {}", name))?;
+            if let Some((spatial, range)) = config.guide_sigma {
+                upsampler = upsampler.with_spatial_sigma(spatial).with_range_sigma(range);
+            }
+            let guide = Image::load(guide_path).map_err(|e| format!("Failed to load guide: {}", e))?;
+            return Ok(UpscalePath::Guided { upsampler, guide });
+        }
+
+        if let Some(ibp) = back_projection(name, config) {
+            return Ok(UpscalePath::BackProjection(ibp));
+        }
+        if let Some(sharp) = sharp_bilinear(name, config) {
+            return Ok(UpscalePath::Integer(sharp));
+        }
+        if let Some(vectorizer) = configured_vectorizer(name, config) {
+            return Ok(UpscalePath::Vectorize(vectorizer));
+        }
+
+        build_upscaler(name, config).map(UpscalePath::Upscaler)
+    }
+}
+
 /// Get all available upscalers
+///
+/// RAISR, sparse coding and the CNNs need a model file and are only built by
+/// `build_upscaler`.
 pub fn all_upscalers() -> Vec<Box<dyn Upscaler>> {
     vec![
         // Instant
         Box::new(instant::NearestNeighbor),
         Box::new(instant::Bilinear),
+        Box::new(instant::SharpBilinear::new()),
         // Fast
         Box::new(fast::Bicubic),
         Box::new(fast::Lanczos::fast()),
         Box::new(fast::Lanczos::new()),
         Box::new(fast::Lanczos::high_quality()),
+        Box::new(fast::EwaLanczos::new()),
+        Box::new(fast::EwaLanczos::sharp()),
+        Box::new(LabelUpscaler::new()),
         // Medium
         Box::new(medium::EdgeDirected),
         Box::new(medium::ScaleByRules),
+        Box::new(medium::DirectionalCubicConvolution),
+        Box::new(medium::IterativeCurvature::new()),
+        Box::new(medium::FractalCoding::new()),
+        Box::new(SignedDistanceField::new()),
+        Box::new(Tracer::new()),
+        Box::new(EdgeHybrid::new()),
+        Box::new(Ensemble::new()),
+        Box::new(ProgressiveChain::new()),
         // Slow
         Box::new(slow::IterativeBackProjection::fast()),
         Box::new(slow::IterativeBackProjection::new()),
         Box::new(slow::IterativeBackProjection::quality()),
         Box::new(slow::TotalVariation::new()),
+        Box::new(example::SelfSimilarity::new()),
+        Box::new(Depixelize::new()),
     ]
 }
 
@@ -73,8 +313,25 @@ pub fn upscalers_by_tier(tier: UpscaleTier) -> Vec<Box<dyn Upscaler>> {
 }
 
 
-use crate::algorithms::{fast, instant, medium, slow};
+use crate::algorithms::{example, fast, instant, medium, slow};
+use crate::algorithms::chain::ProgressiveChain;
+use crate::algorithms::depixelize::Depixelize;
+use crate::algorithms::ensemble::Ensemble;
+use crate::algorithms::hybrid::EdgeHybrid;
+use crate::algorithms::label::{is_label_algorithm, LabelUpscaler};
+use crate::algorithms::sdf::SignedDistanceField;
+use crate::algorithms::trace::Tracer;
 use crate::algorithms::upscaler::{UpscaleTier, Upscaler};
+use crate::algorithms::vector::Vectorizer;
+use crate::algorithms::guided::GuidedUpsampler;
+use crate::algorithms::image::{Image, Pixel};
+use crate::algorithms::neural::NeuralUpscaler;
+use crate::algorithms::raisr::Raisr;
+use crate::algorithms::sampling::EdgeMode;
+use crate::algorithms::sparse::SparseCoding;
+use crate::algorithms::supersample::Supersample;
+use crate::algorithms::transform::{Interpolation, Warp};
+use crate::event_chain_pipeline::pipeline_config::PipelineConfig;
 
 #[cfg(test)]
 mod tests {
@@ -84,11 +341,54 @@ mod tests {
     fn test_get_upscaler() {
         assert!(get_upscaler("nearest").is_some());
         assert!(get_upscaler("bilinear").is_some());
+        assert!(get_upscaler("sharp_bilinear").is_some());
         assert!(get_upscaler("bicubic").is_some());
         assert!(get_upscaler("lanczos").is_some());
+        assert!(get_upscaler("ewa_lanczos").is_some());
+        assert!(get_upscaler("dcci").is_some());
+        assert!(get_upscaler("icbi").is_some());
+        assert!(get_upscaler("self_similarity").is_some());
+        assert!(get_upscaler("fractal").is_some());
+        assert!(get_upscaler("mask").is_some());
+        assert!(get_upscaler("sdf").is_some());
+        assert!(get_upscaler("depixelize").is_some());
+        assert!(get_upscaler("hybrid").is_some());
+        assert!(get_upscaler("ensemble").is_some());
+        assert!(get_upscaler("chain").is_some());
+        assert!(get_vectorizer("depixelize").is_some());
+        assert!(get_vectorizer("trace").is_some());
+        assert!(get_vectorizer("lanczos3").is_none());
         assert!(get_upscaler("invalid").is_none());
     }
 
+    #[test]
+    fn test_build_upscaler() {
+        let config = PipelineConfig::new(2.0);
+        assert!(build_upscaler("TV", &config).is_ok());
+        assert!(build_upscaler("Lanczos3", &config).is_ok());
+        assert!(build_upscaler("raisr", &config).is_err());
+        assert!(build_upscaler("invalid", &config).is_err());
+
+        assert!(matches!(UpscalePath::select("IBP", &config), Ok(UpscalePath::BackProjection(_))));
+        assert!(matches!(UpscalePath::select("Depixelize", &config), Ok(UpscalePath::Vectorize(_))));
+        assert!(matches!(UpscalePath::select("bicubic", &config), Ok(UpscalePath::Upscaler(_))));
+        let rotated = config.with_rotation(30.0);
+        assert!(matches!(UpscalePath::select("Bicubic", &rotated), Ok(UpscalePath::Rotate { .. })));
+        assert!(UpscalePath::select("tv", &rotated).is_err());
+
+        // Supersampling only wraps plain upscalers, never labels or special paths
+        let supersampled = PipelineConfig::new(3.0).with_supersample(crate::algorithms::supersample::InternalScale::NextPowerOfTwo);
+        match UpscalePath::select("Nearest", &supersampled) {
+            Ok(UpscalePath::Upscaler(upscaler)) => assert!(upscaler.name().starts_with("Supersampled")),
+            _ => panic!("nearest should be a supersampled upscaler"),
+        }
+        assert!(build_upscaler("mask", &supersampled).is_err());
+        for name in ["mask", "depixelize", "ibp", "integer"] {
+            assert!(UpscalePath::select(name, &supersampled).is_err(), "{} accepted supersampling", name);
+        }
+        assert!(UpscalePath::select("bicubic", &supersampled.with_rotation(10.0)).is_err());
+    }
+
     #[test]
     fn test_all_upscalers() {
         let upscalers = all_upscalers();
@@ -2,9 +2,20 @@ use std::env;
 use std::path::Path;
 use std::time::{Duration, Instant};
 use event_chains::{EventChain, EventContext, FaultToleranceMode};
+use image_upscaling::algorithms::chain::ChainSpec;
+use image_upscaling::algorithms::degradation::Psf;
+use image_upscaling::algorithms::display::DisplayEmulation;
+use image_upscaling::algorithms::ensemble::{Ensemble, Fusion};
 use image_upscaling::algorithms::image::Image;
-use image_upscaling::algorithms::prelude::*;
-use image_upscaling::algorithms::slow::IterativeBackProjection;
+use image_upscaling::algorithms::instant::IntegerFit;
+use image_upscaling::algorithms::label::LabelMap;
+use image_upscaling::algorithms::palette::IndexedImage;
+use image_upscaling::algorithms::sampling::{EdgeMode, GridAlignment, SamplingConfig};
+use image_upscaling::algorithms::raisr::RaisrTrainer;
+use image_upscaling::algorithms::slow::MultiScaleSchedule;
+use image_upscaling::algorithms::supersample::InternalScale;
+use image_upscaling::algorithms::sparse::DictionaryTrainer;
+use image_upscaling::algorithms::vector::{is_svg_path, VectorImage};
 use image_upscaling::content_analysis::ContentAnalysis;
 use image_upscaling::event_chain_pipeline::analyze_content_event::AnalyzeContentEvent;
 use image_upscaling::event_chain_pipeline::detect_quality_issues_event::DetectQualityIssuesEvent;
@@ -15,12 +26,14 @@ use image_upscaling::event_chain_pipeline::preprocess_image_event::PreprocessIma
 use image_upscaling::event_chain_pipeline::save_image_event::SaveImageEvent;
 use image_upscaling::event_chain_pipeline::upscale_with_strategy_event::UpscaleWithStrategyEvent;
 use image_upscaling::event_chain_pipeline::validate_image_event::ValidateImageEvent;
+use image_upscaling::UpscalePath;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum ProcessingMode {
     Pipeline,      // Multi-step intelligent pipeline
     Traditional,   // Direct upscaling (NO event chains)
     Compare,       // Compare both approaches
+    Train,         // Fit a learned model from a folder of images
 }
 
 fn print_usage() {
@@ -44,29 +57,117 @@ fn print_usage() {
     println!("  --mode=pipeline     Intelligent multi-step pipeline (NEW, default)");
     println!("  --mode=traditional  Direct upscaling");
     println!("  --mode=compare      Compare both approaches");
+    println!("  --mode=train        Fit a learned model: <input> is a folder of HR images,");
+    println!("                      <output> the model file (--algorithm=raisr or sparse)");
     println!();
 
     println!("Algorithm Selection:");
     println!("  [No --algorithm]    Pipeline: Auto-detect (recommended)");
     println!("                      Others: Use lanczos3 (default)");
     println!("  --algorithm=NAME    Force specific algorithm (all modes)");
+    println!("  --baseline=NAME     Compare mode: also run NAME directly for a side-by-side");
+    println!();
+
+    println!("Sampling Options (all modes):");
+    println!("  --align=MODE        Pixel grid: center, corner, or a phase such as 0.25");
+    println!("  --edge=MODE         Borders: clamp, mirror, wrap (tileable), constant:R,G,B");
+    println!("  --rotate=DEG        Rotate clockwise while upscaling, in one resampling step");
+    println!("                      (kernel algorithms only; background from --edge=constant)");
+    println!();
+
+    println!("Reconstruction Options (ibp, tv):");
+    println!("  --psf=KERNEL        Degradation model: box, bicubic (default), gaussian:SIGMA");
+    println!("  --stages=PLAN       Coarse-to-fine: auto, auto:ITERS, or SCALE:ITERS,... (e.g. 2:10,4:10,8:5)");
+    println!();
+
+    println!("Reference Options (reference):");
+    println!("  --reference=PATHS   High-resolution reference images, comma-separated or repeated");
+    println!();
+
+    println!("Guided Upsampling (joint_bilateral, guided_filter):");
+    println!("  --guide=PATH        High-resolution guide image; the output takes its size");
+    println!("  --sigma=S,R         Spatial sigma in source pixels, range sigma in 0-255 (default 1,25)");
+    println!();
+
+    println!("Vector Output (depixelize, trace):");
+    println!("  OUTPUT.svg          Write the vector shapes instead of a raster");
+    println!("  --tolerance=PX      Bezier fitting tolerance for trace, in source pixels (default 0.5)");
+    println!();
+
+    println!("Edge-Map Hybrid (hybrid):");
+    println!("  --hybrid=EDGE,FLAT  Algorithms for edges and flat regions (default edi,lanczos3)");
+    println!("  --blend=LOW,HIGH    Gradient range over which edges take over (default 48,192)");
+    println!();
+
+    println!("Ensemble (ensemble):");
+    println!("  --ensemble=LIST     Members as NAME[:WEIGHT],... (default bicubic,lanczos3,edi)");
+    println!("  --fusion=RULE       median (default), mean, backprojection[:WINDOW] (uses --psf)");
+    println!();
+
+    println!("Progressive Chain (chain):");
+    println!("  --chain=SPEC        Stages as NAME:FACTOR,...,NAME (the last takes the rest),");
+    println!("                      or auto[:STAGE[,FINISHER]] doubling (default auto:xbr,lanczos3)");
+    println!();
+
+    println!("Supersampling (any algorithm without its own output path):");
+    println!("  --supersample=MODE  Render at integer, pow2 or a fixed factor, then downsample (plain upscalers only)");
+    println!("  --downscale=KERNEL  Downsampling filter: box (area average, default), bicubic, gaussian:SIGMA");
+    println!();
+
+    println!("Integer Scaling (integer):");
+    println!("  --integer=FIT       Fractional factors: refuse (default), pad or pad:R,G,B (letterbox)");
+    println!();
+
+    println!("Palette (images with at most 255 colors):");
+    println!("  --dither[=STRENGTH] Ordered dithering when snapping blends to the palette (0-1, bare flag 1)");
+    println!("  --indexed           Save an indexed PNG or GIF; fails past 256 colors (.gif output tries this anyway)");
+    println!();
+
+    println!("Learned Models (raisr, sparse, cnn):");
+    println!("  --model=PATH        Trained model file to load");
     println!();
 
     println!("Pipeline-Only Options:");
     println!("  --no-preprocess     Disable preprocessing");
     println!("  --no-postprocess    Disable post-processing");
+    println!("  --display=FILTERS   Retro display look for pixel art: crt, or a list of");
+    println!("                      scanlines[:S], aperture[:S], shadowmask[:S], lcd[:S], bloom[:RADIUS[:S]]");
+    println!("  --palette           Snap blends to the source palette for any content (default: pixel art only)");
+    println!("  --no-palette        Keep blended colors instead of snapping to the source palette");
     println!();
 
     println!("Available Algorithms:");
     println!("  nearest      Nearest Neighbor (fastest, pixel-perfect)");
     println!("  bilinear     Bilinear Interpolation (fast, smooth)");
+    println!("  sharp_bilinear  Integer nearest prescale, bilinear for the rest (crisp retro pixels)");
+    println!("  integer      Whole-factor block copies only (see --integer)");
+    println!("  mask         Label maps and masks: smooth boundaries, only input values (traditional mode)");
     println!("  bicubic      Bicubic Interpolation (balanced)");
     println!("  lanczos2     Lanczos2 (sharp, fast)");
     println!("  lanczos3     Lanczos3 (sharpest, recommended)");
     println!("  lanczos4     Lanczos4 (maximum quality)");
+    println!("  ewa_lanczos  EWA Jinc-windowed Jinc (radially symmetric, no diagonal jaggies)");
+    println!("  ewa_sharp    EWA Lanczos with mpv's sharp blur");
+    println!("  dcci         Directional Cubic Convolution (diagonal edges)");
+    println!("  icbi         Iterative Curvature-Based Interpolation");
+    println!("  fractal      Fractal (IFS) coding, decoded at the target size");
+    println!("  sdf          Signed distance field (crisp text and line art at 4x-16x)");
+    println!("  hybrid       Edge-directed on edges, Lanczos elsewhere, blended per pixel");
+    println!("  ensemble     Runs several algorithms and fuses their outputs");
+    println!("  chain        A different algorithm per stage, e.g. xbr 2x, xbr 2x, lanczos3 (large factors)");
     println!("  ibp-fast     Iterative Back-Projection Fast (5 iterations)");
     println!("  ibp          Iterative Back-Projection Standard (10 iterations)");
     println!("  ibp-quality  Iterative Back-Projection Quality (20 iterations)");
+    println!("  tv           Total Variation super-resolution (primal-dual)");
+    println!("  selfsim      Self-similarity SR (detail from the image's own pyramid)");
+    println!("  reference    Example-based SR from --reference images");
+    println!("  raisr        RAISR hashed learned filters (needs --model=BANK)");
+    println!("  sparse       Sparse-coding dictionary SR (needs --model=DICTIONARY)");
+    println!("  jbu          Joint bilateral upsampling to a --guide image (joint_bilateral)");
+    println!("  guided       Guided-filter upsampling to a --guide image (guided_filter)");
+    println!("  cnn          SRCNN/ESPCN/FSRCNN inference (needs --model=WEIGHTS.safetensors)");
+    println!("  trace        Vector tracing of flat-colour logos and line art (.svg output)");
+    println!("  depixelize   Pixel-art vectorisation with Kopf-Lischinski Voronoi cell reshaping (top quality for pixel art; .svg output)");
     println!();
 
     println!("Examples:");
@@ -82,30 +183,56 @@ fn print_usage() {
     println!("  # Compare both modes");
     println!("  cargo run input.jpg output.png --mode=compare");
     println!();
+    println!("  # Train RAISR filters on your own photos, then use them");
+    println!("  cargo run ./photos filters.raisr 2 --mode=train --algorithm=raisr");
+    println!("  cargo run input.jpg output.png 2 --algorithm=raisr --model=filters.raisr");
+    println!("  cargo run input.jpg output.png 2 --algorithm=cnn --model=espcn_x2.safetensors");
+    println!();
+    println!("  # Compare EWA against Lanczos in one run");
+    println!("  cargo run input.jpg output.png --mode=compare --algorithm=ewa_lanczos --baseline=lanczos3");
+    println!();
 }
 
-fn get_traditional_upscaler(algorithm: &str) -> Result<Box<dyn image_upscaling::algorithms::upscaler::Upscaler>, String> {
+fn train_model(folder: &str, output_path: &str, algorithm: &str, scale_factor: f32) -> Result<(), String> {
+    let scale = scale_factor.round() as usize;
+
+    println!();
+    println!("Training mode...");
+    println!("   Algorithm: {}", algorithm);
+    println!("   Folder:    {}", folder);
+    println!("   Scale:     {}x", scale);
+    println!();
+
+    let start = Instant::now();
     match algorithm.to_lowercase().as_str() {
-        "nearest" => Ok(Box::new(NearestNeighbor)),
-        "bilinear" => Ok(Box::new(Bilinear)),
-        "bicubic" => Ok(Box::new(Bicubic)),
-        "lanczos2" => Ok(Box::new(Lanczos::fast())),
-        "lanczos3" => Ok(Box::new(Lanczos::new())),
-        "lanczos4" => Ok(Box::new(Lanczos::high_quality())),
-        "ibp-fast" => Ok(Box::new(IterativeBackProjection::fast())),
-        "ibp" | "ibp-standard" => Ok(Box::new(IterativeBackProjection::new())),
-        "ibp-quality" => Ok(Box::new(IterativeBackProjection::quality())),
-        _ => Err(format!("Unknown algorithm: {}", algorithm)),
+        "raisr" => {
+            if !(2..=4).contains(&scale) {
+                return Err("RAISR filters can be trained for 2x, 3x or 4x".to_string());
+            }
+            let bank = RaisrTrainer::new(scale).train_folder(folder)?;
+            bank.save(output_path)?;
+            println!("   Strength thresholds:  {:?}", bank.strength_thresholds());
+            println!("   Coherence thresholds: {:?}", bank.coherence_thresholds());
+        }
+        "sparse" | "sparse_coding" => {
+            if !(2..=4).contains(&scale) {
+                return Err("Sparse-coding dictionaries can be trained for 2x, 3x or 4x".to_string());
+            }
+            let dictionary = DictionaryTrainer::new(scale).train_folder(folder)?;
+            dictionary.save(output_path)?;
+            println!("   Atoms: {} ({}x{} patches)", dictionary.atoms(), dictionary.patch_size, dictionary.patch_size);
+        }
+        other => return Err(format!("No trainer for algorithm: {}", other)),
     }
+
+    println!("   Saved {} in {:.3}s", output_path, start.elapsed().as_secs_f64());
+    Ok(())
 }
 
 fn process_with_pipeline(
     input_path: &str,
     output_path: &str,
-    scale_factor: f32,
-    force_algorithm: Option<String>,
-    enable_preprocessing: bool,
-    enable_postprocessing: bool,
+    config: PipelineConfig,
 ) -> Result<(Image, Duration), String> {
     println!();
     println!("Building intelligent pipeline...");
@@ -113,14 +240,6 @@ fn process_with_pipeline(
     let metrics = event_chains::middleware::metrics::MetricsMiddleware::new();
     let metrics_clone = metrics.clone();
 
-    // Create pipeline configuration
-    let mut config = PipelineConfig::new(scale_factor);
-    if let Some(algo) = force_algorithm {
-        config = config.with_algorithm(algo);
-    }
-    config = config.with_preprocessing(enable_preprocessing);
-    config = config.with_postprocessing(enable_postprocessing);
-
     // Build multi-phase pipeline
     let pipeline = EventChain::new()
         // Infrastructure middleware
@@ -208,8 +327,13 @@ fn process_traditional(
     input_path: &str,
     output_path: &str,
     algorithm_name: &str,
-    scale_factor: f32,
+    config: &PipelineConfig,
 ) -> Result<(Image, Duration), String> {
+    let scale_factor = config.scale_factor;
+    if is_svg_path(output_path) && image_upscaling::get_vectorizer(algorithm_name).is_none() {
+        return Err(format!("SVG output needs a vectorising algorithm (depixelize, trace), got {}", algorithm_name));
+    }
+
     println!();
     println!("Traditional mode (direct processing)...");
     println!("   Algorithm: {}", algorithm_name);
@@ -224,14 +348,61 @@ fn process_traditional(
     let load_duration = start_load.elapsed();
     println!("   Loaded {}x{} in {:.3}s", image.width, image.height, load_duration.as_secs_f64());
 
-    // Get upscaler
-    let upscaler = get_traditional_upscaler(algorithm_name)?;
-
     // Upscale
     println!();
     println!("Upscaling...");
     let start_upscale = Instant::now();
-    let output_image = upscaler.upscale(&image, scale_factor);
+    let mut label_output: Option<LabelMap> = None;
+    let mut vector_output: Option<VectorImage> = None;
+    let output_image = match UpscalePath::select(algorithm_name, config)? {
+        UpscalePath::Rotate { warp, degrees } => {
+            // Rotate and upscale in a single resampling step
+            println!("   Rotating {} degrees", degrees);
+            warp.rotate_and_scale(&image, degrees, scale_factor)
+        }
+        UpscalePath::Labels(upscaler) => {
+            // Reload without colour conversion so IDs and palette indices stay exact
+            let labels = LabelMap::load(input_path)?;
+            println!("   Labels: {:?}", labels.format);
+            let upscaled = upscaler.upscale_labels(&labels, scale_factor);
+            let image = upscaled.to_image();
+            label_output = Some(upscaled);
+            image
+        }
+        UpscalePath::Guided { upsampler, guide } => {
+            // The guide sets the output size
+            println!("   Guide: {}x{}", guide.width, guide.height);
+            upsampler.upsample(&image, &guide)?
+        }
+        UpscalePath::BackProjection(ibp) => {
+            let result = ibp.reconstruct(&image, scale_factor);
+            println!("   Residual RMSE: {}", result.residual_report());
+            result.image
+        }
+        UpscalePath::Integer(sharp) => {
+            sharp.try_upscale(&image, scale_factor, &config.sampling.unwrap_or_default())?
+        }
+        UpscalePath::Vectorize(vectorizer) => {
+            // Keep the shapes for SVG output
+            let vector = vectorizer.vectorize(&image);
+            println!("   Shapes: {}", vector.shapes.len());
+            let image = vector.rasterize_scaled(scale_factor);
+            vector_output = Some(vector);
+            image
+        }
+        UpscalePath::Upscaler(upscaler) => {
+            if let Some(internal) = config.supersample {
+                println!("   Supersampling at {}x", internal.resolve(scale_factor));
+            }
+            if config.sampling.is_some() && !upscaler.supports_sampling() {
+                println!("   {} ignores the sampling grid and edge mode", upscaler.name());
+            }
+            match config.sampling {
+                Some(ref sampling) => upscaler.upscale_with_sampling(&image, scale_factor, sampling),
+                None => upscaler.upscale(&image, scale_factor),
+            }
+        }
+    };
     let upscale_duration = start_upscale.elapsed();
     println!("   Upscaled to {}x{} in {:.3}s",
              output_image.width, output_image.height, upscale_duration.as_secs_f64());
@@ -240,8 +411,13 @@ fn process_traditional(
     println!();
     println!("Saving image...");
     let start_save = Instant::now();
-    output_image.save(output_path)
-        .map_err(|e| format!("Failed to save image: {}", e))?;
+    match (label_output, vector_output) {
+        (Some(ref labels), _) => labels.save(output_path)?,
+        (None, Some(ref vector)) if is_svg_path(output_path) => vector.save_svg(output_path, scale_factor)?,
+        _ if config.indexed_output => IndexedImage::from_image(&output_image)?.save(output_path)?,
+        _ => output_image.save(output_path)
+            .map_err(|e| format!("Failed to save image: {}", e))?,
+    }
     let save_duration = start_save.elapsed();
     println!("   Saved in {:.3}s", save_duration.as_secs_f64());
 
@@ -257,8 +433,8 @@ fn process_traditional(
 fn compare_modes(
     input_path: &str,
     output_path: &str,
-    scale_factor: f32,
-    force_algorithm: Option<String>,
+    config: PipelineConfig,
+    baseline_algorithm: Option<String>,
 ) -> Result<(), String> {
     println!();
     println!("===============================================================");
@@ -275,7 +451,7 @@ fn compare_modes(
     analysis.print_summary();
 
     // Use forced algorithm if provided, otherwise use recommendation
-    let algorithm_to_use = force_algorithm.clone()
+    let algorithm_to_use = config.force_algorithm.clone()
         .unwrap_or_else(|| analysis.content_type.recommended_algorithm().to_string());
 
     println!();
@@ -293,16 +469,16 @@ fn compare_modes(
         input_path,
         &trad_output,
         &algorithm_to_use,
-        scale_factor,
+        &config,
     );
 
-    let trad_duration = match trad_result {
-        Ok((_, dur)) => Some(dur),
+    let (trad_image, trad_duration) = match trad_result {
+        Ok((img, dur)) => (Some(img), Some(dur)),
         Err(e) => {
             println!();
             println!("Traditional mode skipped: {}", e);
             println!();
-            None
+            (None, None)
         }
     };
 
@@ -321,10 +497,9 @@ fn compare_modes(
     let pipe_result = process_with_pipeline(
         input_path,
         &pipe_output,
-        scale_factor,
-        force_algorithm.clone(),  // Pass the forced algorithm!
-        true,
-        true,
+        config.clone()  // Keeps the forced algorithm!
+            .with_preprocessing(true)
+            .with_postprocessing(true),
     );
 
     let pipe_duration = match pipe_result {
@@ -337,6 +512,28 @@ fn compare_modes(
         }
     };
 
+    // Optionally run a second algorithm directly for a side-by-side comparison
+    let baseline_output = format!("{}_baseline.png",
+                                  Path::new(output_path).file_stem().unwrap().to_str().unwrap());
+
+    let baseline_result = baseline_algorithm.as_ref().and_then(|baseline_algo| {
+        println!();
+        println!("---------------------------------------------------------------");
+        println!();
+        println!("TEST 3: Baseline Algorithm ({})", baseline_algo);
+        println!("---------------------------------------------------------------");
+
+        match process_traditional(input_path, &baseline_output, baseline_algo, &config) {
+            Ok(result) => Some(result),
+            Err(e) => {
+                println!();
+                println!("Baseline algorithm skipped: {}", e);
+                println!();
+                None
+            }
+        }
+    });
+
     // Comparison summary - only if we have at least one result
     if trad_duration.is_none() && pipe_duration.is_none() {
         return Err("All modes failed or were skipped".to_string());
@@ -377,9 +574,33 @@ fn compare_modes(
         println!("| Pipeline          | SKIPPED      | ---          | N/A        |");
     }
 
+    if let Some((_, base_dur)) = &baseline_result {
+        let overhead = (base_dur.as_secs_f64() / baseline.as_secs_f64() - 1.0) * 100.0;
+        let overhead_str = if overhead > 0.0 {
+            format!("+{:.1}%", overhead)
+        } else {
+            format!("{:.1}%", overhead)
+        };
+        println!("| Baseline algorithm     | {:>9.3}s | {:>12} | {}       |",
+                 base_dur.as_secs_f64(), overhead_str, Path::new(&baseline_output).file_name().unwrap().to_str().unwrap());
+    }
+
     println!("|------------------------+--------------+--------------+------------|");
     println!();
 
+    if let (Some(trad_img), Some((base_img, _))) = (&trad_image, &baseline_result)
+        && trad_img.width == base_img.width
+        && trad_img.height == base_img.height
+    {
+        let total_diff: u64 = trad_img.pixels.iter().zip(&base_img.pixels)
+            .map(|(a, b)| (a.r.abs_diff(b.r) as u64) + (a.g.abs_diff(b.g) as u64) + (a.b.abs_diff(b.b) as u64))
+            .sum();
+        let mean_diff = total_diff as f64 / (trad_img.pixels.len() * 3).max(1) as f64;
+        println!("Algorithm difference ({} vs {}):", algorithm_to_use, baseline_algorithm.as_deref().unwrap_or(""));
+        println!("   Mean absolute difference: {:.2} per channel", mean_diff);
+        println!();
+    }
+
     println!("Key Insights:");
     if let Some(trad_dur) = trad_duration {
         println!("  * Traditional: Baseline (no overhead, no features)");
@@ -444,31 +665,206 @@ fn main() {
     let mut scale_factor = 2.0f32;
     let mut mode = ProcessingMode::Pipeline;
     let mut force_algorithm: Option<String> = None;
+    let mut baseline_algorithm: Option<String> = None;
     let mut enable_preprocessing = true;
     let mut enable_postprocessing = true;
+    let mut alignment: Option<GridAlignment> = None;
+    let mut edge_mode: Option<EdgeMode> = None;
+    let mut rotation: Option<f32> = None;
+    let mut psf: Option<Psf> = None;
+    let mut schedule: Option<MultiScaleSchedule> = None;
+    let mut references: Vec<String> = Vec::new();
+    let mut model_path: Option<String> = None;
+    let mut guide_path: Option<String> = None;
+    let mut guide_sigma: Option<(f32, f32)> = None;
+    let mut trace_tolerance: Option<f32> = None;
+    let mut hybrid_algorithms: Option<(String, String)> = None;
+    let mut hybrid_thresholds: Option<(f32, f32)> = None;
+    let mut ensemble_members: Option<Vec<(String, f32)>> = None;
+    let mut fusion: Option<Fusion> = None;
+    let mut chain: Option<ChainSpec> = None;
+    let mut supersample: Option<InternalScale> = None;
+    let mut supersample_filter: Option<Psf> = None;
+    let mut integer_fit: Option<IntegerFit> = None;
+    let mut display: Option<DisplayEmulation> = None;
+    let mut preserve_palette: Option<bool> = None;
+    let mut dither: Option<f32> = None;
+    let mut indexed_output = false;
 
     for arg in args.iter().skip(3) {
-        if arg.starts_with("--mode=") {
-            let mode_str = &arg[7..];
+        if let Some(mode_str) = arg.strip_prefix("--mode=") {
             mode = match mode_str.to_lowercase().as_str() {
                 "pipeline" => ProcessingMode::Pipeline,
                 "traditional" | "direct" => ProcessingMode::Traditional,
                 "compare" | "comparison" => ProcessingMode::Compare,
+                "train" => ProcessingMode::Train,
+                _ => {
+                    eprintln!("Error: Unknown mode '{}'. Use 'pipeline', 'traditional', 'compare', or 'train'", mode_str);
+                    std::process::exit(1);
+                }
+            };
+        } else if let Some(algorithm) = arg.strip_prefix("--algorithm=") {
+            force_algorithm = Some(algorithm.to_string());
+        } else if let Some(algorithm) = arg.strip_prefix("--baseline=") {
+            baseline_algorithm = Some(algorithm.to_string());
+        } else if let Some(align_str) = arg.strip_prefix("--align=") {
+            alignment = match GridAlignment::parse(align_str) {
+                Some(a) => Some(a),
+                None => {
+                    eprintln!("Error: Unknown alignment '{}'. Use 'center', 'corner', or a phase like 0.25", align_str);
+                    std::process::exit(1);
+                }
+            };
+        } else if let Some(edge_str) = arg.strip_prefix("--edge=") {
+            edge_mode = match EdgeMode::parse(edge_str) {
+                Some(e) => Some(e),
+                None => {
+                    eprintln!("Error: Unknown edge mode '{}'. Use 'clamp', 'mirror', 'wrap', or 'constant:R,G,B'", edge_str);
+                    std::process::exit(1);
+                }
+            };
+        } else if let Some(degrees) = arg.strip_prefix("--rotate=") {
+            rotation = match degrees.parse::<f32>() {
+                Ok(d) => Some(d),
+                Err(_) => {
+                    eprintln!("Error: Invalid rotation '{}'. Use degrees, e.g. --rotate=1.5", degrees);
+                    std::process::exit(1);
+                }
+            };
+        } else if let Some(psf_str) = arg.strip_prefix("--psf=") {
+            psf = match Psf::parse(psf_str) {
+                Some(p) => Some(p),
+                None => {
+                    eprintln!("Error: Unknown PSF '{}'. Use 'box', 'bicubic', or 'gaussian:SIGMA'", psf_str);
+                    std::process::exit(1);
+                }
+            };
+        } else if let Some(stages) = arg.strip_prefix("--stages=") {
+            schedule = match MultiScaleSchedule::parse(stages) {
+                Some(s) => Some(s),
+                None => {
+                    eprintln!("Error: Invalid stages '{}'. Use 'auto', 'auto:ITERS', or SCALE:ITERS pairs like 2:10,4:10,8:5", stages);
+                    std::process::exit(1);
+                }
+            };
+        } else if let Some(paths) = arg.strip_prefix("--reference=") {
+            references.extend(paths.split(',').filter(|p| !p.is_empty()).map(str::to_string));
+        } else if let Some(path) = arg.strip_prefix("--model=") {
+            model_path = Some(path.to_string());
+        } else if let Some(path) = arg.strip_prefix("--guide=") {
+            guide_path = Some(path.to_string());
+        } else if let Some(sigmas) = arg.strip_prefix("--sigma=") {
+            guide_sigma = match sigmas.split_once(',').map(|(s, r)| (s.parse::<f32>(), r.parse::<f32>())) {
+                Some((Ok(s), Ok(r))) if s > 0.0 && r > 0.0 => Some((s, r)),
+                _ => {
+                    eprintln!("Error: Invalid sigmas '{}'. Use SPATIAL,RANGE, e.g. --sigma=1.5,20", sigmas);
+                    std::process::exit(1);
+                }
+            };
+        } else if let Some(tolerance) = arg.strip_prefix("--tolerance=") {
+            trace_tolerance = match tolerance.parse::<f32>() {
+                Ok(t) if t > 0.0 => Some(t),
                 _ => {
-                    eprintln!("Error: Unknown mode '{}'. Use 'pipeline', 'traditional', or 'compare'", mode_str);
+                    eprintln!("Error: Invalid tolerance '{}'. Use a positive number of pixels, e.g. --tolerance=0.8", tolerance);
+                    std::process::exit(1);
+                }
+            };
+        } else if let Some(algorithms) = arg.strip_prefix("--hybrid=") {
+            hybrid_algorithms = match algorithms.split_once(',') {
+                Some((edge, flat)) if !edge.is_empty() && !flat.is_empty() => Some((edge.to_string(), flat.to_string())),
+                _ => {
+                    eprintln!("Error: Invalid hybrid '{}'. Use EDGE,FLAT, e.g. --hybrid=xbr,bicubic", algorithms);
+                    std::process::exit(1);
+                }
+            };
+        } else if let Some(thresholds) = arg.strip_prefix("--blend=") {
+            hybrid_thresholds = match thresholds.split_once(',').map(|(l, h)| (l.parse::<f32>(), h.parse::<f32>())) {
+                Some((Ok(l), Ok(h))) if l >= 0.0 && h > l => Some((l, h)),
+                _ => {
+                    eprintln!("Error: Invalid blend '{}'. Use LOW,HIGH gradients with LOW < HIGH, e.g. --blend=30,120", thresholds);
+                    std::process::exit(1);
+                }
+            };
+        } else if let Some(members) = arg.strip_prefix("--ensemble=") {
+            ensemble_members = match Ensemble::parse_members(members) {
+                Some(m) if m.len() >= 2 => Some(m),
+                _ => {
+                    eprintln!("Error: Invalid ensemble '{}'. Use two or more NAME[:WEIGHT], e.g. --ensemble=lanczos3:2,edi,xbr", members);
+                    std::process::exit(1);
+                }
+            };
+        } else if let Some(rule) = arg.strip_prefix("--fusion=") {
+            fusion = match Fusion::parse(rule) {
+                Some(f) => Some(f),
+                None => {
+                    eprintln!("Error: Unknown fusion '{}'. Use 'median', 'mean', or 'backprojection[:WINDOW]'", rule);
+                    std::process::exit(1);
+                }
+            };
+        } else if let Some(spec) = arg.strip_prefix("--chain=") {
+            chain = match ChainSpec::parse(spec) {
+                Some(c) => Some(c),
+                None => {
+                    eprintln!("Error: Invalid chain '{}'. Use 'auto', 'auto:STAGE,FINISHER', or stages like xbr:2,xbr:2,lanczos3", spec);
+                    std::process::exit(1);
+                }
+            };
+        } else if let Some(mode) = arg.strip_prefix("--supersample=") {
+            supersample = match InternalScale::parse(mode) {
+                Some(s) => Some(s),
+                None => {
+                    eprintln!("Error: Invalid supersampling '{}'. Use 'integer', 'pow2', or a factor like 4", mode);
+                    std::process::exit(1);
+                }
+            };
+        } else if let Some(kernel) = arg.strip_prefix("--downscale=") {
+            supersample_filter = match Psf::parse(kernel) {
+                Some(p) => Some(p),
+                None => {
+                    eprintln!("Error: Unknown downscale filter '{}'. Use 'box', 'bicubic', or 'gaussian:SIGMA'", kernel);
                     std::process::exit(1);
                 }
             };
-        } else if arg.starts_with("--algorithm=") {
-            force_algorithm = Some(arg[12..].to_string());
+        } else if let Some(fit) = arg.strip_prefix("--integer=") {
+            integer_fit = match IntegerFit::parse(fit) {
+                Some(f) => Some(f),
+                None => {
+                    eprintln!("Error: Unknown integer fit '{}'. Use 'refuse', 'pad', or 'pad:R,G,B'", fit);
+                    std::process::exit(1);
+                }
+            };
+        } else if let Some(filters) = arg.strip_prefix("--display=") {
+            display = match DisplayEmulation::parse(filters) {
+                Some(d) => Some(d),
+                None => {
+                    eprintln!("Error: Invalid display filters '{}'. Use 'crt' or e.g. scanlines:0.6,aperture,bloom:1.5:0.2", filters);
+                    std::process::exit(1);
+                }
+            };
+        } else if arg == "--dither" {
+            dither = Some(1.0);
+        } else if let Some(strength) = arg.strip_prefix("--dither=") {
+            dither = match strength.parse::<f32>() {
+                Ok(s) if (0.0..=1.0).contains(&s) => Some(s),
+                _ => {
+                    eprintln!("Error: Invalid dither strength '{}'. Use a value from 0 to 1", strength);
+                    std::process::exit(1);
+                }
+            };
+        } else if arg == "--indexed" {
+            indexed_output = true;
+        } else if arg == "--palette" {
+            preserve_palette = Some(true);
+        } else if arg == "--no-palette" {
+            preserve_palette = Some(false);
         } else if arg == "--no-preprocess" {
             enable_preprocessing = false;
         } else if arg == "--no-postprocess" {
             enable_postprocessing = false;
-        } else if !arg.starts_with("--") {
-            if let Ok(val) = arg.parse::<f32>() {
-                scale_factor = val;
-            }
+        } else if !arg.starts_with("--")
+            && let Ok(val) = arg.parse::<f32>()
+        {
+            scale_factor = val;
         }
     }
 
@@ -493,9 +889,111 @@ fn main() {
         println!("   Algorithm:  {} (forced)", algo);
     }
 
+    // Build the shared configuration
+    let mut config = PipelineConfig::new(scale_factor)
+        .with_preprocessing(enable_preprocessing)
+        .with_postprocessing(enable_postprocessing);
+    if let Some(ref algo) = force_algorithm {
+        config = config.with_algorithm(algo.clone());
+    }
+    if alignment.is_some() || edge_mode.is_some() {
+        let mut sampling = SamplingConfig::new();
+        if let Some(a) = alignment {
+            sampling = sampling.with_alignment(a);
+        }
+        if let Some(e) = edge_mode {
+            sampling = sampling.with_edge_mode(e);
+        }
+        println!("   Sampling:   {:?} grid, {:?} edges", sampling.alignment, sampling.edge_mode);
+        config = config.with_sampling(sampling);
+    }
+    if let Some(degrees) = rotation {
+        println!("   Rotation:   {} degrees", degrees);
+        config = config.with_rotation(degrees);
+    }
+    if let Some(p) = psf {
+        println!("   PSF:        {:?}", p);
+        config = config.with_psf(p);
+    }
+    if let Some(s) = schedule {
+        println!("   Stages:     {:?}", s.plan(scale_factor));
+        config = config.with_schedule(s);
+    }
+    if !references.is_empty() {
+        println!("   References: {}", references.join(", "));
+        config = config.with_references(references.clone());
+    }
+    if let Some(ref path) = model_path {
+        println!("   Model:      {}", path);
+        config = config.with_model(path.clone());
+    }
+    if let Some(ref path) = guide_path {
+        println!("   Guide:      {}", path);
+        config = config.with_guide(path.clone());
+    }
+    if let Some((spatial, range)) = guide_sigma {
+        config = config.with_guide_sigma(spatial, range);
+    }
+    if let Some(tolerance) = trace_tolerance {
+        config = config.with_trace_tolerance(tolerance);
+    }
+    if let Some((ref edge, ref flat)) = hybrid_algorithms {
+        println!("   Hybrid:     {} on edges, {} elsewhere", edge, flat);
+        config = config.with_hybrid_algorithms(edge.clone(), flat.clone());
+    }
+    if let Some((low, high)) = hybrid_thresholds {
+        config = config.with_hybrid_thresholds(low, high);
+    }
+    if let Some(ref members) = ensemble_members {
+        let names: Vec<String> = members.iter().map(|(name, weight)| format!("{}:{}", name, weight)).collect();
+        println!("   Ensemble:   {}", names.join(", "));
+        config = config.with_ensemble_members(members.clone());
+    }
+    if let Some(f) = fusion {
+        println!("   Fusion:     {:?}", f);
+        config = config.with_fusion(f);
+    }
+    if let Some(c) = chain {
+        let stages: Vec<String> = c.plan(scale_factor).iter().map(|(name, factor)| format!("{} {}x", name, factor)).collect();
+        println!("   Chain:      {}", stages.join(" -> "));
+        config = config.with_chain(c);
+    }
+    if let Some(s) = supersample {
+        println!("   Supersample: {}x, {:?} downscale", s.resolve(scale_factor), supersample_filter.unwrap_or(Psf::Box));
+        config = config.with_supersample(s);
+    }
+    if let Some(f) = supersample_filter {
+        config = config.with_supersample_filter(f);
+    }
+    if let Some(f) = integer_fit {
+        config = config.with_integer_fit(f);
+    }
+    if let Some(d) = display {
+        println!("   Display:    {:?}", d.filters());
+        config = config.with_display(d);
+    }
+    if let Some(p) = preserve_palette {
+        println!("   Palette:    {}", if p { "preserved" } else { "not preserved" });
+        config = config.with_palette_preservation(p);
+    }
+    if let Some(d) = dither {
+        println!("   Dither:     {}", d);
+        config = config.with_dither(d);
+    }
+    if indexed_output {
+        println!("   Indexed:    yes");
+        config = config.with_indexed_output(true);
+    }
+
     // For non-pipeline modes, default to lanczos3 if no algorithm specified
     let default_algo = force_algorithm.clone().unwrap_or_else(|| {
-        if mode != ProcessingMode::Pipeline {
+        if mode == ProcessingMode::Train {
+            "raisr".to_string()
+        } else if mode != ProcessingMode::Pipeline && !references.is_empty() {
+            "reference".to_string()
+        } else if mode != ProcessingMode::Pipeline && guide_path.is_some() {
+            "joint_bilateral".to_string()
+        } else if mode != ProcessingMode::Pipeline {
             "lanczos3".to_string()
         } else {
             String::new()
@@ -505,25 +1003,16 @@ fn main() {
     // Execute based on mode
     let result = match mode {
         ProcessingMode::Pipeline => {
-            process_with_pipeline(
-                input_path,
-                output_path,
-                scale_factor,
-                force_algorithm,
-                enable_preprocessing,
-                enable_postprocessing,
-            ).map(|_| ())
+            process_with_pipeline(input_path, output_path, config).map(|_| ())
         }
         ProcessingMode::Traditional => {
-            process_traditional(
-                input_path,
-                output_path,
-                &default_algo,
-                scale_factor,
-            ).map(|_| ())
+            process_traditional(input_path, output_path, &default_algo, &config).map(|_| ())
         }
         ProcessingMode::Compare => {
-            compare_modes(input_path, output_path, scale_factor, force_algorithm)
+            compare_modes(input_path, output_path, config, baseline_algorithm)
+        }
+        ProcessingMode::Train => {
+            train_model(input_path, output_path, &default_algo, scale_factor)
         }
     };
 